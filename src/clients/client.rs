@@ -1,12 +1,31 @@
-use std::sync::Arc;
+use std::{collections::HashMap, error::Error, sync::Arc, time::Duration};
 
 use parking_lot::RwLock;
 
 use crate::{
-    Counter, DataType, DatatypeState, IntoString, clients::datatype_manager::DatatypeManager,
-    datatypes::DatatypeSet, errors::clients::ClientError, types::uid::Cuid,
+    Counter, DataType, DatatypeState, Document, IntoString, List, Map, Set, Text, Variable,
+    clients::{
+        datatype_manager::DatatypeManager,
+        storage::Storage,
+        sync_manager::{DEFAULT_SYNC_INTERVAL, SyncManager},
+        transport::Transport,
+    },
+    datatypes::{
+        DatatypeSet,
+        datatype::{Datatype, DatatypeBlanket},
+        rollback::{
+            DEFAULT_CLIENT_ROLLBACK_BUDGET, DEFAULT_DATATYPE_ROLLBACK_BUDGET, RollbackBudget,
+        },
+        transactional::TransactionContext,
+        undo::DEFAULT_UNDO_DEPTH,
+    },
+    errors::{clients::ClientError, err},
+    types::uid::{Cuid, Uid},
+    utils::{runtime::run_blocking, unwind::catch_unwind},
 };
 
+const CLIENT_RUNTIME_GROUP: &str = "client";
+
 /// A builder for constructing a [`Client`].
 ///
 /// Use [`Client::builder`] to start, then call [`ClientBuilder::build`]
@@ -23,31 +42,146 @@ pub struct ClientBuilder {
     collection: String,
     alias: String,
     cuid: Cuid,
+    transport: Option<Arc<dyn Transport>>,
+    sync_interval: Duration,
+    storage: Option<Arc<dyn Storage>>,
+    undo_depth: usize,
+    rollback_budget: (usize, usize),
+    lock_timeout: Option<Duration>,
 }
 
 impl ClientBuilder {
+    /// Sets the cuid of the client; a new cuid is generated by default.
+    ///
+    /// A client rebuilt with the same cuid and [`Storage`] restores the datatypes
+    /// stored by the previous one.
+    pub fn with_cuid(mut self, cuid: Cuid) -> Self {
+        self.cuid = cuid;
+        self
+    }
+
+    /// Sets the [`Storage`] in which the client persists its datatypes.
+    pub fn with_storage(mut self, storage: Arc<dyn Storage>) -> Self {
+        self.storage = Some(storage);
+        self
+    }
+
+    /// Sets the [`Transport`] through which the client synchronizes its datatypes.
+    ///
+    /// Without a transport, the client works offline and [`Client::sync`] fails.
+    pub fn with_transport(mut self, transport: Arc<dyn Transport>) -> Self {
+        self.transport = Some(transport);
+        self
+    }
+
+    /// Sets the interval of the background synchronization; the default is 100ms.
+    pub fn with_sync_interval(mut self, interval: Duration) -> Self {
+        self.sync_interval = interval;
+        self
+    }
+
+    /// Sets the maximum number of steps of each datatype that can be undone; the
+    /// default is 100, and 0 disables undo.
+    pub fn with_undo_depth(mut self, depth: usize) -> Self {
+        self.undo_depth = depth;
+        self
+    }
+
+    /// Sets the memory budgets, in bytes, for the history of transactions that each
+    /// datatype keeps to roll back and for the histories of all datatypes of the client;
+    /// the defaults are 1MiB and 64MiB.
+    ///
+    /// The transactions acknowledged by the server are dropped from the history when they
+    /// are confirmed. When a budget is exceeded, the whole history of the datatype that
+    /// commits, executes, or synchronizes a transaction is checkpointed; the transactions
+    /// yet to be pushed are then kept apart until the server acknowledges them, and are no
+    /// longer accounted in the budgets.
+    pub fn with_rollback_budget(mut self, per_datatype: usize, per_client: usize) -> Self {
+        self.rollback_budget = (per_datatype, per_client);
+        self
+    }
+
+    /// Sets how long an operation or a transaction of each datatype waits for another
+    /// transaction of the datatype to end; by default, it waits as long as it takes.
+    ///
+    /// Once the timeout elapses, it fails with
+    /// [`DatatypeError::LockTimeout`](crate::DatatypeError::LockTimeout). The timeout can
+    /// be overridden for a handle by, e.g., [`Counter::with_lock_timeout`], whereas the
+    /// transactions of other clients pulled from the server are never timed out.
+    pub fn with_lock_timeout(mut self, timeout: Duration) -> Self {
+        self.lock_timeout = Some(timeout);
+        self
+    }
+
     /// Finalizes the builder and returns a new [`Client`].
     ///
     /// It initializes client metadata and datatype management structures.
+    /// If a storage is set, the datatypes stored by the client with the same cuid
+    /// are restored. If a transport is set, it also connects to the server and starts
+    /// the background synchronization.
     pub fn build(self) -> Result<Client, ClientError> {
         let client_info = Arc::new(ClientInfo {
             collection: self.collection.into_boxed_str(),
             cuid: self.cuid,
             alias: self.alias.into_boxed_str(),
+            storage: self.storage,
+            undo_depth: self.undo_depth,
+            rollback_budget: Arc::new(RollbackBudget::new(
+                self.rollback_budget.0,
+                self.rollback_budget.1,
+            )),
+            lock_timeout: self.lock_timeout,
         });
+        let mut datatype_manager = DatatypeManager::new(client_info.clone());
+        if let Some(storage) = &client_info.storage {
+            for (key, records) in storage.load(&client_info.collection, &client_info.cuid)? {
+                datatype_manager.restore_datatype(&key, records)?;
+            }
+        }
+        let datatypes = Arc::new(RwLock::new(datatype_manager));
+        let sync_manager = match self.transport {
+            Some(transport) => {
+                let sm = Arc::new(SyncManager::new(
+                    client_info.clone(),
+                    transport,
+                    datatypes.clone(),
+                ));
+                sm.start(self.sync_interval)?;
+                Some(sm)
+            }
+            None => None,
+        };
 
         Ok(Client {
             info: client_info.clone(),
-            datatypes: RwLock::new(DatatypeManager::new(client_info.clone())),
+            datatypes,
+            sync_manager,
         })
     }
 }
 
-#[derive(Default)]
 pub struct ClientInfo {
     pub collection: Box<str>,
     pub cuid: Cuid,
     pub alias: Box<str>,
+    pub storage: Option<Arc<dyn Storage>>,
+    pub undo_depth: usize,
+    pub rollback_budget: Arc<RollbackBudget>,
+    pub lock_timeout: Option<Duration>,
+}
+
+impl Default for ClientInfo {
+    fn default() -> Self {
+        Self {
+            collection: Default::default(),
+            cuid: Default::default(),
+            alias: Default::default(),
+            storage: Default::default(),
+            undo_depth: DEFAULT_UNDO_DEPTH,
+            rollback_budget: Default::default(),
+            lock_timeout: None,
+        }
+    }
 }
 
 /// Facade for creating and subscribing to SyncYam datatypes.
@@ -60,7 +194,8 @@ pub struct ClientInfo {
 /// helpers to get specific datatypes.
 pub struct Client {
     info: Arc<ClientInfo>,
-    datatypes: RwLock<DatatypeManager>,
+    datatypes: Arc<RwLock<DatatypeManager>>,
+    sync_manager: Option<Arc<SyncManager>>,
 }
 
 impl Client {
@@ -78,6 +213,15 @@ impl Client {
             collection: collection.into(),
             alias: alias.into(),
             cuid: Cuid::new(),
+            transport: None,
+            sync_interval: DEFAULT_SYNC_INTERVAL,
+            storage: None,
+            undo_depth: DEFAULT_UNDO_DEPTH,
+            rollback_budget: (
+                DEFAULT_DATATYPE_ROLLBACK_BUDGET,
+                DEFAULT_CLIENT_ROLLBACK_BUDGET,
+            ),
+            lock_timeout: None,
         }
     }
 
@@ -92,6 +236,23 @@ impl Client {
             .subscribe_or_create_datatype(&key, r#type, state)
     }
 
+    /// Runs [`Self::subscribe_or_create_datatype`] off the executor of the caller,
+    /// because it can block on the lock of the datatypes and on the storage.
+    async fn subscribe_or_create_datatype_async(
+        &self,
+        key: String,
+        r#type: DataType,
+        state: DatatypeState,
+    ) -> Result<DatatypeSet, ClientError> {
+        let datatypes = self.datatypes.clone();
+        run_blocking(CLIENT_RUNTIME_GROUP, move || {
+            datatypes
+                .write()
+                .subscribe_or_create_datatype(&key, r#type, state)
+        })
+        .await
+    }
+
     /// Subscribes to an existing `Counter` identified by `key`.
     ///
     /// If the datatype does not yet exist locally, it is registered
@@ -132,12 +293,713 @@ impl Client {
         .map(|ds| Ok(ds.ensure_counter().unwrap()))?
     }
 
+    /// The async version of [`Client::subscribe_counter`].
+    pub async fn subscribe_counter_async(
+        &self,
+        key: impl IntoString,
+    ) -> Result<Counter, ClientError> {
+        self.subscribe_or_create_datatype_async(
+            key.into(),
+            DataType::Counter,
+            DatatypeState::DueToSubscribe,
+        )
+        .await
+        .map(|ds| ds.ensure_counter().unwrap())
+    }
+
+    /// The async version of [`Client::create_counter`].
+    pub async fn create_counter_async(&self, key: impl IntoString) -> Result<Counter, ClientError> {
+        self.subscribe_or_create_datatype_async(
+            key.into(),
+            DataType::Counter,
+            DatatypeState::DueToCreate,
+        )
+        .await
+        .map(|ds| ds.ensure_counter().unwrap())
+    }
+
+    /// The async version of [`Client::subscribe_or_create_counter`].
+    pub async fn subscribe_or_create_counter_async(
+        &self,
+        key: impl IntoString,
+    ) -> Result<Counter, ClientError> {
+        self.subscribe_or_create_datatype_async(
+            key.into(),
+            DataType::Counter,
+            DatatypeState::DueToSubscribeOrCreate,
+        )
+        .await
+        .map(|ds| ds.ensure_counter().unwrap())
+    }
+
+    /// Subscribes to an existing `Variable` identified by `key`.
+    ///
+    /// If the datatype does not yet exist locally, it is registered
+    /// with [`DatatypeState::DueToSubscribe`].
+    pub fn subscribe_variable(&self, key: impl IntoString) -> Result<Variable, ClientError> {
+        self.subscribe_or_create_datatype(
+            key.into(),
+            DataType::Variable,
+            DatatypeState::DueToSubscribe,
+        )
+        .map(|ds| Ok(ds.ensure_variable().unwrap()))?
+    }
+
+    /// Creates a `Variable` identified by `key`.
+    ///
+    /// If the datatype already exists with a compatible state, the
+    /// existing handle is returned. New instances are marked with
+    /// [`DatatypeState::DueToCreate`].
+    pub fn create_variable(&self, key: impl IntoString) -> Result<Variable, ClientError> {
+        self.subscribe_or_create_datatype(
+            key.into(),
+            DataType::Variable,
+            DatatypeState::DueToCreate,
+        )
+        .map(|ds| Ok(ds.ensure_variable().unwrap()))?
+    }
+
+    /// Ensures a `Variable` exists by subscribing or creating it.
+    ///
+    /// The returned instance is marked with
+    /// [`DatatypeState::DueToSubscribeOrCreate`].
+    pub fn subscribe_or_create_variable(
+        &self,
+        key: impl IntoString,
+    ) -> Result<Variable, ClientError> {
+        self.subscribe_or_create_datatype(
+            key.into(),
+            DataType::Variable,
+            DatatypeState::DueToSubscribeOrCreate,
+        )
+        .map(|ds| Ok(ds.ensure_variable().unwrap()))?
+    }
+
+    /// The async version of [`Client::subscribe_variable`].
+    pub async fn subscribe_variable_async(
+        &self,
+        key: impl IntoString,
+    ) -> Result<Variable, ClientError> {
+        self.subscribe_or_create_datatype_async(
+            key.into(),
+            DataType::Variable,
+            DatatypeState::DueToSubscribe,
+        )
+        .await
+        .map(|ds| ds.ensure_variable().unwrap())
+    }
+
+    /// The async version of [`Client::create_variable`].
+    pub async fn create_variable_async(
+        &self,
+        key: impl IntoString,
+    ) -> Result<Variable, ClientError> {
+        self.subscribe_or_create_datatype_async(
+            key.into(),
+            DataType::Variable,
+            DatatypeState::DueToCreate,
+        )
+        .await
+        .map(|ds| ds.ensure_variable().unwrap())
+    }
+
+    /// The async version of [`Client::subscribe_or_create_variable`].
+    pub async fn subscribe_or_create_variable_async(
+        &self,
+        key: impl IntoString,
+    ) -> Result<Variable, ClientError> {
+        self.subscribe_or_create_datatype_async(
+            key.into(),
+            DataType::Variable,
+            DatatypeState::DueToSubscribeOrCreate,
+        )
+        .await
+        .map(|ds| ds.ensure_variable().unwrap())
+    }
+
+    /// Subscribes to an existing `List` identified by `key`.
+    ///
+    /// If the datatype does not yet exist locally, it is registered
+    /// with [`DatatypeState::DueToSubscribe`].
+    pub fn subscribe_list(&self, key: impl IntoString) -> Result<List, ClientError> {
+        self.subscribe_or_create_datatype(key.into(), DataType::List, DatatypeState::DueToSubscribe)
+            .map(|ds| Ok(ds.ensure_list().unwrap()))?
+    }
+
+    /// Creates a `List` identified by `key`.
+    ///
+    /// If the datatype already exists with a compatible state, the
+    /// existing handle is returned. New instances are marked with
+    /// [`DatatypeState::DueToCreate`].
+    pub fn create_list(&self, key: impl IntoString) -> Result<List, ClientError> {
+        self.subscribe_or_create_datatype(key.into(), DataType::List, DatatypeState::DueToCreate)
+            .map(|ds| Ok(ds.ensure_list().unwrap()))?
+    }
+
+    /// Ensures a `List` exists by subscribing or creating it.
+    ///
+    /// The returned instance is marked with
+    /// [`DatatypeState::DueToSubscribeOrCreate`].
+    pub fn subscribe_or_create_list(&self, key: impl IntoString) -> Result<List, ClientError> {
+        self.subscribe_or_create_datatype(
+            key.into(),
+            DataType::List,
+            DatatypeState::DueToSubscribeOrCreate,
+        )
+        .map(|ds| Ok(ds.ensure_list().unwrap()))?
+    }
+
+    /// The async version of [`Client::subscribe_list`].
+    pub async fn subscribe_list_async(&self, key: impl IntoString) -> Result<List, ClientError> {
+        self.subscribe_or_create_datatype_async(
+            key.into(),
+            DataType::List,
+            DatatypeState::DueToSubscribe,
+        )
+        .await
+        .map(|ds| ds.ensure_list().unwrap())
+    }
+
+    /// The async version of [`Client::create_list`].
+    pub async fn create_list_async(&self, key: impl IntoString) -> Result<List, ClientError> {
+        self.subscribe_or_create_datatype_async(
+            key.into(),
+            DataType::List,
+            DatatypeState::DueToCreate,
+        )
+        .await
+        .map(|ds| ds.ensure_list().unwrap())
+    }
+
+    /// The async version of [`Client::subscribe_or_create_list`].
+    pub async fn subscribe_or_create_list_async(
+        &self,
+        key: impl IntoString,
+    ) -> Result<List, ClientError> {
+        self.subscribe_or_create_datatype_async(
+            key.into(),
+            DataType::List,
+            DatatypeState::DueToSubscribeOrCreate,
+        )
+        .await
+        .map(|ds| ds.ensure_list().unwrap())
+    }
+
+    /// Subscribes to an existing `Map` identified by `key`.
+    ///
+    /// If the datatype does not yet exist locally, it is registered
+    /// with [`DatatypeState::DueToSubscribe`].
+    pub fn subscribe_map(&self, key: impl IntoString) -> Result<Map, ClientError> {
+        self.subscribe_or_create_datatype(key.into(), DataType::Map, DatatypeState::DueToSubscribe)
+            .map(|ds| Ok(ds.ensure_map().unwrap()))?
+    }
+
+    /// Creates a `Map` identified by `key`.
+    ///
+    /// If the datatype already exists with a compatible state, the
+    /// existing handle is returned. New instances are marked with
+    /// [`DatatypeState::DueToCreate`].
+    pub fn create_map(&self, key: impl IntoString) -> Result<Map, ClientError> {
+        self.subscribe_or_create_datatype(key.into(), DataType::Map, DatatypeState::DueToCreate)
+            .map(|ds| Ok(ds.ensure_map().unwrap()))?
+    }
+
+    /// Ensures a `Map` exists by subscribing or creating it.
+    ///
+    /// The returned instance is marked with
+    /// [`DatatypeState::DueToSubscribeOrCreate`].
+    pub fn subscribe_or_create_map(&self, key: impl IntoString) -> Result<Map, ClientError> {
+        self.subscribe_or_create_datatype(
+            key.into(),
+            DataType::Map,
+            DatatypeState::DueToSubscribeOrCreate,
+        )
+        .map(|ds| Ok(ds.ensure_map().unwrap()))?
+    }
+
+    /// The async version of [`Client::subscribe_map`].
+    pub async fn subscribe_map_async(&self, key: impl IntoString) -> Result<Map, ClientError> {
+        self.subscribe_or_create_datatype_async(
+            key.into(),
+            DataType::Map,
+            DatatypeState::DueToSubscribe,
+        )
+        .await
+        .map(|ds| ds.ensure_map().unwrap())
+    }
+
+    /// The async version of [`Client::create_map`].
+    pub async fn create_map_async(&self, key: impl IntoString) -> Result<Map, ClientError> {
+        self.subscribe_or_create_datatype_async(
+            key.into(),
+            DataType::Map,
+            DatatypeState::DueToCreate,
+        )
+        .await
+        .map(|ds| ds.ensure_map().unwrap())
+    }
+
+    /// The async version of [`Client::subscribe_or_create_map`].
+    pub async fn subscribe_or_create_map_async(
+        &self,
+        key: impl IntoString,
+    ) -> Result<Map, ClientError> {
+        self.subscribe_or_create_datatype_async(
+            key.into(),
+            DataType::Map,
+            DatatypeState::DueToSubscribeOrCreate,
+        )
+        .await
+        .map(|ds| ds.ensure_map().unwrap())
+    }
+
+    /// Subscribes to an existing `Document` identified by `key`.
+    ///
+    /// If the datatype does not yet exist locally, it is registered
+    /// with [`DatatypeState::DueToSubscribe`].
+    pub fn subscribe_document(&self, key: impl IntoString) -> Result<Document, ClientError> {
+        self.subscribe_or_create_datatype(
+            key.into(),
+            DataType::Document,
+            DatatypeState::DueToSubscribe,
+        )
+        .map(|ds| Ok(ds.ensure_document().unwrap()))?
+    }
+
+    /// Creates a `Document` identified by `key`.
+    ///
+    /// If the datatype already exists with a compatible state, the
+    /// existing handle is returned. New instances are marked with
+    /// [`DatatypeState::DueToCreate`].
+    pub fn create_document(&self, key: impl IntoString) -> Result<Document, ClientError> {
+        self.subscribe_or_create_datatype(
+            key.into(),
+            DataType::Document,
+            DatatypeState::DueToCreate,
+        )
+        .map(|ds| Ok(ds.ensure_document().unwrap()))?
+    }
+
+    /// Ensures a `Document` exists by subscribing or creating it.
+    ///
+    /// The returned instance is marked with
+    /// [`DatatypeState::DueToSubscribeOrCreate`].
+    pub fn subscribe_or_create_document(
+        &self,
+        key: impl IntoString,
+    ) -> Result<Document, ClientError> {
+        self.subscribe_or_create_datatype(
+            key.into(),
+            DataType::Document,
+            DatatypeState::DueToSubscribeOrCreate,
+        )
+        .map(|ds| Ok(ds.ensure_document().unwrap()))?
+    }
+
+    /// The async version of [`Client::subscribe_document`].
+    pub async fn subscribe_document_async(
+        &self,
+        key: impl IntoString,
+    ) -> Result<Document, ClientError> {
+        self.subscribe_or_create_datatype_async(
+            key.into(),
+            DataType::Document,
+            DatatypeState::DueToSubscribe,
+        )
+        .await
+        .map(|ds| ds.ensure_document().unwrap())
+    }
+
+    /// The async version of [`Client::create_document`].
+    pub async fn create_document_async(
+        &self,
+        key: impl IntoString,
+    ) -> Result<Document, ClientError> {
+        self.subscribe_or_create_datatype_async(
+            key.into(),
+            DataType::Document,
+            DatatypeState::DueToCreate,
+        )
+        .await
+        .map(|ds| ds.ensure_document().unwrap())
+    }
+
+    /// The async version of [`Client::subscribe_or_create_document`].
+    pub async fn subscribe_or_create_document_async(
+        &self,
+        key: impl IntoString,
+    ) -> Result<Document, ClientError> {
+        self.subscribe_or_create_datatype_async(
+            key.into(),
+            DataType::Document,
+            DatatypeState::DueToSubscribeOrCreate,
+        )
+        .await
+        .map(|ds| ds.ensure_document().unwrap())
+    }
+
+    /// Subscribes to an existing `Text` identified by `key`.
+    ///
+    /// If the datatype does not yet exist locally, it is registered
+    /// with [`DatatypeState::DueToSubscribe`].
+    pub fn subscribe_text(&self, key: impl IntoString) -> Result<Text, ClientError> {
+        self.subscribe_or_create_datatype(key.into(), DataType::Text, DatatypeState::DueToSubscribe)
+            .map(|ds| Ok(ds.ensure_text().unwrap()))?
+    }
+
+    /// Creates a `Text` identified by `key`.
+    ///
+    /// If the datatype already exists with a compatible state, the
+    /// existing handle is returned. New instances are marked with
+    /// [`DatatypeState::DueToCreate`].
+    pub fn create_text(&self, key: impl IntoString) -> Result<Text, ClientError> {
+        self.subscribe_or_create_datatype(key.into(), DataType::Text, DatatypeState::DueToCreate)
+            .map(|ds| Ok(ds.ensure_text().unwrap()))?
+    }
+
+    /// Ensures a `Text` exists by subscribing or creating it.
+    ///
+    /// The returned instance is marked with
+    /// [`DatatypeState::DueToSubscribeOrCreate`].
+    pub fn subscribe_or_create_text(&self, key: impl IntoString) -> Result<Text, ClientError> {
+        self.subscribe_or_create_datatype(
+            key.into(),
+            DataType::Text,
+            DatatypeState::DueToSubscribeOrCreate,
+        )
+        .map(|ds| Ok(ds.ensure_text().unwrap()))?
+    }
+
+    /// The async version of [`Client::subscribe_text`].
+    pub async fn subscribe_text_async(&self, key: impl IntoString) -> Result<Text, ClientError> {
+        self.subscribe_or_create_datatype_async(
+            key.into(),
+            DataType::Text,
+            DatatypeState::DueToSubscribe,
+        )
+        .await
+        .map(|ds| ds.ensure_text().unwrap())
+    }
+
+    /// The async version of [`Client::create_text`].
+    pub async fn create_text_async(&self, key: impl IntoString) -> Result<Text, ClientError> {
+        self.subscribe_or_create_datatype_async(
+            key.into(),
+            DataType::Text,
+            DatatypeState::DueToCreate,
+        )
+        .await
+        .map(|ds| ds.ensure_text().unwrap())
+    }
+
+    /// The async version of [`Client::subscribe_or_create_text`].
+    pub async fn subscribe_or_create_text_async(
+        &self,
+        key: impl IntoString,
+    ) -> Result<Text, ClientError> {
+        self.subscribe_or_create_datatype_async(
+            key.into(),
+            DataType::Text,
+            DatatypeState::DueToSubscribeOrCreate,
+        )
+        .await
+        .map(|ds| ds.ensure_text().unwrap())
+    }
+
+    /// Subscribes to an existing `Set` identified by `key`.
+    ///
+    /// If the datatype does not yet exist locally, it is registered
+    /// with [`DatatypeState::DueToSubscribe`].
+    pub fn subscribe_set(&self, key: impl IntoString) -> Result<Set, ClientError> {
+        self.subscribe_or_create_datatype(key.into(), DataType::Set, DatatypeState::DueToSubscribe)
+            .map(|ds| Ok(ds.ensure_set().unwrap()))?
+    }
+
+    /// Creates a `Set` identified by `key`.
+    ///
+    /// If the datatype already exists with a compatible state, the
+    /// existing handle is returned. New instances are marked with
+    /// [`DatatypeState::DueToCreate`].
+    pub fn create_set(&self, key: impl IntoString) -> Result<Set, ClientError> {
+        self.subscribe_or_create_datatype(key.into(), DataType::Set, DatatypeState::DueToCreate)
+            .map(|ds| Ok(ds.ensure_set().unwrap()))?
+    }
+
+    /// Ensures a `Set` exists by subscribing or creating it.
+    ///
+    /// The returned instance is marked with
+    /// [`DatatypeState::DueToSubscribeOrCreate`].
+    pub fn subscribe_or_create_set(&self, key: impl IntoString) -> Result<Set, ClientError> {
+        self.subscribe_or_create_datatype(
+            key.into(),
+            DataType::Set,
+            DatatypeState::DueToSubscribeOrCreate,
+        )
+        .map(|ds| Ok(ds.ensure_set().unwrap()))?
+    }
+
+    /// The async version of [`Client::subscribe_set`].
+    pub async fn subscribe_set_async(&self, key: impl IntoString) -> Result<Set, ClientError> {
+        self.subscribe_or_create_datatype_async(
+            key.into(),
+            DataType::Set,
+            DatatypeState::DueToSubscribe,
+        )
+        .await
+        .map(|ds| ds.ensure_set().unwrap())
+    }
+
+    /// The async version of [`Client::create_set`].
+    pub async fn create_set_async(&self, key: impl IntoString) -> Result<Set, ClientError> {
+        self.subscribe_or_create_datatype_async(
+            key.into(),
+            DataType::Set,
+            DatatypeState::DueToCreate,
+        )
+        .await
+        .map(|ds| ds.ensure_set().unwrap())
+    }
+
+    /// The async version of [`Client::subscribe_or_create_set`].
+    pub async fn subscribe_or_create_set_async(
+        &self,
+        key: impl IntoString,
+    ) -> Result<Set, ClientError> {
+        self.subscribe_or_create_datatype_async(
+            key.into(),
+            DataType::Set,
+            DatatypeState::DueToSubscribeOrCreate,
+        )
+        .await
+        .map(|ds| ds.ensure_set().unwrap())
+    }
+
     /// Returns an existing datatype by `key`, if it has been created or
     /// subscribed via this client.
     pub fn get_datatype(&self, key: &str) -> Option<DatatypeSet> {
         self.datatypes.read().get_datatype(key)
     }
 
+    /// Executes multiple operations on the datatypes of `keys` atomically within a
+    /// transaction.
+    ///
+    /// Only the datatypes of `keys` are locked for the transaction, in the order of their
+    /// [`Duid`](crate::Duid)s, so that concurrent transactions never deadlock. `tx_func`
+    /// operates on them through the handles from [`ClientTransaction`]; the handles
+    /// obtained otherwise wait for the transaction to end, and thus must not be used within
+    /// it. If `tx_func` returns an error or panics, the changes of all the datatypes are
+    /// rolled back; otherwise, they are committed together, and the transaction of each
+    /// datatype has the same [`Transaction::group`](crate::Transaction::group).
+    ///
+    /// The datatypes must be given up front, since they are locked before `tx_func` runs,
+    /// and the set `tx_func` would touch cannot be known in advance. A datatype missing
+    /// from `keys` is not detected until `tx_func` runs, and is not part of the
+    /// transaction: [`ClientTransaction::get_datatype`] returns `None` for it, and a handle
+    /// of it used within `tx_func` is neither locked nor grouped, so that its changes are
+    /// kept even if the transaction is rolled back.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClientError::FailedToFindDatatype`] if the client has no open datatype for
+    /// any of `keys`, or [`ClientError::FailedTransaction`] if `tx_func` returns an error or
+    /// panics, any of the datatypes cannot be locked within the lock timeout of the client,
+    /// or the transaction of any of them is rejected by a hook of
+    /// [`Datatype::before_commit`](crate::Datatype::before_commit).
+    ///
+    /// # Examples
+    /// ```
+    /// use syncyam::Client;
+    /// let client = Client::builder("test-collection", "test-client").build().unwrap();
+    /// let counter = client.create_counter("test-counter").unwrap();
+    /// let variable = client.create_variable("test-variable").unwrap();
+    /// let keys = ["test-counter", "test-variable"];
+    /// let result: Result<(), _> = client.transaction("failing-update", &keys, |tx| {
+    ///     let counter = tx.get_datatype("test-counter").unwrap().ensure_counter().unwrap();
+    ///     let variable = tx.get_datatype("test-variable").unwrap().ensure_variable().unwrap();
+    ///     counter.increase_by(2);
+    ///     variable.set("two");
+    ///     Err("rejected".into())
+    /// });
+    /// assert!(result.is_err());
+    /// assert_eq!(counter.get_value(), 0); // unchanged
+    /// assert!(variable.get().is_null()); // unchanged
+    ///
+    /// let value = client.transaction("update", &keys, |tx| {
+    ///     let counter = tx.get_datatype("test-counter").unwrap().ensure_counter().unwrap();
+    ///     let variable = tx.get_datatype("test-variable").unwrap().ensure_variable().unwrap();
+    ///     variable.set("three");
+    ///     Ok(counter.increase_by(3))
+    /// });
+    /// assert_eq!(value.unwrap(), 3);
+    /// assert_eq!(variable.get(), "three".into());
+    /// ```
+    pub fn transaction<T, R>(
+        &self,
+        tag: impl IntoString,
+        keys: &[&str],
+        tx_func: T,
+    ) -> Result<R, ClientError>
+    where
+        T: FnOnce(&ClientTransaction) -> Result<R, Box<dyn Error + Send + Sync>>,
+    {
+        let mut datatypes = {
+            let datatypes = self.datatypes.read();
+            keys.iter()
+                .map(|key| {
+                    datatypes.get_datatype(key).ok_or_else(|| {
+                        err!(
+                            ClientError::FailedToFindDatatype,
+                            format!("no datatype for '{key}'")
+                        )
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()?
+        };
+        datatypes.sort_by(|a, b| a.get_core().attr.duid.cmp(&b.get_core().attr.duid));
+        datatypes.dedup_by(|a, b| a.get_core().attr.duid == b.get_core().attr.duid);
+        let tx_ctx = Arc::new(TransactionContext::new_in_group(tag, Uid::new()));
+        let mut guards = Vec::with_capacity(datatypes.len());
+        for dt in datatypes.iter() {
+            // the guards locked so far are dropped on failure, which ends their transactions.
+            let guard = dt
+                .get_core()
+                .lock_transaction(tx_ctx.clone())
+                .map_err(|e| err!(ClientError::FailedTransaction, e))?;
+            guards.push(guard);
+        }
+        let tx = ClientTransaction {
+            datatypes: datatypes
+                .iter()
+                .map(|dt| (dt.get_key().to_owned(), dt.with_tx_ctx(tx_ctx.clone())))
+                .collect(),
+        };
+        let result = catch_unwind(|| tx_func(&tx))
+            .unwrap_or_else(|msg| Err(format!("panicked in transaction: {msg}").into()))
+            .and_then(|r| {
+                // committed only if none of the datatypes is rejected by its hooks.
+                for dt in datatypes.iter() {
+                    dt.get_core().check_before_commit()?;
+                }
+                Ok(r)
+            });
+        if result.is_ok() {
+            guards.iter_mut().for_each(|dg| dg.commit());
+        }
+        // every datatype ends its transaction, committed or rolled back, by dropping the guard.
+        drop(guards);
+        result.map_err(|e| err!(ClientError::FailedTransaction, e.to_string()))
+    }
+
+    /// Imports a datatype as `key` from a `snapshot` exported by
+    /// [`Datatype::export_snapshot`](crate::Datatype::export_snapshot), e.g., to seed a new
+    /// environment with the data of another.
+    ///
+    /// The imported datatype keeps the contents and the [`Duid`](crate::Duid) of the
+    /// exported one, and is [`DatatypeState::DueToSubscribeOrCreate`]. Since the server
+    /// relays only the changes made after the snapshot, every client sharing the datatype
+    /// is expected to import the same snapshot. The transactions already in the snapshot
+    /// are skipped, so it can also be imported on the server of the exporting client.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClientError::FailedToImportDatatype`] if the snapshot is malformed, or the
+    /// client already has a datatype for `key`.
+    ///
+    /// # Examples
+    /// ```
+    /// use syncyam::{Client, Datatype};
+    /// let client1 = Client::builder("test-collection", "client1").build().unwrap();
+    /// let text1 = client1.create_text("test-text").unwrap();
+    /// text1.insert(0, "hello").unwrap();
+    /// let snapshot = text1.export_snapshot();
+    ///
+    /// let client2 = Client::builder("test-collection", "client2").build().unwrap();
+    /// let text2 = client2
+    ///     .import_datatype("test-text", &snapshot)
+    ///     .unwrap()
+    ///     .ensure_text()
+    ///     .unwrap();
+    /// text2.insert(5, " world").unwrap();
+    /// assert_eq!(text2.to_string(), "hello world");
+    /// assert!(client2.import_datatype("test-text", &snapshot).is_err());
+    /// ```
+    pub fn import_datatype(
+        &self,
+        key: impl IntoString,
+        snapshot: &[u8],
+    ) -> Result<DatatypeSet, ClientError> {
+        self.datatypes
+            .write()
+            .import_datatype(&key.into(), snapshot)
+    }
+
+    /// Unsubscribes from the datatype `key`, which stops being synchronized.
+    ///
+    /// The datatype becomes [`DatatypeState::DueToUnsubscribe`], and then
+    /// [`DatatypeState::Closed`] once its pending changes are pushed to the server by the
+    /// next synchronization; without a transport, it is closed right away. Local
+    /// operations on it are rejected with
+    /// [`DatatypeError::ClosedDatatype`](crate::DatatypeError::ClosedDatatype). Once it is
+    /// closed, a subscription to the key reopens it, resuming the synchronization from
+    /// where it stopped.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClientError::FailedToFindDatatype`] if the client has no datatype for `key`,
+    /// or [`ClientError::FailedToChangeDatatypeState`] if it is going to be deleted.
+    ///
+    /// # Examples
+    /// ```
+    /// use syncyam::{Client, Datatype, DatatypeState};
+    /// let client = Client::builder("test-collection", "test-client").build().unwrap();
+    /// let counter = client.create_counter("test-counter").unwrap();
+    /// client.unsubscribe("test-counter").unwrap();
+    /// assert_eq!(counter.get_state(), DatatypeState::Closed);
+    /// assert!(client.get_datatype("test-counter").is_none());
+    /// assert_eq!(counter.increase(), 0);
+    /// let counter = client.subscribe_counter("test-counter").unwrap();
+    /// assert_eq!(counter.get_state(), DatatypeState::DueToSubscribe);
+    /// assert_eq!(counter.increase(), 1);
+    /// ```
+    pub fn unsubscribe(&self, key: &str) -> Result<(), ClientError> {
+        let state = match self.sync_manager {
+            Some(_) => DatatypeState::DueToUnsubscribe,
+            None => DatatypeState::Closed,
+        };
+        self.datatypes.write().set_state(key, state)
+    }
+
+    /// Closes the datatype `key` right away without notifying the server.
+    ///
+    /// Unlike [`Self::unsubscribe`], the changes not yet pushed to the server are kept
+    /// local until the datatype is reopened by a subscription to the key.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClientError::FailedToFindDatatype`] if the client has no datatype for `key`.
+    pub fn close_datatype(&self, key: &str) -> Result<(), ClientError> {
+        self.datatypes.write().set_state(key, DatatypeState::Closed)
+    }
+
+    /// Deletes the datatype `key` from the server, and thereby from every subscriber.
+    ///
+    /// The datatype becomes [`DatatypeState::DueToDelete`], and then
+    /// [`DatatypeState::Deleted`] once the next synchronization deletes it from the
+    /// server; without a transport, it is deleted right away. The other subscribers
+    /// find it [`DatatypeState::Deleted`] by their next synchronization.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClientError::FailedToFindDatatype`] if the client has no datatype for `key`,
+    /// or [`ClientError::FailedToChangeDatatypeState`] if it is being unsubscribed.
+    pub fn delete_datatype(&self, key: &str) -> Result<(), ClientError> {
+        let state = match self.sync_manager {
+            Some(_) => DatatypeState::DueToDelete,
+            None => DatatypeState::Deleted,
+        };
+        self.datatypes.write().set_state(key, state)
+    }
+
     /// Returns the collection name this client is associated with.
     pub fn get_collection(&self) -> &str {
         &self.info.collection
@@ -147,11 +1009,71 @@ impl Client {
     pub fn get_alias(&self) -> &str {
         &self.info.alias
     }
+
+    /// Synchronizes all datatypes of this client with the server immediately.
+    ///
+    /// Datatypes are also synchronized in the background, so calling this is
+    /// only needed to make sure local changes are pushed at a specific moment.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ClientError::FailedToSync`] if the client has no transport,
+    /// the transport fails, or the server rejects any of the datatypes.
+    pub fn sync(&self) -> Result<(), ClientError> {
+        match &self.sync_manager {
+            Some(sm) => sm.sync(),
+            None => Err(err!(
+                ClientError::FailedToSync,
+                "the client has no transport"
+            )),
+        }
+    }
+
+    /// Synchronizes all datatypes of this client with the server like [`Self::sync`],
+    /// but without blocking the executor of the caller.
+    pub async fn sync_async(&self) -> Result<(), ClientError> {
+        match &self.sync_manager {
+            Some(sm) => {
+                let sm = sm.clone();
+                run_blocking(CLIENT_RUNTIME_GROUP, move || sm.sync()).await
+            }
+            None => Err(err!(
+                ClientError::FailedToSync,
+                "the client has no transport"
+            )),
+        }
+    }
+}
+
+/// The datatypes of a [`Client`] locked by [`Client::transaction`].
+pub struct ClientTransaction {
+    datatypes: HashMap<String, DatatypeSet>,
+}
+
+impl ClientTransaction {
+    /// Returns the datatype `key` whose handle operates in this transaction, if it is one
+    /// of the keys given to [`Client::transaction`].
+    pub fn get_datatype(&self, key: &str) -> Option<DatatypeSet> {
+        self.datatypes.get(key).cloned()
+    }
+}
+
+impl Drop for Client {
+    fn drop(&mut self) {
+        if let Some(sm) = &self.sync_manager {
+            sm.stop();
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests_client {
-    use crate::{Datatype, DatatypeState, clients::client::Client};
+    use std::{sync::Arc, time::Duration};
+
+    use crate::{
+        ClientError, Datatype, DatatypeError, DatatypeSet, DatatypeState, clients::client::Client,
+        datatypes::datatype::DatatypeBlanket, server::MemoryServer,
+    };
 
     #[test]
     fn can_assert_send_and_sync_traits() {
@@ -190,4 +1112,380 @@ mod tests_client {
         let counter3 = client3.subscribe_or_create_counter("k1").unwrap();
         assert_eq!(counter3.get_state(), DatatypeState::DueToSubscribeOrCreate);
     }
+
+    #[test]
+    fn can_use_variable_from_client() {
+        let client = Client::builder(module_path!(), module_path!())
+            .build()
+            .unwrap();
+        let variable1 = client.create_variable("v1").unwrap();
+        assert_eq!(variable1.get_state(), DatatypeState::DueToCreate);
+        variable1.set("yam");
+
+        let variable2 = client.create_variable("v1").unwrap();
+        assert_eq!(variable2.get(), "yam".into());
+        assert!(client.subscribe_variable("v1").is_err());
+        assert!(client.create_counter("v1").is_err());
+
+        let variable3 = client.subscribe_variable("v2").unwrap();
+        assert_eq!(variable3.get_state(), DatatypeState::DueToSubscribe);
+        let variable4 = client.subscribe_or_create_variable("v3").unwrap();
+        assert_eq!(variable4.get_state(), DatatypeState::DueToSubscribeOrCreate);
+    }
+
+    #[test]
+    fn can_use_list_from_client() {
+        let client = Client::builder(module_path!(), module_path!())
+            .build()
+            .unwrap();
+        let list1 = client.create_list("l1").unwrap();
+        list1.insert(0, [1, 2]).unwrap();
+        let list2 = client.create_list("l1").unwrap();
+        assert_eq!(list2.len(), 2);
+        assert!(client.create_variable("l1").is_err());
+
+        let list3 = client.subscribe_list("l2").unwrap();
+        assert_eq!(list3.get_state(), DatatypeState::DueToSubscribe);
+        let list4 = client.subscribe_or_create_list("l3").unwrap();
+        assert_eq!(list4.get_state(), DatatypeState::DueToSubscribeOrCreate);
+    }
+
+    #[test]
+    fn can_use_map_from_client() {
+        let client = Client::builder(module_path!(), module_path!())
+            .build()
+            .unwrap();
+        let map1 = client.create_map("m1").unwrap();
+        map1.put("k1", 1);
+        let map2 = client.create_map("m1").unwrap();
+        assert_eq!(map2.get("k1"), Some(1.into()));
+        assert!(client.create_list("m1").is_err());
+        assert!(client.get_datatype("m1").unwrap().ensure_map().is_some());
+
+        let map3 = client.subscribe_map("m2").unwrap();
+        assert_eq!(map3.get_state(), DatatypeState::DueToSubscribe);
+        let map4 = client.subscribe_or_create_map("m3").unwrap();
+        assert_eq!(map4.get_state(), DatatypeState::DueToSubscribeOrCreate);
+    }
+
+    #[test]
+    fn can_use_document_from_client() {
+        let client = Client::builder(module_path!(), module_path!())
+            .build()
+            .unwrap();
+        let doc1 = client.create_document("d1").unwrap();
+        doc1.put_to_object("", "k1", 1).unwrap();
+        let doc2 = client.create_document("d1").unwrap();
+        assert_eq!(doc2.get_by_path("/k1"), Some(1.into()));
+        assert!(client.create_map("d1").is_err());
+        assert!(
+            client
+                .get_datatype("d1")
+                .unwrap()
+                .ensure_document()
+                .is_some()
+        );
+
+        let doc3 = client.subscribe_document("d2").unwrap();
+        assert_eq!(doc3.get_state(), DatatypeState::DueToSubscribe);
+        let doc4 = client.subscribe_or_create_document("d3").unwrap();
+        assert_eq!(doc4.get_state(), DatatypeState::DueToSubscribeOrCreate);
+    }
+
+    #[test]
+    fn can_use_text_from_client() {
+        let client = Client::builder(module_path!(), module_path!())
+            .build()
+            .unwrap();
+        let text1 = client.create_text("t1").unwrap();
+        text1.insert(0, "yam").unwrap();
+        let text2 = client.create_text("t1").unwrap();
+        assert_eq!(text2.to_string(), "yam");
+        assert!(client.create_list("t1").is_err());
+        assert!(client.get_datatype("t1").unwrap().ensure_text().is_some());
+
+        let text3 = client.subscribe_text("t2").unwrap();
+        assert_eq!(text3.get_state(), DatatypeState::DueToSubscribe);
+        let text4 = client.subscribe_or_create_text("t3").unwrap();
+        assert_eq!(text4.get_state(), DatatypeState::DueToSubscribeOrCreate);
+    }
+
+    #[test]
+    fn can_use_set_from_client() {
+        let client = Client::builder(module_path!(), module_path!())
+            .build()
+            .unwrap();
+        let set1 = client.create_set("s1").unwrap();
+        set1.add("yam");
+        let set2 = client.create_set("s1").unwrap();
+        assert!(set2.contains("yam"));
+        assert!(client.create_map("s1").is_err());
+        assert!(client.get_datatype("s1").unwrap().ensure_set().is_some());
+
+        let set3 = client.subscribe_set("s2").unwrap();
+        assert_eq!(set3.get_state(), DatatypeState::DueToSubscribe);
+        let set4 = client.subscribe_or_create_set("s3").unwrap();
+        assert_eq!(set4.get_state(), DatatypeState::DueToSubscribeOrCreate);
+    }
+
+    #[test]
+    fn can_close_and_resubscribe_datatype() {
+        let client = Client::builder(module_path!(), module_path!())
+            .build()
+            .unwrap();
+        let list1 = client.create_list("k1").unwrap();
+        list1.insert(0, ["a"]).unwrap();
+        client.close_datatype("k1").unwrap();
+        assert_eq!(list1.get_state(), DatatypeState::Closed);
+        assert!(client.get_datatype("k1").is_none());
+        assert!(matches!(
+            list1.insert(0, ["b"]),
+            Err(DatatypeError::ClosedDatatype(_))
+        ));
+        assert_eq!(list1.len(), 1);
+        assert!(matches!(
+            client.unsubscribe("k1"),
+            Err(ClientError::FailedToFindDatatype(_))
+        ));
+
+        assert!(client.create_counter("k1").is_err());
+        let list2 = client.subscribe_list("k1").unwrap();
+        assert_eq!(list2.get_state(), DatatypeState::DueToSubscribe);
+        assert_eq!(list1.get_state(), DatatypeState::DueToSubscribe);
+        assert!(list2.insert(0, ["b"]).is_ok());
+        assert_eq!(list1.len(), 2);
+
+        client.delete_datatype("k1").unwrap();
+        assert_eq!(list2.get_state(), DatatypeState::Deleted);
+        assert!(matches!(
+            client.delete_datatype("k1"),
+            Err(ClientError::FailedToFindDatatype(_))
+        ));
+        assert!(list2.insert(0, ["c"]).is_err());
+        assert!(client.get_datatype("k1").is_none());
+    }
+
+    #[test]
+    fn can_run_transaction_across_datatypes() {
+        let client = Client::builder(module_path!(), module_path!())
+            .build()
+            .unwrap();
+        let counter = client.create_counter("k1").unwrap();
+        let list = client.create_list("k2").unwrap();
+        let text = client.create_text("k3").unwrap();
+        client.close_datatype("k3").unwrap();
+
+        let result: Result<(), _> = client.transaction("closed", &["k1", "k3"], |_| Ok(()));
+        assert!(matches!(result, Err(ClientError::FailedToFindDatatype(_))));
+
+        let result: Result<(), _> = client.transaction("failure", &["k1", "k2"], |tx| {
+            assert!(tx.get_datatype("k3").is_none());
+            let counter = tx.get_datatype("k1").unwrap().ensure_counter().unwrap();
+            let list = tx.get_datatype("k2").unwrap().ensure_list().unwrap();
+            counter.increase_by(2);
+            list.insert(0, ["a"])?;
+            Err("failure".into())
+        });
+        assert!(matches!(result, Err(ClientError::FailedTransaction(_))));
+        assert_eq!(counter.get_value(), 0);
+        assert!(list.is_empty());
+
+        let len = client
+            .transaction("success", &["k2", "k1"], |tx| {
+                let counter = tx.get_datatype("k1").unwrap().ensure_counter().unwrap();
+                let list = tx.get_datatype("k2").unwrap().ensure_list().unwrap();
+                counter.increase_by(2);
+                // a transaction of a handle is nested in the transaction of the client.
+                let nested: Result<(), _> = list.transaction("nested", |l| {
+                    l.insert(0, ["b"])?;
+                    Err("nested failure".into())
+                });
+                assert!(nested.is_err());
+                list.insert(0, ["a"])?;
+                Ok(list.len())
+            })
+            .unwrap();
+        assert_eq!(len, 1);
+        assert_eq!(counter.get_value(), 2);
+        assert_eq!(list.to_vec(), vec!["a".into()]);
+        assert_eq!(text.to_string(), "");
+
+        let last_group = |ds: DatatypeSet| {
+            let txs = ds.get_core().mutable.read().rollback.transactions().clone();
+            txs.back().unwrap().group().cloned()
+        };
+        let group1 = last_group(client.get_datatype("k1").unwrap());
+        let group2 = last_group(client.get_datatype("k2").unwrap());
+        assert!(group1.is_some());
+        assert_eq!(group1, group2);
+
+        // the datatypes not given are neither locked nor in the group.
+        client
+            .transaction("only_counter", &["k1"], |tx| {
+                assert!(tx.get_datatype("k2").is_none());
+                let counter = tx.get_datatype("k1").unwrap().ensure_counter().unwrap();
+                list.insert(1, ["c"])?;
+                Ok(counter.increase())
+            })
+            .unwrap();
+        assert_eq!(list.len(), 2);
+        assert_ne!(last_group(client.get_datatype("k1").unwrap()), group1);
+        assert_eq!(last_group(client.get_datatype("k2").unwrap()), None);
+    }
+
+    #[test]
+    fn can_leave_unlisted_datatypes_out_of_transaction() {
+        let client = Client::builder(module_path!(), module_path!())
+            .build()
+            .unwrap();
+        let counter = client.create_counter("k1").unwrap();
+        let variable = client.create_variable("k2").unwrap();
+        let result: Result<(), _> = client.transaction("unlisted", &["k1"], |tx| {
+            let counter = tx.get_datatype("k1").unwrap().ensure_counter().unwrap();
+            counter.increase();
+            // k2 is not locked, so that its change is made apart from the transaction.
+            assert!(tx.get_datatype("k2").is_none());
+            variable.set(1);
+            Err("failure".into())
+        });
+        assert!(matches!(result, Err(ClientError::FailedTransaction(_))));
+        assert_eq!(counter.get_value(), 0);
+        assert_eq!(variable.get(), 1.into());
+    }
+
+    #[test]
+    fn can_run_transactions_across_datatypes_concurrently() {
+        let client = Arc::new(
+            Client::builder(module_path!(), module_path!())
+                .build()
+                .unwrap(),
+        );
+        let counter1 = client.create_counter("k1").unwrap();
+        let counter2 = client.create_counter("k2").unwrap();
+
+        let handles: Vec<_> = (0..2)
+            .map(|i| {
+                let client = client.clone();
+                let (counter1, counter2) = (counter1.clone(), counter2.clone());
+                std::thread::spawn(move || {
+                    for _ in 0..50 {
+                        client
+                            .transaction(format!("t{i}"), &["k1", "k2"], |tx| {
+                                for key in ["k2", "k1"] {
+                                    let counter =
+                                        tx.get_datatype(key).unwrap().ensure_counter().unwrap();
+                                    counter.increase();
+                                }
+                                Ok(())
+                            })
+                            .unwrap();
+                        counter2.increase();
+                        counter1.increase();
+                    }
+                })
+            })
+            .collect();
+        handles.into_iter().for_each(|h| h.join().unwrap());
+        assert_eq!(counter1.get_value(), 200);
+        assert_eq!(counter2.get_value(), 200);
+    }
+
+    #[test]
+    fn can_time_out_transaction_across_datatypes() {
+        let client = Client::builder(module_path!(), module_path!())
+            .with_lock_timeout(Duration::from_millis(20))
+            .build()
+            .unwrap();
+        let counter1 = client.create_counter("k1").unwrap();
+        let counter2 = client.create_counter("k2").unwrap();
+        let (locked_tx, locked_rx) = std::sync::mpsc::channel();
+        let blocking = {
+            let counter2 = counter2.clone();
+            std::thread::spawn(move || {
+                counter2.transaction("blocking", move |c| {
+                    locked_tx.send(()).unwrap();
+                    std::thread::sleep(Duration::from_millis(200));
+                    Ok(c.increase())
+                })
+            })
+        };
+        locked_rx.recv().unwrap();
+        let result = client.transaction("timeout", &["k1", "k2"], |tx| {
+            let counter = tx.get_datatype("k1").unwrap().ensure_counter().unwrap();
+            Ok(counter.increase())
+        });
+        assert!(matches!(result, Err(ClientError::FailedTransaction(_))));
+        // the datatypes locked before the timeout are released.
+        assert_eq!(counter1.increase(), 1);
+        assert_eq!(blocking.join().unwrap().unwrap(), 1);
+        assert_eq!(counter2.get_value(), 1);
+    }
+
+    #[test]
+    fn can_roll_back_panicking_transaction_across_datatypes() {
+        let client = Client::builder(module_path!(), module_path!())
+            .build()
+            .unwrap();
+        let counter = client.create_counter("k1").unwrap();
+        let variable = client.create_variable("k2").unwrap();
+        let result: Result<(), _> = client.transaction("panic", &["k1", "k2"], |tx| {
+            let counter = tx.get_datatype("k1").unwrap().ensure_counter().unwrap();
+            let variable = tx.get_datatype("k2").unwrap().ensure_variable().unwrap();
+            counter.increase();
+            variable.set(1);
+            panic!("in transaction");
+        });
+        assert!(matches!(result, Err(ClientError::FailedTransaction(_))));
+        assert_eq!(counter.get_value(), 0);
+        assert!(variable.get().is_null());
+        // the datatypes are released.
+        assert_eq!(counter.increase(), 1);
+        assert!(variable.set(2).is_null());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn can_use_async_api() {
+        let server = Arc::new(MemoryServer::new());
+        let client1 = Client::builder(module_path!(), "c1")
+            .with_transport(server.clone())
+            .build()
+            .unwrap();
+        let client2 = Client::builder(module_path!(), "c2")
+            .with_transport(server.clone())
+            .with_sync_interval(Duration::from_secs(3600))
+            .build()
+            .unwrap();
+
+        let counter1 = client1.create_counter_async("k1").await.unwrap();
+        let counter = counter1.clone();
+        // the futures are Send, so that they can be spawned
+        tokio::spawn(async move {
+            counter
+                .transaction_async("tx", |c| {
+                    c.increase_by(2);
+                    c.increase_by(3);
+                    Ok(())
+                })
+                .await
+        })
+        .await
+        .unwrap()
+        .unwrap();
+        // subscribed by the background synchronization
+        tokio::time::timeout(Duration::from_secs(3), counter1.wait_until_subscribed())
+            .await
+            .unwrap();
+        assert!(client1.create_variable_async("k1").await.is_err());
+
+        let counter2 = client2.subscribe_counter_async("k1").await.unwrap();
+        assert_eq!(counter2.get_state(), DatatypeState::DueToSubscribe);
+        client2.sync_async().await.unwrap();
+        tokio::time::timeout(Duration::from_secs(3), counter2.wait_until_subscribed())
+            .await
+            .unwrap();
+        assert_eq!(counter2.get_value(), 5);
+
+        let offline = Client::builder(module_path!(), "c3").build().unwrap();
+        assert!(offline.sync_async().await.is_err());
+    }
 }
@@ -4,7 +4,14 @@ use std::{
 };
 
 use crate::{
-    ClientError, DataType, DatatypeState, clients::client::ClientInfo, datatypes::DatatypeSet,
+    ClientError, DataType, DatatypeState,
+    clients::client::ClientInfo,
+    datatypes::{
+        DatatypeSet,
+        datatype::{Datatype, DatatypeBlanket},
+        record::{DatatypeRecord, ExportedSnapshot},
+        transactional::TransactionalDatatype,
+    },
     errors::err,
 };
 
@@ -21,8 +28,18 @@ impl DatatypeManager {
         }
     }
 
+    /// Returns the datatype `key` unless it is closed.
     pub fn get_datatype(&self, key: &str) -> Option<DatatypeSet> {
-        self.datatypes.get(key).cloned()
+        self.datatypes.get(key).filter(|dt| is_open(dt)).cloned()
+    }
+
+    /// Returns the datatypes not closed, which are to be synchronized.
+    pub fn get_datatypes(&self) -> Vec<DatatypeSet> {
+        self.datatypes
+            .values()
+            .filter(|dt| is_open(dt))
+            .cloned()
+            .collect()
     }
 
     pub fn subscribe_or_create_datatype(
@@ -34,7 +51,27 @@ impl DatatypeManager {
         match self.datatypes.entry(key.to_owned()) {
             Entry::Occupied(entry) => {
                 let existing = entry.get();
-                if existing.get_type() != r#type || existing.get_state() != state {
+                // a closed datatype is reopened as it is, so that it resumes from its checkpoint.
+                if existing.get_state() == DatatypeState::Closed && existing.get_type() == r#type {
+                    existing.get_core().set_state(state).map_err(|e| {
+                        err!(
+                            ClientError::FailedToSubscribeOrCreateDatatype,
+                            format!("'{key}': {e}")
+                        )
+                    })?;
+                    return Ok(existing.clone());
+                }
+                // a subscribed datatype, e.g., restored from the storage, can be obtained again
+                // by the same request that created or subscribed to it.
+                let compatible = existing.get_state() == state
+                    || existing.get_state() == DatatypeState::Subscribed
+                        && matches!(
+                            state,
+                            DatatypeState::DueToCreate
+                                | DatatypeState::DueToSubscribe
+                                | DatatypeState::DueToSubscribeOrCreate
+                        );
+                if existing.get_type() != r#type || !compatible {
                     return Err(err!(
                         ClientError::FailedToSubscribeOrCreateDatatype,
                         format!(
@@ -48,11 +85,86 @@ impl DatatypeManager {
             }
             Entry::Vacant(_) => {
                 let dt = DatatypeSet::new(r#type, key, state, self.info.clone());
+                dt.get_core().persist_snapshot();
                 self.datatypes.insert(key.to_owned(), dt.clone());
                 Ok(dt)
             }
         }
     }
+
+    /// Sets the `state` of the datatype `key` requested by the client.
+    ///
+    /// A closed datatype is kept to be reopened by a subscription to the key, whereas a
+    /// deleted one is removed.
+    pub fn set_state(&mut self, key: &str, state: DatatypeState) -> Result<(), ClientError> {
+        let Some(datatype) = self.get_datatype(key) else {
+            return Err(err!(
+                ClientError::FailedToFindDatatype,
+                format!("no datatype for '{key}'")
+            ));
+        };
+        datatype.get_core().set_state(state).map_err(|e| {
+            err!(
+                ClientError::FailedToChangeDatatypeState,
+                format!("'{key}': {e}")
+            )
+        })?;
+        if state == DatatypeState::Deleted {
+            self.datatypes.remove(key);
+        }
+        Ok(())
+    }
+
+    /// Removes the datatypes deleted by the server.
+    pub fn remove_deleted_datatypes(&mut self) {
+        self.datatypes
+            .retain(|_, dt| dt.get_state() != DatatypeState::Deleted);
+    }
+
+    /// Imports the datatype `key` from a snapshot exported by another client.
+    pub fn import_datatype(
+        &mut self,
+        key: &str,
+        snapshot: &[u8],
+    ) -> Result<DatatypeSet, ClientError> {
+        if self.datatypes.contains_key(key) {
+            return Err(err!(
+                ClientError::FailedToImportDatatype,
+                format!("'{key}' already exists")
+            ));
+        }
+        let datatype = ExportedSnapshot::decode(snapshot)
+            .and_then(|snapshot| TransactionalDatatype::import(key, snapshot, self.info.clone()))
+            .map_err(|e| err!(ClientError::FailedToImportDatatype, format!("'{key}': {e}")))?;
+        datatype.persist_snapshot();
+        let dt = DatatypeSet::from_core(Arc::new(datatype));
+        self.datatypes.insert(key.to_owned(), dt.clone());
+        Ok(dt)
+    }
+
+    /// Restores the datatype `key` from the `records` loaded from the storage.
+    pub fn restore_datatype(
+        &mut self,
+        key: &str,
+        records: Vec<Vec<u8>>,
+    ) -> Result<(), ClientError> {
+        let datatype = records
+            .iter()
+            .map(|record| DatatypeRecord::decode(record))
+            .collect::<Result<Vec<_>, _>>()
+            .and_then(|records| TransactionalDatatype::restore(key, records, self.info.clone()))
+            .map_err(|e| err!(ClientError::FailedToAccessStorage, format!("'{key}': {e}")))?;
+        if datatype.get_state() == DatatypeState::Deleted {
+            return Ok(());
+        }
+        self.datatypes
+            .insert(key.to_owned(), DatatypeSet::from_core(Arc::new(datatype)));
+        Ok(())
+    }
+}
+
+fn is_open(datatype: &DatatypeSet) -> bool {
+    datatype.get_state() != DatatypeState::Closed
 }
 
 #[cfg(test)]
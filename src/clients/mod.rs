@@ -1,2 +1,5 @@
 pub mod client;
 mod datatype_manager;
+pub mod storage;
+mod sync_manager;
+pub mod transport;
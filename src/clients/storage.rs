@@ -0,0 +1,360 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{ErrorKind, Read, Write},
+    path::{Path, PathBuf},
+};
+
+use parking_lot::Mutex;
+use tracing::warn;
+
+use crate::{
+    errors::{clients::ClientError, err},
+    types::uid::Cuid,
+};
+
+const LOG_EXTENSION: &str = "log";
+
+/// The logs of datatypes, each of which is a pair of a datatype key and its records.
+pub type DatatypeLogs = Vec<(String, Vec<Vec<u8>>)>;
+
+/// Storage persists the datatypes of a client across restarts.
+///
+/// For each datatype, the SDK stores a log of opaque records: a snapshot followed by the
+/// changes made after it. Implement this trait to keep the records in any kind of storage,
+/// and register it with [`ClientBuilder::with_storage`](crate::ClientBuilder::with_storage).
+/// Records are partitioned by the collection and the cuid of the client, so a client
+/// rebuilt with the same cuid restores its datatypes.
+pub trait Storage: Send + Sync {
+    /// Loads the records of all datatypes of the client `cuid`, grouped by datatype key.
+    fn load(&self, collection: &str, cuid: &Cuid) -> Result<DatatypeLogs, ClientError>;
+
+    /// Appends `record` to the log of the datatype `key`.
+    fn append(
+        &self,
+        collection: &str,
+        cuid: &Cuid,
+        key: &str,
+        record: &[u8],
+    ) -> Result<(), ClientError>;
+
+    /// Replaces the log of the datatype `key` with `snapshot`.
+    fn compact(
+        &self,
+        collection: &str,
+        cuid: &Cuid,
+        key: &str,
+        snapshot: &[u8],
+    ) -> Result<(), ClientError>;
+}
+
+/// A [`Storage`] that keeps the log of each datatype in an append-only file.
+///
+/// The files are laid out as `<root>/<collection>/<cuid>/<key>.log`, where the collection
+/// and the key are hex-encoded to be safe as file names. Each record is prefixed with
+/// its length, and a partially written record at the end of a log is ignored.
+///
+/// # Examples
+///
+/// ```
+/// # use std::sync::Arc;
+/// # use syncyam::{Client, Cuid, FileStorage};
+/// let root = std::env::temp_dir().join(format!("syncyam-doc-{}", Cuid::new()));
+/// let storage = Arc::new(FileStorage::new(&root));
+/// let cuid = Cuid::new();
+/// {
+///     let client = Client::builder("col", "app")
+///         .with_cuid(cuid.clone())
+///         .with_storage(storage.clone())
+///         .build()
+///         .unwrap();
+///     client.create_counter("counter").unwrap().increase_by(3);
+/// }
+/// let client = Client::builder("col", "app")
+///     .with_cuid(cuid)
+///     .with_storage(storage)
+///     .build()
+///     .unwrap();
+/// assert_eq!(client.create_counter("counter").unwrap().get_value(), 3);
+/// # std::fs::remove_dir_all(root).unwrap();
+/// ```
+pub struct FileStorage {
+    root: PathBuf,
+    lock: Mutex<()>,
+}
+
+impl FileStorage {
+    pub fn new(root: impl AsRef<Path>) -> Self {
+        Self {
+            root: root.as_ref().to_path_buf(),
+            lock: Default::default(),
+        }
+    }
+
+    fn client_dir(&self, collection: &str, cuid: &Cuid) -> PathBuf {
+        self.root.join(to_hex(collection)).join(cuid.as_ref())
+    }
+
+    fn log_path(&self, collection: &str, cuid: &Cuid, key: &str) -> PathBuf {
+        self.client_dir(collection, cuid)
+            .join(to_hex(key))
+            .with_extension(LOG_EXTENSION)
+    }
+
+    fn read_log(path: &Path) -> Result<Vec<Vec<u8>>, ClientError> {
+        let mut buf = vec![];
+        File::open(path)
+            .and_then(|mut f| f.read_to_end(&mut buf))
+            .map_err(|e| err!(ClientError::FailedToAccessStorage, e))?;
+        let mut records = vec![];
+        let mut rest = buf.as_slice();
+        while rest.len() >= 4 {
+            let len = u32::from_le_bytes(rest[..4].try_into().unwrap()) as usize;
+            if rest.len() < 4 + len {
+                break;
+            }
+            records.push(rest[4..4 + len].to_vec());
+            rest = &rest[4 + len..];
+        }
+        if !rest.is_empty() {
+            warn!("ignored a partial record at the end of {path:?}");
+        }
+        Ok(records)
+    }
+}
+
+impl Storage for FileStorage {
+    fn load(&self, collection: &str, cuid: &Cuid) -> Result<DatatypeLogs, ClientError> {
+        let _guard = self.lock.lock();
+        let entries = match fs::read_dir(self.client_dir(collection, cuid)) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(vec![]),
+            Err(e) => return Err(err!(ClientError::FailedToAccessStorage, e)),
+        };
+        let mut logs = vec![];
+        for entry in entries {
+            let path = entry
+                .map_err(|e| err!(ClientError::FailedToAccessStorage, e))?
+                .path();
+            if path.extension().is_none_or(|ext| ext != LOG_EXTENSION) {
+                continue;
+            }
+            let Some(key) = path
+                .file_stem()
+                .and_then(|stem| from_hex(&stem.to_string_lossy()))
+            else {
+                warn!("ignored an unknown file {path:?}");
+                continue;
+            };
+            logs.push((key, Self::read_log(&path)?));
+        }
+        Ok(logs)
+    }
+
+    fn append(
+        &self,
+        collection: &str,
+        cuid: &Cuid,
+        key: &str,
+        record: &[u8],
+    ) -> Result<(), ClientError> {
+        let _guard = self.lock.lock();
+        let mut framed = Vec::with_capacity(4 + record.len());
+        framed.extend_from_slice(&(record.len() as u32).to_le_bytes());
+        framed.extend_from_slice(record);
+        OpenOptions::new()
+            .append(true)
+            .open(self.log_path(collection, cuid, key))
+            .and_then(|mut f| f.write_all(&framed))
+            .map_err(|e| err!(ClientError::FailedToAccessStorage, e))
+    }
+
+    fn compact(
+        &self,
+        collection: &str,
+        cuid: &Cuid,
+        key: &str,
+        snapshot: &[u8],
+    ) -> Result<(), ClientError> {
+        let _guard = self.lock.lock();
+        let path = self.log_path(collection, cuid, key);
+        let tmp_path = path.with_extension("tmp");
+        let mut framed = Vec::with_capacity(4 + snapshot.len());
+        framed.extend_from_slice(&(snapshot.len() as u32).to_le_bytes());
+        framed.extend_from_slice(snapshot);
+        // the snapshot replaces the log atomically by renaming a fully written file.
+        fs::create_dir_all(self.client_dir(collection, cuid))
+            .and_then(|_| fs::write(&tmp_path, &framed))
+            .and_then(|_| fs::rename(&tmp_path, &path))
+            .map_err(|e| err!(ClientError::FailedToAccessStorage, e))
+    }
+}
+
+fn to_hex(s: &str) -> String {
+    s.bytes().map(|b| format!("{b:02x}")).collect()
+}
+
+fn from_hex(s: &str) -> Option<String> {
+    if !s.len().is_multiple_of(2) {
+        return None;
+    }
+    let bytes = (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(s.get(i..i + 2)?, 16).ok())
+        .collect::<Option<Vec<u8>>>()?;
+    String::from_utf8(bytes).ok()
+}
+
+#[cfg(test)]
+mod tests_storage {
+    use std::{fs::OpenOptions, io::Write, path::PathBuf, sync::Arc, time::Duration};
+
+    use crate::{
+        Client, ClientError, Datatype, DatatypeState,
+        clients::storage::{FileStorage, Storage, from_hex, to_hex},
+        datatypes::datatype::DatatypeBlanket,
+        server::MemoryServer,
+        types::uid::Cuid,
+    };
+
+    fn temp_root() -> PathBuf {
+        std::env::temp_dir().join(format!("syncyam-{}", Cuid::new()))
+    }
+
+    fn new_client(storage: &Arc<FileStorage>, cuid: &Cuid) -> Client {
+        Client::builder(module_path!(), module_path!())
+            .with_cuid(cuid.clone())
+            .with_storage(storage.clone())
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn can_encode_file_names_in_hex() {
+        assert_eq!(to_hex("a/b"), "612f62");
+        assert_eq!(from_hex("612f62").unwrap(), "a/b");
+        assert!(from_hex("612").is_none());
+        assert!(from_hex("zz").is_none());
+    }
+
+    #[test]
+    fn can_append_load_and_compact_records() {
+        let root = temp_root();
+        let storage = FileStorage::new(&root);
+        let cuid = Cuid::new();
+        assert!(storage.load("col", &cuid).unwrap().is_empty());
+        assert_eq!(
+            storage.append("col", &cuid, "k1", &[1]).unwrap_err(),
+            ClientError::FailedToAccessStorage("".into())
+        );
+
+        storage.compact("col", &cuid, "k1", &[0, 0]).unwrap();
+        storage.append("col", &cuid, "k1", &[1]).unwrap();
+        storage.append("col", &cuid, "k1", &[2, 2, 2]).unwrap();
+        let logs = storage.load("col", &cuid).unwrap();
+        assert_eq!(
+            logs,
+            vec![("k1".to_owned(), vec![vec![0, 0], vec![1], vec![2, 2, 2]])]
+        );
+
+        // a partially written record is ignored
+        let path = storage.log_path("col", &cuid, "k1");
+        OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap()
+            .write_all(&[9, 0, 0, 0, 1])
+            .unwrap();
+        assert_eq!(storage.load("col", &cuid).unwrap()[0].1.len(), 3);
+
+        storage.compact("col", &cuid, "k1", &[3]).unwrap();
+        assert_eq!(storage.load("col", &cuid).unwrap()[0].1, vec![vec![3]]);
+        assert!(storage.load("col", &Cuid::new()).unwrap().is_empty());
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn can_restore_datatypes_with_the_same_cuid() {
+        let root = temp_root();
+        let storage = Arc::new(FileStorage::new(&root));
+        let cuid = Cuid::new();
+        {
+            let client = new_client(&storage, &cuid);
+            let counter = client.create_counter("counter").unwrap();
+            // more transactions than the snapshot interval
+            for _ in 0..150 {
+                counter.increase();
+            }
+            let list = client.subscribe_or_create_list("list").unwrap();
+            list.insert(0, ["a", "b", "c"]).unwrap();
+            list.delete(1, 1).unwrap();
+            let variable = client.subscribe_variable("variable").unwrap();
+            variable.set("yam");
+        }
+
+        let client = new_client(&storage, &cuid);
+        let counter = client.create_counter("counter").unwrap();
+        assert_eq!(counter.get_value(), 150);
+        assert_eq!(counter.get_state(), DatatypeState::DueToCreate);
+        assert_eq!(counter.increase(), 151);
+        {
+            let mutable = counter.get_core().mutable.read();
+            assert_eq!(mutable.pending_transactions().len(), 151);
+            assert_eq!(mutable.op_id.cseq, 151);
+        }
+        let list = client.subscribe_or_create_list("list").unwrap();
+        assert_eq!(list.to_vec(), vec!["a".into(), "c".into()]);
+        let variable = client.subscribe_variable("variable").unwrap();
+        assert_eq!(variable.get(), "yam".into());
+        assert!(client.subscribe_counter("variable").is_err());
+
+        let other = new_client(&storage, &Cuid::new());
+        assert!(other.get_datatype("counter").is_none());
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn can_resume_syncing_after_restore() {
+        let root = temp_root();
+        let storage = Arc::new(FileStorage::new(&root));
+        let server = Arc::new(MemoryServer::new());
+        let build = |cuid: &Cuid, alias: &str| {
+            Client::builder(module_path!(), alias)
+                .with_cuid(cuid.clone())
+                .with_storage(storage.clone())
+                .with_transport(server.clone())
+                .with_sync_interval(Duration::from_secs(3600))
+                .build()
+                .unwrap()
+        };
+        let (cuid1, cuid2) = (Cuid::new(), Cuid::new());
+        {
+            let client1 = build(&cuid1, "c1");
+            let counter1 = client1.create_counter("k1").unwrap();
+            counter1.increase_by(2);
+            client1.sync().unwrap();
+            counter1.increase_by(3); // not synced
+        }
+        {
+            let client2 = build(&cuid2, "c2");
+            let counter2 = client2.subscribe_counter("k1").unwrap();
+            client2.sync().unwrap();
+            assert_eq!(counter2.get_value(), 2);
+            counter2.increase_by(10);
+            client2.sync().unwrap();
+        }
+
+        let client1 = build(&cuid1, "c1");
+        let counter1 = client1.create_counter("k1").unwrap();
+        assert_eq!(counter1.get_state(), DatatypeState::Subscribed);
+        assert_eq!(counter1.get_value(), 5);
+        client1.sync().unwrap();
+        assert_eq!(counter1.get_value(), 15);
+
+        let client2 = build(&cuid2, "c2");
+        let counter2 = client2.subscribe_counter("k1").unwrap();
+        assert_eq!(counter2.get_value(), 12);
+        client2.sync().unwrap();
+        assert_eq!(counter2.get_value(), 15);
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,291 @@
+use std::{sync::Arc, time::Duration};
+
+use parking_lot::{Mutex, RwLock};
+use tokio::sync::Notify;
+use tracing::{instrument, warn};
+
+use crate::{
+    Datatype,
+    clients::{
+        client::ClientInfo,
+        datatype_manager::DatatypeManager,
+        transport::{Notifier, Transport},
+    },
+    datatypes::datatype::DatatypeBlanket,
+    errors::{clients::ClientError, err},
+    protocol::PushPullRequest,
+    utils::runtime::get_or_init_runtime,
+};
+
+pub const DEFAULT_SYNC_INTERVAL: Duration = Duration::from_millis(100);
+const SYNC_RUNTIME_GROUP: &str = "sync";
+
+/// SyncManager synchronizes the datatypes of a client with the server through a [`Transport`].
+///
+/// A background loop on the SDK runtime runs a synchronization every sync interval,
+/// or as soon as the server notifies the client.
+pub struct SyncManager {
+    info: Arc<ClientInfo>,
+    transport: Arc<dyn Transport>,
+    datatypes: Arc<RwLock<DatatypeManager>>,
+    notifier: Notifier,
+    shutdown: Arc<Notify>,
+    sync_mutex: Mutex<()>,
+}
+
+impl SyncManager {
+    pub fn new(
+        info: Arc<ClientInfo>,
+        transport: Arc<dyn Transport>,
+        datatypes: Arc<RwLock<DatatypeManager>>,
+    ) -> Self {
+        Self {
+            info,
+            transport,
+            datatypes,
+            notifier: Notifier::new(),
+            shutdown: Default::default(),
+            sync_mutex: Default::default(),
+        }
+    }
+
+    /// Connects to the server and starts the background sync loop.
+    pub fn start(self: &Arc<Self>, interval: Duration) -> Result<(), ClientError> {
+        self.transport
+            .connect(&self.info.cuid, self.notifier.clone())?;
+        let this = self.clone();
+        get_or_init_runtime(SYNC_RUNTIME_GROUP).spawn(async move {
+            loop {
+                tokio::select! {
+                    _ = this.shutdown.notified() => break,
+                    _ = this.notifier.notified() => {},
+                    _ = tokio::time::sleep(interval) => {},
+                }
+                let sm = this.clone();
+                // errors are already logged, and the next round retries them.
+                let _ = tokio::task::spawn_blocking(move || sm.sync()).await;
+            }
+        });
+        Ok(())
+    }
+
+    /// Stops the background sync loop and disconnects from the server.
+    pub fn stop(&self) {
+        self.shutdown.notify_one();
+        self.transport.disconnect(&self.info.cuid);
+    }
+
+    /// Pushes the pending transactions of all datatypes, and applies the response.
+    #[instrument(skip_all,
+        fields(
+            syncyam.col=%self.info.collection,
+            syncyam.cl=%self.info.alias,
+            syncyam.cuid=%self.info.cuid,
+        )
+    )]
+    pub fn sync(&self) -> Result<(), ClientError> {
+        let _guard = self.sync_mutex.lock();
+        let datatypes = self.datatypes.read().get_datatypes();
+        if datatypes.is_empty() {
+            return Ok(());
+        }
+        let request = PushPullRequest {
+            collection: self.info.collection.to_string(),
+            cuid: self.info.cuid.clone(),
+            packs: datatypes
+                .iter()
+                .map(|dt| dt.get_core().create_push_pull_pack())
+                .collect(),
+        };
+        let response = self.transport.push_pull(request)?;
+
+        let mut failed = vec![];
+        for pack in response.packs {
+            let Some(dt) = datatypes.iter().find(|dt| dt.get_key() == pack.key) else {
+                warn!("no datatype for {pack}");
+                continue;
+            };
+            let key = pack.key.clone();
+            if let Err(e) = dt.get_core().apply_push_pull_pack(pack) {
+                failed.push(format!("'{key}': {e}"));
+            }
+        }
+        self.datatypes.write().remove_deleted_datatypes();
+        if !failed.is_empty() {
+            return Err(err!(ClientError::FailedToSync, failed.join(", ")));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests_sync_manager {
+    use std::{sync::Arc, time::Duration};
+
+    use parking_lot::Mutex;
+
+    use crate::{
+        Client, ClientError, Datatype, DatatypeState,
+        clients::transport::{Notifier, Transport},
+        datatypes::datatype::DatatypeBlanket,
+        operations::transaction::Transaction,
+        protocol::{Checkpoint, PushPullRequest, PushPullResponse},
+        types::uid::Cuid,
+    };
+
+    /// A transport that accepts every pack and returns `remote` transactions.
+    #[derive(Default)]
+    struct MockTransport {
+        requests: Mutex<Vec<PushPullRequest>>,
+        remote: Mutex<Vec<Arc<Transaction>>>,
+        notifier: Mutex<Option<Notifier>>,
+        reject: Mutex<bool>,
+    }
+
+    impl Transport for MockTransport {
+        fn push_pull(&self, request: PushPullRequest) -> Result<PushPullResponse, ClientError> {
+            let mut response = PushPullResponse::default();
+            for pack in request.packs.iter() {
+                let mut pack = pack.clone();
+                if *self.reject.lock() {
+                    pack.error = Some("rejected".to_owned());
+                }
+                let cseq = pack.transactions.iter().map(|tx| tx.cseq()).max();
+                let remote = self.remote.lock().clone();
+                let sseq = remote.iter().map(|tx| tx.sseq()).max();
+                pack.checkpoint.sync(&Checkpoint::new(
+                    sseq.unwrap_or_default(),
+                    cseq.unwrap_or_default(),
+                ));
+                pack.state = DatatypeState::Subscribed;
+                pack.transactions = remote;
+                response.packs.push(pack);
+            }
+            self.requests.lock().push(request);
+            Ok(response)
+        }
+
+        fn connect(&self, _cuid: &Cuid, notifier: Notifier) -> Result<(), ClientError> {
+            self.notifier.lock().replace(notifier);
+            Ok(())
+        }
+
+        fn disconnect(&self, _cuid: &Cuid) {
+            self.notifier.lock().take();
+        }
+    }
+
+    fn new_client(transport: Arc<MockTransport>, interval: Duration) -> Client {
+        Client::builder(module_path!(), module_path!())
+            .with_transport(transport)
+            .with_sync_interval(interval)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn can_push_pending_transactions() {
+        let transport = Arc::new(MockTransport::default());
+        let client = new_client(transport.clone(), Duration::from_secs(3600));
+        let counter = client.create_counter("k1").unwrap();
+        counter.increase();
+        counter.increase_by(2);
+
+        client.sync().unwrap();
+        assert_eq!(counter.get_state(), DatatypeState::Subscribed);
+        {
+            let requests = transport.requests.lock();
+            let pack = &requests.last().unwrap().packs[0];
+            assert_eq!(pack.state, DatatypeState::DueToCreate);
+            assert_eq!(pack.transactions.len(), 2);
+        }
+        assert_eq!(
+            counter.get_core().mutable.read().checkpoint,
+            Checkpoint::new(0, 2)
+        );
+
+        client.sync().unwrap();
+        counter.increase();
+        client.sync().unwrap();
+        let requests = transport.requests.lock();
+        let pack = &requests.last().unwrap().packs[0];
+        assert_eq!(pack.state, DatatypeState::Subscribed);
+        assert_eq!(pack.transactions.len(), 1);
+        assert_eq!(pack.transactions[0].cseq(), 3);
+        // the transactions confirmed are dropped from the rollback history
+        let mutable = counter.get_core().mutable.read();
+        assert!(mutable.rollback.transactions().is_empty());
+        assert_eq!(mutable.rollback.size(), 0);
+    }
+
+    #[test]
+    fn can_pull_remote_transactions() {
+        let remote_client = Client::builder(module_path!(), module_path!())
+            .build()
+            .unwrap();
+        let remote_counter = remote_client.create_counter("k1").unwrap();
+        remote_counter.increase_by(10);
+        let mut remote_tx = (*remote_counter
+            .get_core()
+            .mutable
+            .read()
+            .rollback
+            .transactions()[0])
+            .clone();
+        remote_tx.set_sseq(1);
+
+        let transport = Arc::new(MockTransport::default());
+        transport.remote.lock().push(Arc::new(remote_tx));
+        let client = new_client(transport.clone(), Duration::from_secs(3600));
+        let counter = client.subscribe_counter("k1").unwrap();
+        client.sync().unwrap();
+        assert_eq!(counter.get_value(), 10);
+        assert_eq!(
+            counter.get_core().mutable.read().checkpoint,
+            Checkpoint::new(1, 0)
+        );
+        client.sync().unwrap();
+        assert_eq!(counter.get_value(), 10);
+    }
+
+    #[test]
+    fn can_fail_to_sync() {
+        let client = Client::builder(module_path!(), module_path!())
+            .build()
+            .unwrap();
+        assert_eq!(
+            client.sync().unwrap_err(),
+            ClientError::FailedToSync("".into())
+        );
+
+        let transport = Arc::new(MockTransport::default());
+        *transport.reject.lock() = true;
+        let client = new_client(transport.clone(), Duration::from_secs(3600));
+        let counter = client.subscribe_counter("k1").unwrap();
+        assert!(client.sync().is_err());
+        assert_eq!(counter.get_state(), DatatypeState::DueToSubscribe);
+    }
+
+    #[test]
+    fn can_sync_in_background() {
+        let transport = Arc::new(MockTransport::default());
+        let client = new_client(transport.clone(), Duration::from_millis(10));
+        let counter = client.create_counter("k1").unwrap();
+        counter.increase();
+        awaitility::at_most(Duration::from_secs(3))
+            .until(|| counter.get_state() == DatatypeState::Subscribed);
+    }
+
+    #[test]
+    fn can_sync_when_notified() {
+        let transport = Arc::new(MockTransport::default());
+        let client = new_client(transport.clone(), Duration::from_secs(3600));
+        let counter = client.create_counter("k1").unwrap();
+        transport.notifier.lock().as_ref().unwrap().notify();
+        awaitility::at_most(Duration::from_secs(3))
+            .until(|| counter.get_state() == DatatypeState::Subscribed);
+
+        drop(client);
+        assert!(transport.notifier.lock().is_none());
+    }
+}
@@ -0,0 +1,82 @@
+use std::sync::Arc;
+
+use tokio::sync::Notify;
+
+use crate::{
+    errors::clients::ClientError,
+    protocol::{PushPullRequest, PushPullResponse},
+    types::uid::Cuid,
+};
+
+/// A handle through which the server notifies a client that it has something to pull.
+///
+/// A notified client runs a synchronization as soon as possible instead of waiting for
+/// the next sync interval.
+#[derive(Clone, Default)]
+pub struct Notifier {
+    notify: Arc<Notify>,
+}
+
+impl Notifier {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Notifies the client that new transactions are available on the server.
+    pub fn notify(&self) {
+        self.notify.notify_one();
+    }
+
+    pub(crate) async fn notified(&self) {
+        self.notify.notified().await
+    }
+}
+
+/// Transport delivers the messages between a [`Client`](crate::Client) and a SyncYam server.
+///
+/// Implement this trait to connect clients with a server over any kind of network,
+/// and register it with [`ClientBuilder::with_transport`](crate::clients::client::ClientBuilder::with_transport).
+/// Methods are called from the background threads of the SDK, so they can block.
+pub trait Transport: Send + Sync {
+    /// Sends the pending transactions of datatypes and pulls the transactions
+    /// of other clients for them.
+    fn push_pull(&self, request: PushPullRequest) -> Result<PushPullResponse, ClientError>;
+
+    /// Connects the client `cuid`, so that the server can push notifications via `notifier`.
+    fn connect(&self, cuid: &Cuid, notifier: Notifier) -> Result<(), ClientError>;
+
+    /// Disconnects the client `cuid`.
+    fn disconnect(&self, cuid: &Cuid);
+}
+
+#[cfg(test)]
+mod tests_transport {
+    use std::{
+        sync::{
+            Arc,
+            atomic::{AtomicBool, Ordering},
+        },
+        time::Duration,
+    };
+
+    use crate::clients::transport::Notifier;
+
+    #[tokio::test]
+    async fn can_notify_before_and_after_waiting() {
+        let notifier = Notifier::new();
+        notifier.notify();
+        // a notification before waiting is not lost
+        notifier.notified().await;
+
+        let notified = Arc::new(AtomicBool::new(false));
+        let (n, flag) = (notifier.clone(), notified.clone());
+        let jh = tokio::spawn(async move {
+            n.notified().await;
+            flag.store(true, Ordering::SeqCst);
+        });
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        notifier.notify();
+        jh.await.unwrap();
+        assert!(notified.load(Ordering::SeqCst));
+    }
+}
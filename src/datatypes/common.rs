@@ -1,4 +1,12 @@
+use crate::types::{document_value::DocumentValue, primitive::Primitive};
+
 pub enum ReturnType {
     None,
     Counter(i64),
+    Variable(Primitive),
+    List(Vec<Primitive>),
+    Map(Vec<Option<Primitive>>),
+    Document(Vec<Option<DocumentValue>>),
+    Text(String),
+    Set(bool),
 }
@@ -1,4 +1,4 @@
-use std::{error::Error, sync::Arc};
+use std::{error::Error, sync::Arc, time::Duration};
 
 use crate::{
     DataType, DatatypeError, DatatypeState, IntoString,
@@ -34,6 +34,21 @@ impl Counter {
         }
     }
 
+    pub(crate) fn from_core(datatype: Arc<TransactionalDatatype>) -> Self {
+        Counter {
+            datatype,
+            tx_ctx: Default::default(),
+        }
+    }
+
+    /// Returns a handle of this counter operating in the transaction of `tx_ctx`.
+    pub(crate) fn with_tx_ctx(&self, tx_ctx: Arc<TransactionContext>) -> Self {
+        Counter {
+            datatype: self.datatype.clone(),
+            tx_ctx,
+        }
+    }
+
     datatype_instrument! {
     /// Increases the counter by the specified delta value.
     ///
@@ -107,16 +122,82 @@ impl Counter {
     /// ```
     pub fn get_value(&self) -> i64 {
         let mutable = self.datatype.mutable.read();
-        let Crdt::Counter(c) = &mutable.crdt;
+        let Crdt::Counter(c) = &mutable.crdt else {
+            unreachable!()
+        };
         c.value()
     }
 
+    /// Returns a handle of this counter whose operations and transactions wait at most
+    /// `timeout` for another transaction of the counter, overriding the lock timeout of
+    /// the client.
+    ///
+    /// Once the timeout elapses, they fail with [`DatatypeError::LockTimeout`], whereas
+    /// [`Counter::increase_by`] returns the value unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::time::Duration;
+    /// # use syncyam::{Client, DatatypeError};
+    /// let client = Client::builder("test-collection", "test-client").build().unwrap();
+    /// let counter = client.create_counter("test-counter").unwrap();
+    /// let impatient = counter.with_lock_timeout(Duration::from_millis(10));
+    /// counter
+    ///     .transaction("slow-update", move |c| {
+    ///         c.increase_by(2);
+    ///         let result = std::thread::spawn(move || {
+    ///             impatient.transaction("impatient-update", |c| Ok(c.increase()))
+    ///         })
+    ///         .join()
+    ///         .unwrap();
+    ///         assert!(matches!(result, Err(DatatypeError::LockTimeout(_))));
+    ///         Ok(())
+    ///     })
+    ///     .unwrap();
+    /// assert_eq!(counter.get_value(), 2);
+    /// ```
+    pub fn with_lock_timeout(&self, timeout: Duration) -> Self {
+        let tx_ctx = TransactionContext::with_lock_timeout(self.tx_ctx.clone(), timeout);
+        self.with_tx_ctx(Arc::new(tx_ctx))
+    }
+
+    /// Returns a handle of this counter whose transactions are marked as events, which
+    /// other clients receive by [`Datatype::on_remote_event`](crate::Datatype::on_remote_event).
+    ///
+    /// Only the transactions begun by the handle are marked; a transaction nested in
+    /// another one is committed as part of the outer one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use syncyam::{Client, Datatype};
+    /// let client = Client::builder("test-collection", "test-client").build().unwrap();
+    /// let counter = client.create_counter("test-counter").unwrap();
+    /// counter.after_commit(|tx| {
+    ///     if tx.is_event() {
+    ///         println!("order placed: {tx}");
+    ///     }
+    /// });
+    /// counter
+    ///     .as_event()
+    ///     .transaction("order-placed", |c| Ok(c.increase()))
+    ///     .unwrap();
+    /// ```
+    pub fn as_event(&self) -> Self {
+        self.with_tx_ctx(Arc::new(TransactionContext::for_event(self.tx_ctx.clone())))
+    }
+
     datatype_instrument! {
     /// Executes multiple operations atomically within a transaction.
     ///
-    /// If the transaction function returns an error, all operations within
+    /// If the transaction function returns an error or panics, all operations within
     /// the transaction are rolled back, leaving the counter unchanged.
     ///
+    /// A transaction begun with the counter given to `tx_func` is nested in this one:
+    /// if it fails, only its own operations are rolled back, and otherwise its
+    /// operations are committed along with this transaction under the outer tag.
+    ///
     /// # Arguments
     ///
     /// * `tag` - A descriptive label for the transaction
@@ -124,7 +205,8 @@ impl Counter {
     ///
     /// # Returns
     ///
-    /// `Ok(())` if the transaction succeeded, `Err(DatatypeError)` otherwise
+    /// `Ok` with the value returned by `tx_func` if the transaction succeeded,
+    /// `Err(DatatypeError)` otherwise
     ///
     /// # Examples
     ///
@@ -133,44 +215,102 @@ impl Counter {
     /// let client = Client::builder("test-collection", "test-client").build().unwrap();
     /// let counter = client.create_counter("test-counter".to_string()).unwrap();
     ///
-    /// // Successful transaction
+    /// // Successful transaction returning a value
     /// let result = counter.transaction("batch-update", |c| {
     ///     c.increase_by(10);
-    ///     c.increase_by(5);
-    ///     Ok(())
+    ///     Ok(c.increase_by(5))
     /// });
-    /// assert!(result.is_ok());
+    /// assert_eq!(result.unwrap(), 15);
     /// assert_eq!(counter.get_value(), 15);
     ///
     /// // Failed transaction - changes are rolled back
-    /// let result = counter.transaction("failing-update", |c| {
+    /// let result: Result<(), _> = counter.transaction("failing-update", |c| {
     ///     c.increase_by(100);
     ///     Err("something went wrong".into())
     /// });
     /// assert!(result.is_err());
     /// assert_eq!(counter.get_value(), 15); // unchanged
+    ///
+    /// // Nested transaction - only the inner changes are rolled back
+    /// let result = counter.transaction("outer-update", |c| {
+    ///     c.increase_by(1);
+    ///     let inner: Result<(), _> = c.transaction("inner-update", |c| {
+    ///         c.increase_by(100);
+    ///         Err("something went wrong".into())
+    ///     });
+    ///     assert!(inner.is_err());
+    ///     Ok(c.increase_by(2))
+    /// });
+    /// assert_eq!(result.unwrap(), 18);
     /// ```
-    pub fn transaction<T>(
+    pub fn transaction<T, R>(
         &self,
         tag: impl IntoString,
         tx_func: T,
-    ) -> Result<(), DatatypeError>
+    ) -> Result<R, DatatypeError>
     where
-        T: FnOnce(Self) -> Result<(), Box<dyn Error + Send + Sync>> + Send + Sync + 'static,
+        T: FnOnce(Self) -> Result<R, Box<dyn Error + Send + Sync>> + Send + Sync + 'static,
     {
-        let this_tx_ctx = Arc::new(TransactionContext::new(tag));
+        let this_tx_ctx = Arc::new(TransactionContext::new_within(tag, self.tx_ctx.clone()));
         let this_tx_ctx_clone = this_tx_ctx.clone();
         let do_tx_func = move || {
             let mut counter_clone = self.clone();
             counter_clone.tx_ctx = this_tx_ctx_clone.clone();
             match tx_func(counter_clone) {
-                Ok(_) => Ok(()),
+                Ok(r) => Ok(r),
                 Err(e) => Err(DatatypeError::FailedTransaction(e.to_string())),
             }
         };
         self.datatype
             .do_transaction(this_tx_ctx, do_tx_func)
     }}
+
+    datatype_instrument! {
+    /// Executes multiple operations atomically within a transaction like
+    /// [`Counter::transaction`], but yields to the executor instead of blocking the thread
+    /// while another transaction of this counter is running.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use syncyam::Client;
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let client = Client::builder("test-collection", "test-client").build().unwrap();
+    /// let counter = client.create_counter_async("test-counter").await.unwrap();
+    /// let result = counter
+    ///     .transaction_async("batch-update", |c| {
+    ///         c.increase_by(10);
+    ///         c.increase_by(5);
+    ///         Ok(())
+    ///     })
+    ///     .await;
+    /// assert!(result.is_ok());
+    /// assert_eq!(counter.get_value(), 15);
+    /// # }
+    /// ```
+    pub async fn transaction_async<T, R>(
+        &self,
+        tag: impl IntoString,
+        tx_func: T,
+    ) -> Result<R, DatatypeError>
+    where
+        T: FnOnce(Self) -> Result<R, Box<dyn Error + Send + Sync>> + Send + Sync + 'static,
+    {
+        let this_tx_ctx = Arc::new(TransactionContext::new_within(tag, self.tx_ctx.clone()));
+        let this_tx_ctx_clone = this_tx_ctx.clone();
+        let do_tx_func = move || {
+            let mut counter_clone = self.clone();
+            counter_clone.tx_ctx = this_tx_ctx_clone.clone();
+            match tx_func(counter_clone) {
+                Ok(r) => Ok(r),
+                Err(e) => Err(DatatypeError::FailedTransaction(e.to_string())),
+            }
+        };
+        self.datatype
+            .do_transaction_async(this_tx_ctx, do_tx_func)
+            .await
+    }}
 }
 
 impl DatatypeBlanket for Counter {
@@ -185,7 +325,7 @@ mod tests_counter {
     use tracing_opentelemetry::OpenTelemetrySpanExt;
 
     use crate::{
-        DataType,
+        DataType, DatatypeError,
         datatypes::{counter::Counter, datatype::Datatype},
     };
 
@@ -230,13 +370,12 @@ mod tests_counter {
         );
         let result1 = counter.transaction("success", |c| {
             c.increase_by(1);
-            c.increase_by(2);
-            Ok(())
+            Ok(c.increase_by(2))
         });
-        assert!(result1.is_ok());
+        assert_eq!(result1, Ok(3));
         assert_eq!(3, counter.get_value());
 
-        let result2 = counter.transaction("failure", |c| {
+        let result2: Result<(), _> = counter.transaction("failure", |c| {
             c.increase_by(11);
             c.increase_by(22);
             Err("failed".into())
@@ -276,4 +415,43 @@ mod tests_counter {
         }
         assert_eq!(1 + 2 + 3 + 4, counter.get_value());
     }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 10)]
+    #[instrument]
+    async fn can_run_panicking_transactions_concurrently() {
+        let counter = Counter::new(
+            module_path!().to_owned(),
+            Default::default(),
+            Default::default(),
+        );
+        let mut join_handles = vec![];
+        for i in 0..10 {
+            let counter = counter.clone();
+            join_handles.push(tokio::spawn(async move {
+                counter.transaction(format!("tag:{i}"), move |c| {
+                    c.increase_by(i);
+                    if i % 2 == 1 {
+                        panic!("odd: {i}");
+                    }
+                    // a panic in the nested transaction is not propagated.
+                    let nested: Result<(), _> = c.transaction("nested", |c| {
+                        c.increase_by(100);
+                        panic!("nested");
+                    });
+                    assert!(matches!(nested, Err(DatatypeError::FailedTransaction(_))));
+                    Ok(())
+                })
+            }));
+        }
+
+        let mut failures = 0;
+        for jh in join_handles {
+            if jh.await.unwrap().is_err() {
+                failures += 1;
+            }
+        }
+        assert_eq!(failures, 5);
+        assert_eq!(2 + 4 + 6 + 8, counter.get_value());
+        assert_eq!(21, counter.increase_by(1));
+    }
 }
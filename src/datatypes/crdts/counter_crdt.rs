@@ -1,10 +1,12 @@
 use crate::{
     DatatypeError,
     datatypes::common::ReturnType,
+    errors::err,
     operations::{Operation, body::OperationBody},
 };
 
 #[derive(Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CounterCrdt {
     value: i64,
 }
@@ -26,10 +28,20 @@ impl CounterCrdt {
                 Ok(ReturnType::Counter(ret))
             }
             #[allow(unreachable_patterns)]
-            _ => unimplemented!(),
+            _ => Err(err!(
+                DatatypeError::FailedToExecuteOperation,
+                format!("{} cannot be executed on a counter", op.body)
+            )),
         }
     }
 
+    pub fn execute_remote_operation(
+        &mut self,
+        op: &Operation,
+    ) -> Result<ReturnType, DatatypeError> {
+        self.execute_local_operation(op)
+    }
+
     #[inline]
     pub fn to_bytes(&self) -> [u8; 8] {
         self.value.to_le_bytes()
@@ -0,0 +1,771 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    DatatypeError,
+    datatypes::common::ReturnType,
+    errors::err,
+    operations::{Operation, body::OperationBody},
+    types::{
+        document_value::DocumentValue, element_id::ElementId, operation_id::OperationId,
+        primitive::Primitive,
+    },
+    utils::bytes::{BytesReader, BytesWriter},
+};
+
+const TAG_PRIMITIVE: u8 = 0;
+const TAG_OBJECT: u8 = 1;
+const TAG_ARRAY: u8 = 2;
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct ObjectEntry {
+    /// `None` is a tombstone of a removed key.
+    child: Option<ElementId>,
+    op_id: OperationId,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct ArrayElement {
+    /// The id of the node of the element.
+    id: ElementId,
+    deleted: bool,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+enum NodeValue {
+    Primitive(Primitive),
+    Object(BTreeMap<String, ObjectEntry>),
+    Array(Vec<ArrayElement>),
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct DocumentNode {
+    id: ElementId,
+    value: NodeValue,
+}
+
+/// A JSON-like tree of objects, arrays, and primitives.
+///
+/// Every node is identified by an [`ElementId`] derived from the operation creating it,
+/// where the nodes of a nested value are numbered in pre-order by `offset`. The root is
+/// an object identified by the default [`ElementId`].
+///
+/// The keys of an object are last-writer-wins registers like
+/// [`super::map_crdt::MapCrdt`], and an array is a replicated growable array like
+/// [`super::list_crdt::ListCrdt`], whose elements are nodes. Removed or overwritten nodes
+/// are kept, so that operations from other clients can still refer to them.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DocumentCrdt {
+    /// sorted by id
+    nodes: Vec<DocumentNode>,
+}
+
+impl Default for DocumentCrdt {
+    fn default() -> Self {
+        Self {
+            nodes: vec![DocumentNode {
+                id: ElementId::default(),
+                value: NodeValue::Object(BTreeMap::new()),
+            }],
+        }
+    }
+}
+
+impl DocumentCrdt {
+    /// Returns the value at `path`, or `None` if it does not exist.
+    pub fn get_by_path(&self, path: &str) -> Option<DocumentValue> {
+        self.resolve(path).ok().map(|id| self.materialize(&id))
+    }
+
+    /// Returns the value of the whole document.
+    pub fn to_value(&self) -> DocumentValue {
+        self.materialize(&ElementId::default())
+    }
+
+    fn node(&self, id: &ElementId) -> Option<&DocumentNode> {
+        self.nodes
+            .binary_search_by(|n| n.id.cmp(id))
+            .ok()
+            .map(|i| &self.nodes[i])
+    }
+
+    fn node_mut(&mut self, id: &ElementId) -> Option<&mut DocumentNode> {
+        self.nodes
+            .binary_search_by(|n| n.id.cmp(id))
+            .ok()
+            .map(|i| &mut self.nodes[i])
+    }
+
+    fn materialize(&self, id: &ElementId) -> DocumentValue {
+        let Some(node) = self.node(id) else {
+            return DocumentValue::default();
+        };
+        match &node.value {
+            NodeValue::Primitive(p) => DocumentValue::Primitive(p.clone()),
+            NodeValue::Object(entries) => DocumentValue::Object(
+                entries
+                    .iter()
+                    .filter_map(|(k, e)| e.child.as_ref().map(|c| (k.clone(), self.materialize(c))))
+                    .collect(),
+            ),
+            NodeValue::Array(elements) => DocumentValue::Array(
+                elements
+                    .iter()
+                    .filter(|e| !e.deleted)
+                    .map(|e| self.materialize(&e.id))
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Resolves `path`, a JSON pointer like `/users/0/name`, into the id of the node.
+    fn resolve(&self, path: &str) -> Result<ElementId, DatatypeError> {
+        let mut id = ElementId::default();
+        if path.is_empty() {
+            return Ok(id);
+        }
+        let Some(tokens) = path.strip_prefix('/') else {
+            return Err(err!(
+                DatatypeError::FailedToExecuteOperation,
+                format!("invalid path: {path:?}")
+            ));
+        };
+        for token in tokens.split('/') {
+            let token = token.replace("~1", "/").replace("~0", "~");
+            let child = match self.node(&id).map(|n| &n.value) {
+                Some(NodeValue::Object(entries)) => {
+                    entries.get(&token).and_then(|e| e.child.clone())
+                }
+                Some(NodeValue::Array(elements)) => token.parse::<usize>().ok().and_then(|pos| {
+                    elements
+                        .iter()
+                        .filter(|e| !e.deleted)
+                        .nth(pos)
+                        .map(|e| e.id.clone())
+                }),
+                _ => None,
+            };
+            let Some(child) = child else {
+                return Err(err!(
+                    DatatypeError::FailedToExecuteOperation,
+                    format!("no value at {token:?} of path {path:?}")
+                ));
+            };
+            id = child;
+        }
+        Ok(id)
+    }
+
+    /// Adds the nodes of `value`, numbering them from `offset` in pre-order,
+    /// and returns the id of the top node.
+    fn add_nodes(
+        &mut self,
+        value: &DocumentValue,
+        op_id: &OperationId,
+        offset: &mut u32,
+    ) -> ElementId {
+        let id = ElementId::new(op_id, *offset);
+        *offset += 1;
+        let value = match value {
+            DocumentValue::Primitive(p) => NodeValue::Primitive(p.clone()),
+            DocumentValue::Object(o) => NodeValue::Object(
+                o.iter()
+                    .map(|(k, v)| {
+                        let child = Some(self.add_nodes(v, op_id, offset));
+                        let op_id = op_id.clone();
+                        (k.clone(), ObjectEntry { child, op_id })
+                    })
+                    .collect(),
+            ),
+            DocumentValue::Array(a) => NodeValue::Array(
+                a.iter()
+                    .map(|v| ArrayElement {
+                        id: self.add_nodes(v, op_id, offset),
+                        deleted: false,
+                    })
+                    .collect(),
+            ),
+        };
+        if let Err(idx) = self.nodes.binary_search_by(|n| n.id.cmp(&id)) {
+            let node_id = id.clone();
+            self.nodes.insert(idx, DocumentNode { id: node_id, value });
+        }
+        id
+    }
+
+    fn object_mut(
+        &mut self,
+        target: &ElementId,
+    ) -> Result<&mut BTreeMap<String, ObjectEntry>, DatatypeError> {
+        match self.node_mut(target).map(|n| &mut n.value) {
+            Some(NodeValue::Object(entries)) => Ok(entries),
+            _ => Err(err!(
+                DatatypeError::FailedToExecuteOperation,
+                format!("no object: {target}")
+            )),
+        }
+    }
+
+    fn array_mut(&mut self, target: &ElementId) -> Result<&mut Vec<ArrayElement>, DatatypeError> {
+        match self.node_mut(target).map(|n| &mut n.value) {
+            Some(NodeValue::Array(elements)) => Ok(elements),
+            _ => Err(err!(
+                DatatypeError::FailedToExecuteOperation,
+                format!("no array: {target}")
+            )),
+        }
+    }
+
+    /// Writes `child` (`None` to remove) to `key` of the object `target` if `op_id` is not
+    /// older than that of the last writer, and returns the previous value.
+    fn write_to_object(
+        &mut self,
+        target: &ElementId,
+        key: &str,
+        child: Option<ElementId>,
+        op_id: &OperationId,
+    ) -> Result<Option<DocumentValue>, DatatypeError> {
+        let entries = self.object_mut(target)?;
+        let old = match entries.get_mut(key) {
+            Some(entry) if op_id < &entry.op_id => entry.child.clone(),
+            Some(entry) => {
+                entry.op_id = op_id.clone();
+                std::mem::replace(&mut entry.child, child)
+            }
+            None => {
+                let op_id = op_id.clone();
+                entries.insert(key.to_owned(), ObjectEntry { child, op_id });
+                None
+            }
+        };
+        Ok(old.map(|id| self.materialize(&id)))
+    }
+
+    pub fn put_to_object(
+        &mut self,
+        target: &ElementId,
+        entries: &[(String, DocumentValue)],
+        replace: bool,
+        op_id: &OperationId,
+    ) -> Result<Vec<Option<DocumentValue>>, DatatypeError> {
+        self.object_mut(target)?;
+        let mut offset = 0;
+        let mut olds = Vec::with_capacity(entries.len());
+        for (key, value) in entries.iter() {
+            let child = self.add_nodes(value, op_id, &mut offset);
+            olds.push(self.write_to_object(target, key, Some(child), op_id)?);
+        }
+        if replace {
+            let object = self.object_mut(target)?;
+            for (key, entry) in object.iter_mut() {
+                if &entry.op_id < op_id && !entries.iter().any(|(k, _)| k == key) {
+                    entry.child = None;
+                    entry.op_id = op_id.clone();
+                }
+            }
+        }
+        Ok(olds)
+    }
+
+    pub fn remove_in_object(
+        &mut self,
+        target: &ElementId,
+        key: &str,
+        op_id: &OperationId,
+    ) -> Result<Option<DocumentValue>, DatatypeError> {
+        self.write_to_object(target, key, None, op_id)
+    }
+
+    fn check_bound(elements: &[ArrayElement], pos: usize, len: usize) -> Result<(), DatatypeError> {
+        let size = elements.iter().filter(|e| !e.deleted).count();
+        if pos + len > size {
+            return Err(err!(
+                DatatypeError::FailedToExecuteOperation,
+                format!("out of bound: pos={pos}, len={len}, size={size}")
+            ));
+        }
+        Ok(())
+    }
+
+    /// Inserts `values` at `pos` of the array `target`, and returns the element after
+    /// which they are inserted.
+    pub fn insert_to_array_by_pos(
+        &mut self,
+        target: &ElementId,
+        pos: usize,
+        values: &[DocumentValue],
+        op_id: &OperationId,
+    ) -> Result<Option<ElementId>, DatatypeError> {
+        let elements = self.array_mut(target)?;
+        Self::check_bound(elements, pos, 0)?;
+        let prev = match pos {
+            0 => None,
+            _ => elements
+                .iter()
+                .filter(|e| !e.deleted)
+                .nth(pos - 1)
+                .map(|e| e.id.clone()),
+        };
+        self.insert_to_array_by_prev(target, prev.as_ref(), values, op_id)?;
+        Ok(prev)
+    }
+
+    pub fn insert_to_array_by_prev(
+        &mut self,
+        target: &ElementId,
+        prev: Option<&ElementId>,
+        values: &[DocumentValue],
+        op_id: &OperationId,
+    ) -> Result<(), DatatypeError> {
+        let elements = self.array_mut(target)?;
+        let mut idx = match prev {
+            None => 0,
+            Some(p) => match elements.iter().position(|e| &e.id == p) {
+                Some(i) => i + 1,
+                None => {
+                    return Err(err!(
+                        DatatypeError::FailedToExecuteOperation,
+                        format!("no previous element: {p}")
+                    ));
+                }
+            },
+        };
+        let mut offset = 0;
+        for value in values.iter() {
+            let id = self.add_nodes(value, op_id, &mut offset);
+            let elements = self.array_mut(target)?;
+            if elements.iter().any(|e| e.id == id) {
+                // already inserted
+                return Ok(());
+            }
+            while idx < elements.len() && elements[idx].id > id {
+                idx += 1;
+            }
+            elements.insert(idx, ArrayElement { id, deleted: false });
+            idx += 1;
+        }
+        Ok(())
+    }
+
+    /// Deletes `len` elements from `pos` of the array `target`, and returns the deleted
+    /// elements and their values.
+    pub fn delete_in_array_by_pos(
+        &mut self,
+        target: &ElementId,
+        pos: usize,
+        len: usize,
+    ) -> Result<(Vec<ElementId>, Vec<DocumentValue>), DatatypeError> {
+        let elements = self.array_mut(target)?;
+        Self::check_bound(elements, pos, len)?;
+        let targets: Vec<ElementId> = elements
+            .iter()
+            .filter(|e| !e.deleted)
+            .skip(pos)
+            .take(len)
+            .map(|e| e.id.clone())
+            .collect();
+        let deleted = self.delete_in_array_by_targets(target, &targets)?;
+        Ok((targets, deleted))
+    }
+
+    pub fn delete_in_array_by_targets(
+        &mut self,
+        target: &ElementId,
+        targets: &[ElementId],
+    ) -> Result<Vec<DocumentValue>, DatatypeError> {
+        let elements = self.array_mut(target)?;
+        let mut deleted_ids = vec![];
+        for element in elements.iter_mut() {
+            if !element.deleted && targets.contains(&element.id) {
+                element.deleted = true;
+                deleted_ids.push(element.id.clone());
+            }
+        }
+        Ok(deleted_ids.iter().map(|id| self.materialize(id)).collect())
+    }
+
+    fn target_of(target: &Option<ElementId>) -> Result<&ElementId, DatatypeError> {
+        target.as_ref().ok_or_else(|| {
+            err!(
+                DatatypeError::FailedToExecuteOperation,
+                "no target for the remote operation"
+            )
+        })
+    }
+
+    pub fn execute_local_operation(
+        &mut self,
+        op: &mut Operation,
+        op_id: &OperationId,
+    ) -> Result<ReturnType, DatatypeError> {
+        match op.body {
+            OperationBody::DocumentPutToObject(ref mut body) => {
+                let target = self.resolve(&body.path)?;
+                let olds = self.put_to_object(&target, &body.entries, body.replace, op_id)?;
+                body.target = Some(target);
+                Ok(ReturnType::Document(olds))
+            }
+            OperationBody::DocumentRemoveInObject(ref mut body) => {
+                let target = self.resolve(&body.path)?;
+                let old = self.remove_in_object(&target, &body.key, op_id)?;
+                body.target = Some(target);
+                Ok(ReturnType::Document(vec![old]))
+            }
+            OperationBody::DocumentInsertToArray(ref mut body) => {
+                let target = self.resolve(&body.path)?;
+                body.prev = self.insert_to_array_by_pos(&target, body.pos, &body.values, op_id)?;
+                body.target = Some(target);
+                Ok(ReturnType::Document(vec![]))
+            }
+            OperationBody::DocumentDeleteInArray(ref mut body) => {
+                let target = self.resolve(&body.path)?;
+                let (targets, deleted) =
+                    self.delete_in_array_by_pos(&target, body.pos, body.len)?;
+                body.target = Some(target);
+                body.targets = targets;
+                Ok(ReturnType::Document(
+                    deleted.into_iter().map(Some).collect(),
+                ))
+            }
+            #[allow(unreachable_patterns)]
+            _ => Err(err!(
+                DatatypeError::FailedToExecuteOperation,
+                format!("{} cannot be executed on a document", op.body)
+            )),
+        }
+    }
+
+    pub fn execute_remote_operation(
+        &mut self,
+        op: &Operation,
+        op_id: &OperationId,
+    ) -> Result<ReturnType, DatatypeError> {
+        match op.body {
+            OperationBody::DocumentPutToObject(ref body) => {
+                let target = Self::target_of(&body.target)?;
+                let olds = self.put_to_object(target, &body.entries, body.replace, op_id)?;
+                Ok(ReturnType::Document(olds))
+            }
+            OperationBody::DocumentRemoveInObject(ref body) => {
+                let target = Self::target_of(&body.target)?;
+                let old = self.remove_in_object(target, &body.key, op_id)?;
+                Ok(ReturnType::Document(vec![old]))
+            }
+            OperationBody::DocumentInsertToArray(ref body) => {
+                let target = Self::target_of(&body.target)?;
+                self.insert_to_array_by_prev(target, body.prev.as_ref(), &body.values, op_id)?;
+                Ok(ReturnType::Document(vec![]))
+            }
+            OperationBody::DocumentDeleteInArray(ref body) => {
+                let target = Self::target_of(&body.target)?;
+                let deleted = self.delete_in_array_by_targets(target, &body.targets)?;
+                Ok(ReturnType::Document(
+                    deleted.into_iter().map(Some).collect(),
+                ))
+            }
+            #[allow(unreachable_patterns)]
+            _ => Err(err!(
+                DatatypeError::FailedToExecuteOperation,
+                format!("{} cannot be executed on a document", op.body)
+            )),
+        }
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut writer = BytesWriter::new();
+        writer.write_u32(self.nodes.len() as u32);
+        for node in self.nodes.iter() {
+            node.id.write_to(&mut writer);
+            match &node.value {
+                NodeValue::Primitive(p) => {
+                    writer.write_u8(TAG_PRIMITIVE);
+                    p.write_to(&mut writer);
+                }
+                NodeValue::Object(entries) => {
+                    writer.write_u8(TAG_OBJECT);
+                    writer.write_u32(entries.len() as u32);
+                    for (key, entry) in entries.iter() {
+                        writer.write_str(key);
+                        entry.op_id.write_to(&mut writer);
+                        writer.write_bool(entry.child.is_some());
+                        if let Some(child) = &entry.child {
+                            child.write_to(&mut writer);
+                        }
+                    }
+                }
+                NodeValue::Array(elements) => {
+                    writer.write_u8(TAG_ARRAY);
+                    writer.write_u32(elements.len() as u32);
+                    for element in elements.iter() {
+                        element.id.write_to(&mut writer);
+                        writer.write_bool(element.deleted);
+                    }
+                }
+            }
+        }
+        writer.into_bytes()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DatatypeError> {
+        let mut reader = BytesReader::new(bytes);
+        let len = reader.read_u32()?;
+        // not preallocated by `len`, which is read from untrusted bytes.
+        let mut nodes: Vec<DocumentNode> = Vec::new();
+        for _ in 0..len {
+            let id = ElementId::read_from(&mut reader)?;
+            let value = match reader.read_u8()? {
+                TAG_PRIMITIVE => NodeValue::Primitive(Primitive::read_from(&mut reader)?),
+                TAG_OBJECT => {
+                    let mut entries = BTreeMap::new();
+                    for _ in 0..reader.read_u32()? {
+                        let key = reader.read_string()?;
+                        let op_id = OperationId::read_from(&mut reader)?;
+                        let child = match reader.read_bool()? {
+                            true => Some(ElementId::read_from(&mut reader)?),
+                            false => None,
+                        };
+                        entries.insert(key, ObjectEntry { child, op_id });
+                    }
+                    NodeValue::Object(entries)
+                }
+                TAG_ARRAY => {
+                    let count = reader.read_u32()?;
+                    let elements = (0..count)
+                        .map(|_| {
+                            Ok(ArrayElement {
+                                id: ElementId::read_from(&mut reader)?,
+                                deleted: reader.read_bool()?,
+                            })
+                        })
+                        .collect::<Result<_, DatatypeError>>()?;
+                    NodeValue::Array(elements)
+                }
+                tag => {
+                    return Err(DatatypeError::FailedToDeserialize(format!(
+                        "unknown document node tag: {tag}"
+                    )));
+                }
+            };
+            if nodes.last().is_some_and(|last| last.id >= id) {
+                return Err(DatatypeError::FailedToDeserialize(
+                    "unsorted nodes in document crdt".to_owned(),
+                ));
+            }
+            nodes.push(DocumentNode { id, value });
+        }
+        if !reader.is_empty() {
+            return Err(DatatypeError::FailedToDeserialize(
+                "trailing bytes in document crdt".to_owned(),
+            ));
+        }
+        let document = Self { nodes };
+        if !matches!(
+            document.node(&ElementId::default()).map(|n| &n.value),
+            Some(NodeValue::Object(_))
+        ) {
+            return Err(DatatypeError::FailedToDeserialize(
+                "no root object in document crdt".to_owned(),
+            ));
+        }
+        Ok(document)
+    }
+}
+
+#[cfg(test)]
+mod tests_document_crdt {
+    use crate::{
+        DatatypeError,
+        datatypes::crdts::document_crdt::DocumentCrdt,
+        operations::Operation,
+        types::{document_value::DocumentValue, operation_id::OperationId, uid::Cuid},
+    };
+
+    fn new_op_id(cuid: &str) -> OperationId {
+        let mut op_id = OperationId::new_with_cuid(&Cuid::try_from(cuid).unwrap());
+        op_id.next_lamport();
+        op_id
+    }
+
+    /// Executes `op` locally, and returns it with the operation id to execute it remotely.
+    fn execute(
+        doc: &mut DocumentCrdt,
+        mut op: Operation,
+        op_id: &mut OperationId,
+    ) -> (Operation, OperationId) {
+        doc.execute_local_operation(&mut op, op_id).unwrap();
+        let executed = op_id.clone();
+        op_id.next_lamport();
+        (op, executed)
+    }
+
+    #[test]
+    fn can_edit_nested_objects_and_arrays() {
+        let mut op_id = new_op_id("0000000000000001");
+        let mut doc = DocumentCrdt::default();
+        let user = DocumentValue::from_iter([
+            ("name", DocumentValue::from("yam")),
+            ("tags", DocumentValue::Array(vec!["a".into(), "c".into()])),
+        ]);
+        execute(
+            &mut doc,
+            Operation::new_document_put_to_object("".into(), vec![("user".into(), user)], false),
+            &mut op_id,
+        );
+        execute(
+            &mut doc,
+            Operation::new_document_insert_to_array("/user/tags".into(), 1, vec!["b".into()]),
+            &mut op_id,
+        );
+        assert_eq!(
+            doc.get_by_path("/user/tags"),
+            Some(DocumentValue::Array(vec![
+                "a".into(),
+                "b".into(),
+                "c".into()
+            ]))
+        );
+        assert_eq!(doc.get_by_path("/user/tags/1"), Some("b".into()));
+        assert_eq!(doc.get_by_path("/user/name"), Some("yam".into()));
+        assert_eq!(doc.get_by_path("/user/none"), None);
+        assert_eq!(doc.get_by_path("user"), None);
+
+        execute(
+            &mut doc,
+            Operation::new_document_delete_in_array("/user/tags".into(), 0, 2),
+            &mut op_id,
+        );
+        execute(
+            &mut doc,
+            Operation::new_document_remove_in_object("/user".into(), "name".into()),
+            &mut op_id,
+        );
+        let expected = DocumentValue::from_iter([(
+            "user",
+            DocumentValue::from_iter([("tags", DocumentValue::Array(vec!["c".into()]))]),
+        )]);
+        assert_eq!(doc.to_value(), expected);
+
+        // invalid paths and positions fail
+        let mut op = Operation::new_document_insert_to_array("/user".into(), 0, vec![1.into()]);
+        assert!(doc.execute_local_operation(&mut op, &op_id).is_err());
+        let mut op = Operation::new_document_delete_in_array("/user/tags".into(), 0, 2);
+        assert!(doc.execute_local_operation(&mut op, &op_id).is_err());
+        assert_eq!(doc.to_value(), expected);
+    }
+
+    #[test]
+    fn can_converge_concurrent_edits() {
+        let mut op_id1 = new_op_id("0000000000000001");
+        let mut op_id2 = new_op_id("0000000000000002");
+        let mut doc1 = DocumentCrdt::default();
+        let mut doc2 = DocumentCrdt::default();
+
+        let (op, remote_op_id) = execute(
+            &mut doc1,
+            Operation::new_document_put_to_object(
+                "".into(),
+                vec![("list".into(), DocumentValue::new_array())],
+                false,
+            ),
+            &mut op_id1,
+        );
+        doc2.execute_remote_operation(&op, &remote_op_id).unwrap();
+        op_id2.lamport = op_id1.lamport;
+
+        let local1 = [
+            execute(
+                &mut doc1,
+                Operation::new_document_insert_to_array("/list".into(), 0, vec![1.into()]),
+                &mut op_id1,
+            ),
+            execute(
+                &mut doc1,
+                Operation::new_document_put_to_object(
+                    "".into(),
+                    vec![("k".into(), "a".into())],
+                    false,
+                ),
+                &mut op_id1,
+            ),
+        ];
+        let local2 = [
+            execute(
+                &mut doc2,
+                Operation::new_document_insert_to_array(
+                    "/list".into(),
+                    0,
+                    vec![2.into(), 3.into()],
+                ),
+                &mut op_id2,
+            ),
+            execute(
+                &mut doc2,
+                Operation::new_document_put_to_object(
+                    "".into(),
+                    vec![("k".into(), "b".into())],
+                    false,
+                ),
+                &mut op_id2,
+            ),
+        ];
+        for (op, remote_op_id) in local2.iter() {
+            doc1.execute_remote_operation(op, remote_op_id).unwrap();
+        }
+        for (op, remote_op_id) in local1.iter() {
+            doc2.execute_remote_operation(op, remote_op_id).unwrap();
+        }
+        assert_eq!(doc1.to_value(), doc2.to_value());
+        assert_eq!(doc1.get_by_path("/k"), Some("b".into()));
+        assert_eq!(
+            doc1.get_by_path("/list").unwrap().as_array().unwrap().len(),
+            3
+        );
+    }
+
+    #[test]
+    fn can_serialize_and_deserialize_document_crdt() {
+        let mut op_id = new_op_id("0000000000000001");
+        let mut doc = DocumentCrdt::default();
+        execute(
+            &mut doc,
+            Operation::new_document_put_to_object(
+                "".into(),
+                vec![
+                    (
+                        "a".into(),
+                        DocumentValue::Array(vec![1.into(), DocumentValue::new_object()]),
+                    ),
+                    ("b".into(), "x".into()),
+                ],
+                false,
+            ),
+            &mut op_id,
+        );
+        execute(
+            &mut doc,
+            Operation::new_document_put_to_object("".into(), vec![("c".into(), 3.into())], true),
+            &mut op_id,
+        );
+        assert_eq!(doc.to_value(), DocumentValue::from_iter([("c", 3)]));
+
+        let bytes = doc.to_bytes();
+        let deserialized = DocumentCrdt::from_bytes(&bytes).unwrap();
+        assert_eq!(deserialized.to_value(), doc.to_value());
+        assert_eq!(deserialized.to_bytes(), bytes);
+        assert!(DocumentCrdt::from_bytes(&bytes[1..]).is_err());
+        for len in 0..bytes.len() {
+            assert!(DocumentCrdt::from_bytes(&bytes[..len]).is_err());
+        }
+        // a huge length is rejected by the bytes left, without allocating for it.
+        let mut huge = bytes.clone();
+        huge[..4].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert_eq!(
+            DocumentCrdt::from_bytes(&huge).unwrap_err(),
+            DatatypeError::FailedToDeserialize("".into())
+        );
+    }
+}
@@ -0,0 +1,447 @@
+use crate::{
+    DatatypeError,
+    datatypes::common::ReturnType,
+    errors::err,
+    operations::{Operation, body::OperationBody},
+    types::{element_id::ElementId, operation_id::OperationId, primitive::Primitive},
+    utils::bytes::{BytesReader, BytesWriter},
+};
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct ListNode {
+    id: ElementId,
+    value: Primitive,
+    updated: ElementId,
+    deleted: bool,
+}
+
+/// A replicated growable array (RGA).
+///
+/// Every element is identified by an [`ElementId`] derived from the timestamp of the
+/// operation inserting it. Elements are kept in a single sequence including deleted
+/// ones (tombstones), so that operations from other clients can still refer to them.
+/// Concurrent inserts after the same element are ordered by descending [`ElementId`],
+/// which lets every client converge to the same sequence.
+#[derive(Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ListCrdt {
+    nodes: Vec<ListNode>,
+    size: usize,
+}
+
+impl ListCrdt {
+    pub fn len(&self) -> usize {
+        self.size
+    }
+
+    pub fn get(&self, pos: usize) -> Option<&Primitive> {
+        self.alive_nodes().nth(pos).map(|n| &n.value)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Primitive> {
+        self.alive_nodes().map(|n| &n.value)
+    }
+
+    fn alive_nodes(&self) -> impl Iterator<Item = &ListNode> {
+        self.nodes.iter().filter(|n| !n.deleted)
+    }
+
+    /// Returns the index in `nodes` of the alive element at `pos`.
+    fn index_of_pos(&self, pos: usize) -> Option<usize> {
+        self.nodes
+            .iter()
+            .enumerate()
+            .filter(|(_, n)| !n.deleted)
+            .nth(pos)
+            .map(|(i, _)| i)
+    }
+
+    fn index_of_id(&self, id: &ElementId) -> Option<usize> {
+        self.nodes.iter().position(|n| &n.id == id)
+    }
+
+    fn check_bound(&self, pos: usize, len: usize) -> Result<(), DatatypeError> {
+        if pos + len > self.size {
+            return Err(err!(
+                DatatypeError::FailedToExecuteOperation,
+                format!("out of bound: pos={pos}, len={len}, size={}", self.size)
+            ));
+        }
+        Ok(())
+    }
+
+    /// Inserts `values` at `pos`, and returns the element after which they are inserted.
+    pub fn insert_by_pos(
+        &mut self,
+        pos: usize,
+        values: &[Primitive],
+        op_id: &OperationId,
+    ) -> Result<Option<ElementId>, DatatypeError> {
+        self.check_bound(pos, 0)?;
+        let target = match pos {
+            0 => None,
+            _ => self.index_of_pos(pos - 1).map(|i| self.nodes[i].id.clone()),
+        };
+        self.insert_by_target(target.as_ref(), values, op_id)?;
+        Ok(target)
+    }
+
+    pub fn insert_by_target(
+        &mut self,
+        target: Option<&ElementId>,
+        values: &[Primitive],
+        op_id: &OperationId,
+    ) -> Result<(), DatatypeError> {
+        let mut idx = match target {
+            None => 0,
+            Some(t) => match self.index_of_id(t) {
+                Some(i) => i + 1,
+                None => {
+                    return Err(err!(
+                        DatatypeError::FailedToExecuteOperation,
+                        format!("no target element: {t}")
+                    ));
+                }
+            },
+        };
+        for (offset, value) in values.iter().enumerate() {
+            let id = ElementId::new(op_id, offset as u32);
+            if self.index_of_id(&id).is_some() {
+                // already inserted
+                return Ok(());
+            }
+            while idx < self.nodes.len() && self.nodes[idx].id > id {
+                idx += 1;
+            }
+            self.nodes.insert(
+                idx,
+                ListNode {
+                    id: id.clone(),
+                    value: value.clone(),
+                    updated: id,
+                    deleted: false,
+                },
+            );
+            self.size += 1;
+            idx += 1;
+        }
+        Ok(())
+    }
+
+    /// Deletes `len` elements from `pos`, and returns the deleted elements and their values.
+    pub fn delete_by_pos(
+        &mut self,
+        pos: usize,
+        len: usize,
+    ) -> Result<(Vec<ElementId>, Vec<Primitive>), DatatypeError> {
+        self.check_bound(pos, len)?;
+        let mut targets = Vec::with_capacity(len);
+        let mut deleted = Vec::with_capacity(len);
+        let mut idx = self.index_of_pos(pos).unwrap_or(self.nodes.len());
+        while targets.len() < len {
+            let node = &mut self.nodes[idx];
+            if !node.deleted {
+                node.deleted = true;
+                targets.push(node.id.clone());
+                deleted.push(node.value.clone());
+            }
+            idx += 1;
+        }
+        self.size -= len;
+        Ok((targets, deleted))
+    }
+
+    pub fn delete_by_targets(&mut self, targets: &[ElementId]) -> Vec<Primitive> {
+        let mut deleted = vec![];
+        for target in targets {
+            if let Some(idx) = self.index_of_id(target) {
+                let node = &mut self.nodes[idx];
+                if !node.deleted {
+                    node.deleted = true;
+                    self.size -= 1;
+                    deleted.push(node.value.clone());
+                }
+            }
+        }
+        deleted
+    }
+
+    /// Updates the element at `pos`, and returns the updated element and its previous value.
+    pub fn update_by_pos(
+        &mut self,
+        pos: usize,
+        value: Primitive,
+        op_id: &OperationId,
+    ) -> Result<(ElementId, Primitive), DatatypeError> {
+        self.check_bound(pos, 1)?;
+        let idx = self.index_of_pos(pos).unwrap();
+        let target = self.nodes[idx].id.clone();
+        let old = self.update_by_target(&target, value, op_id)?;
+        Ok((target, old))
+    }
+
+    /// Updates the value of `target` if `op_id` is newer than that of the last update,
+    /// and returns the previous value.
+    pub fn update_by_target(
+        &mut self,
+        target: &ElementId,
+        value: Primitive,
+        op_id: &OperationId,
+    ) -> Result<Primitive, DatatypeError> {
+        let Some(idx) = self.index_of_id(target) else {
+            return Err(err!(
+                DatatypeError::FailedToExecuteOperation,
+                format!("no target element: {target}")
+            ));
+        };
+        let node = &mut self.nodes[idx];
+        let updated = ElementId::new(op_id, 0);
+        if updated > node.updated {
+            node.updated = updated;
+            return Ok(std::mem::replace(&mut node.value, value));
+        }
+        Ok(node.value.clone())
+    }
+
+    pub fn execute_local_operation(
+        &mut self,
+        op: &mut Operation,
+        op_id: &OperationId,
+    ) -> Result<ReturnType, DatatypeError> {
+        match op.body {
+            OperationBody::ListInsert(ref mut body) => {
+                body.target = self.insert_by_pos(body.pos, &body.values, op_id)?;
+                Ok(ReturnType::List(vec![]))
+            }
+            OperationBody::ListDelete(ref mut body) => {
+                let (targets, deleted) = self.delete_by_pos(body.pos, body.len)?;
+                body.targets = targets;
+                Ok(ReturnType::List(deleted))
+            }
+            OperationBody::ListUpdate(ref mut body) => {
+                let (target, old) = self.update_by_pos(body.pos, body.value.clone(), op_id)?;
+                body.target = Some(target);
+                Ok(ReturnType::List(vec![old]))
+            }
+            #[allow(unreachable_patterns)]
+            _ => Err(err!(
+                DatatypeError::FailedToExecuteOperation,
+                format!("{} cannot be executed on a list", op.body)
+            )),
+        }
+    }
+
+    pub fn execute_remote_operation(
+        &mut self,
+        op: &Operation,
+        op_id: &OperationId,
+    ) -> Result<ReturnType, DatatypeError> {
+        match op.body {
+            OperationBody::ListInsert(ref body) => {
+                self.insert_by_target(body.target.as_ref(), &body.values, op_id)?;
+                Ok(ReturnType::List(vec![]))
+            }
+            OperationBody::ListDelete(ref body) => {
+                Ok(ReturnType::List(self.delete_by_targets(&body.targets)))
+            }
+            OperationBody::ListUpdate(ref body) => {
+                let Some(target) = body.target.as_ref() else {
+                    return Err(err!(
+                        DatatypeError::FailedToExecuteOperation,
+                        "no target for the remote update"
+                    ));
+                };
+                let old = self.update_by_target(target, body.value.clone(), op_id)?;
+                Ok(ReturnType::List(vec![old]))
+            }
+            #[allow(unreachable_patterns)]
+            _ => Err(err!(
+                DatatypeError::FailedToExecuteOperation,
+                format!("{} cannot be executed on a list", op.body)
+            )),
+        }
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut writer = BytesWriter::new();
+        writer.write_u32(self.nodes.len() as u32);
+        for node in self.nodes.iter() {
+            node.id.write_to(&mut writer);
+            node.updated.write_to(&mut writer);
+            writer.write_u8(node.deleted as u8);
+            node.value.write_to(&mut writer);
+        }
+        writer.into_bytes()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DatatypeError> {
+        let mut reader = BytesReader::new(bytes);
+        let len = reader.read_u32()? as usize;
+        let mut list = Self::default();
+        for _ in 0..len {
+            let node = ListNode {
+                id: ElementId::read_from(&mut reader)?,
+                updated: ElementId::read_from(&mut reader)?,
+                deleted: reader.read_u8()? != 0,
+                value: Primitive::read_from(&mut reader)?,
+            };
+            if !node.deleted {
+                list.size += 1;
+            }
+            list.nodes.push(node);
+        }
+        if !reader.is_empty() {
+            return Err(DatatypeError::FailedToDeserialize(
+                "trailing bytes in list crdt".to_owned(),
+            ));
+        }
+        Ok(list)
+    }
+}
+
+#[cfg(test)]
+mod tests_list_crdt {
+    use crate::{
+        datatypes::crdts::list_crdt::ListCrdt,
+        operations::Operation,
+        types::{operation_id::OperationId, primitive::Primitive, uid::Cuid},
+    };
+
+    fn values(list: &ListCrdt) -> Vec<Primitive> {
+        list.iter().cloned().collect()
+    }
+
+    fn prims(v: &[i64]) -> Vec<Primitive> {
+        v.iter().map(|i| Primitive::from(*i)).collect()
+    }
+
+    #[test]
+    fn can_insert_delete_and_update_by_pos() {
+        let mut op_id = OperationId::new_with_cuid(&Cuid::new());
+        let mut list = ListCrdt::default();
+        op_id.next_lamport();
+        list.insert_by_pos(0, &prims(&[1, 2, 3]), &op_id).unwrap();
+        op_id.next_lamport();
+        list.insert_by_pos(1, &prims(&[4, 5]), &op_id).unwrap();
+        assert_eq!(values(&list), prims(&[1, 4, 5, 2, 3]));
+        assert!(list.insert_by_pos(6, &prims(&[0]), &op_id).is_err());
+
+        let (targets, deleted) = list.delete_by_pos(1, 3).unwrap();
+        assert_eq!(targets.len(), 3);
+        assert_eq!(deleted, prims(&[4, 5, 2]));
+        assert_eq!(values(&list), prims(&[1, 3]));
+        assert!(list.delete_by_pos(1, 2).is_err());
+
+        op_id.next_lamport();
+        let (_, old) = list.update_by_pos(1, Primitive::from("x"), &op_id).unwrap();
+        assert_eq!(old, Primitive::from(3));
+        assert_eq!(list.get(1), Some(&Primitive::from("x")));
+        assert_eq!(list.get(2), None);
+        assert_eq!(list.len(), 2);
+    }
+
+    #[test]
+    fn can_converge_concurrent_inserts_at_the_same_position() {
+        let mut op_id1 = OperationId::new_with_cuid(&Cuid::try_from("0000000000000001").unwrap());
+        let mut op_id2 = OperationId::new_with_cuid(&Cuid::try_from("0000000000000002").unwrap());
+        op_id1.next_lamport();
+        let mut list1 = ListCrdt::default();
+        let mut list2 = ListCrdt::default();
+        list1.insert_by_pos(0, &prims(&[0]), &op_id1).unwrap();
+        list2.insert_by_target(None, &prims(&[0]), &op_id1).unwrap();
+        op_id2.sync(&op_id1);
+
+        // both clients insert after the first element concurrently
+        op_id1.next_lamport();
+        op_id2.next_lamport();
+        let t1 = list1.insert_by_pos(1, &prims(&[1, 1]), &op_id1).unwrap();
+        let t2 = list2.insert_by_pos(1, &prims(&[2, 2]), &op_id2).unwrap();
+        list1
+            .insert_by_target(t2.as_ref(), &prims(&[2, 2]), &op_id2)
+            .unwrap();
+        list2
+            .insert_by_target(t1.as_ref(), &prims(&[1, 1]), &op_id1)
+            .unwrap();
+        assert_eq!(values(&list1), prims(&[0, 2, 2, 1, 1]));
+        assert_eq!(values(&list1), values(&list2));
+    }
+
+    #[test]
+    fn can_converge_concurrent_updates_and_deletes() {
+        let mut op_id1 = OperationId::new_with_cuid(&Cuid::try_from("0000000000000001").unwrap());
+        let mut op_id2 = OperationId::new_with_cuid(&Cuid::try_from("0000000000000002").unwrap());
+        op_id1.next_lamport();
+        let mut list1 = ListCrdt::default();
+        let mut list2 = ListCrdt::default();
+        list1.insert_by_pos(0, &prims(&[0, 1]), &op_id1).unwrap();
+        list2
+            .insert_by_target(None, &prims(&[0, 1]), &op_id1)
+            .unwrap();
+        op_id2.sync(&op_id1);
+
+        op_id1.next_lamport();
+        op_id2.next_lamport();
+        let (u1, _) = list1.update_by_pos(0, "a".into(), &op_id1).unwrap();
+        let (u2, _) = list2.update_by_pos(0, "b".into(), &op_id2).unwrap();
+        let (d2, _) = list2.delete_by_pos(1, 1).unwrap();
+        list1.update_by_target(&u2, "b".into(), &op_id2).unwrap();
+        list1.delete_by_targets(&d2);
+        list2.update_by_target(&u1, "a".into(), &op_id1).unwrap();
+        assert!(list2.delete_by_targets(&d2).is_empty());
+
+        assert_eq!(values(&list1), vec![Primitive::from("b")]);
+        assert_eq!(values(&list1), values(&list2));
+    }
+
+    #[test]
+    fn can_execute_remote_operations() {
+        let mut op_id1 = OperationId::new_with_cuid(&Cuid::try_from("0000000000000001").unwrap());
+        let op_id2 = OperationId::new_with_cuid(&Cuid::try_from("0000000000000002").unwrap());
+        let mut list1 = ListCrdt::default();
+        let mut list2 = ListCrdt::default();
+
+        let mut ops = [
+            Operation::new_list_insert(0, prims(&[1, 2, 3])),
+            Operation::new_list_delete(0, 1),
+            Operation::new_list_update(1, "x".into()),
+        ];
+        for op in ops.iter_mut() {
+            op_id1.next_lamport();
+            list1.execute_local_operation(op, &op_id1).unwrap();
+        }
+        for (i, op) in ops.iter().enumerate() {
+            let mut remote_op_id = op_id1.clone();
+            remote_op_id.lamport = i as u64 + 1;
+            list2.execute_remote_operation(op, &remote_op_id).unwrap();
+        }
+        assert_eq!(
+            values(&list1),
+            vec![Primitive::from(2), Primitive::from("x")]
+        );
+        assert_eq!(values(&list1), values(&list2));
+
+        let mut no_target = Operation::new_list_update(0, "y".into());
+        assert!(list2.execute_remote_operation(&no_target, &op_id2).is_err());
+        list1
+            .execute_local_operation(&mut no_target, &op_id1)
+            .unwrap();
+        assert!(list2.execute_remote_operation(&no_target, &op_id2).is_ok());
+    }
+
+    #[test]
+    fn can_serialize_and_deserialize_list_crdt() {
+        let mut op_id = OperationId::new_with_cuid(&Cuid::new());
+        let mut list = ListCrdt::default();
+        op_id.next_lamport();
+        list.insert_by_pos(0, &prims(&[1, 2, 3]), &op_id).unwrap();
+        list.delete_by_pos(1, 1).unwrap();
+
+        let bytes = list.to_bytes();
+        let deserialized = ListCrdt::from_bytes(&bytes).unwrap();
+        assert_eq!(values(&deserialized), prims(&[1, 3]));
+        assert_eq!(deserialized.len(), 2);
+        assert_eq!(deserialized.nodes.len(), 3);
+        assert!(ListCrdt::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+    }
+}
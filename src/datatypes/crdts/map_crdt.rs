@@ -0,0 +1,255 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    DatatypeError,
+    datatypes::common::ReturnType,
+    errors::err,
+    operations::{Operation, body::OperationBody},
+    types::{operation_id::OperationId, primitive::Primitive},
+    utils::bytes::{BytesReader, BytesWriter},
+};
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct MapEntry {
+    /// `None` is a tombstone of a removed key.
+    value: Option<Primitive>,
+    op_id: OperationId,
+}
+
+/// A map whose keys are last-writer-wins registers.
+///
+/// Concurrent writes to the same key are resolved by comparing the [`OperationId`]s of
+/// the writers, like [`super::variable_crdt::VariableCrdt`]. A removed key is kept as a
+/// tombstone with the [`OperationId`] of the remove, so that a remove and a concurrent
+/// put converge regardless of the order in which they are applied.
+#[derive(Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MapCrdt {
+    entries: BTreeMap<String, MapEntry>,
+    size: usize,
+}
+
+impl MapCrdt {
+    pub fn get(&self, key: &str) -> Option<&Primitive> {
+        self.entries.get(key).and_then(|e| e.value.as_ref())
+    }
+
+    pub fn len(&self) -> usize {
+        self.size
+    }
+
+    /// Returns the keys in ascending order.
+    pub fn keys(&self) -> impl Iterator<Item = &String> {
+        self.iter().map(|(k, _)| k)
+    }
+
+    /// Returns the entries in ascending order of keys.
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &Primitive)> {
+        self.entries
+            .iter()
+            .filter_map(|(k, e)| e.value.as_ref().map(|v| (k, v)))
+    }
+
+    /// Writes `value` (`None` to remove) to `key` if `op_id` is not older than that of
+    /// the last writer, and returns the previous value.
+    ///
+    /// An equal `op_id` is accepted, so that the last of the same keys in an operation wins.
+    fn write(
+        &mut self,
+        key: &str,
+        value: Option<Primitive>,
+        op_id: &OperationId,
+    ) -> Option<Primitive> {
+        let Some(entry) = self.entries.get_mut(key) else {
+            if value.is_some() {
+                self.size += 1;
+            }
+            let op_id = op_id.clone();
+            self.entries
+                .insert(key.to_owned(), MapEntry { value, op_id });
+            return None;
+        };
+        if op_id < &entry.op_id {
+            return entry.value.clone();
+        }
+        match (&entry.value, &value) {
+            (None, Some(_)) => self.size += 1,
+            (Some(_), None) => self.size -= 1,
+            _ => {}
+        }
+        entry.op_id = op_id.clone();
+        std::mem::replace(&mut entry.value, value)
+    }
+
+    pub fn put(&mut self, key: &str, value: Primitive, op_id: &OperationId) -> Option<Primitive> {
+        self.write(key, Some(value), op_id)
+    }
+
+    pub fn remove(&mut self, key: &str, op_id: &OperationId) -> Option<Primitive> {
+        self.write(key, None, op_id)
+    }
+
+    pub fn execute_local_operation(
+        &mut self,
+        op: &Operation,
+        op_id: &OperationId,
+    ) -> Result<ReturnType, DatatypeError> {
+        match op.body {
+            OperationBody::MapPut(ref body) => {
+                let olds = body
+                    .entries
+                    .iter()
+                    .map(|(k, v)| self.put(k, v.clone(), op_id))
+                    .collect();
+                Ok(ReturnType::Map(olds))
+            }
+            OperationBody::MapRemove(ref body) => {
+                Ok(ReturnType::Map(vec![self.remove(&body.key, op_id)]))
+            }
+            #[allow(unreachable_patterns)]
+            _ => Err(err!(
+                DatatypeError::FailedToExecuteOperation,
+                format!("{} cannot be executed on a map", op.body)
+            )),
+        }
+    }
+
+    pub fn execute_remote_operation(
+        &mut self,
+        op: &Operation,
+        op_id: &OperationId,
+    ) -> Result<ReturnType, DatatypeError> {
+        self.execute_local_operation(op, op_id)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut writer = BytesWriter::new();
+        writer.write_u32(self.entries.len() as u32);
+        for (key, entry) in self.entries.iter() {
+            writer.write_str(key);
+            entry.op_id.write_to(&mut writer);
+            writer.write_bool(entry.value.is_some());
+            if let Some(value) = &entry.value {
+                value.write_to(&mut writer);
+            }
+        }
+        writer.into_bytes()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DatatypeError> {
+        let mut reader = BytesReader::new(bytes);
+        let mut map = Self::default();
+        for _ in 0..reader.read_u32()? {
+            let key = reader.read_string()?;
+            let op_id = OperationId::read_from(&mut reader)?;
+            let value = if reader.read_bool()? {
+                map.size += 1;
+                Some(Primitive::read_from(&mut reader)?)
+            } else {
+                None
+            };
+            map.entries.insert(key, MapEntry { value, op_id });
+        }
+        if !reader.is_empty() {
+            return Err(DatatypeError::FailedToDeserialize(
+                "trailing bytes in map crdt".to_owned(),
+            ));
+        }
+        Ok(map)
+    }
+}
+
+#[cfg(test)]
+mod tests_map_crdt {
+    use crate::{
+        datatypes::crdts::map_crdt::MapCrdt,
+        types::{operation_id::OperationId, primitive::Primitive, uid::Cuid},
+    };
+
+    fn new_op_ids() -> (OperationId, OperationId) {
+        let cuid1 = Cuid::try_from("0000000000000001").unwrap();
+        let cuid2 = Cuid::try_from("0000000000000002").unwrap();
+        let mut op_id1 = OperationId::new_with_cuid(&cuid1);
+        let mut op_id2 = OperationId::new_with_cuid(&cuid2);
+        op_id1.next_lamport();
+        op_id2.next_lamport();
+        (op_id1, op_id2)
+    }
+
+    #[test]
+    fn can_put_get_and_remove() {
+        let (mut op_id, _) = new_op_ids();
+        let mut map = MapCrdt::default();
+        assert_eq!(map.put("a", 1.into(), &op_id), None);
+        op_id.next_lamport();
+        assert_eq!(map.put("b", 2.into(), &op_id), None);
+        op_id.next_lamport();
+        assert_eq!(map.put("a", "one".into(), &op_id), Some(1.into()));
+        assert_eq!(map.get("a"), Some(&Primitive::from("one")));
+        assert_eq!(map.len(), 2);
+
+        op_id.next_lamport();
+        assert_eq!(map.remove("a", &op_id), Some("one".into()));
+        assert_eq!(map.remove("x", &op_id), None);
+        assert_eq!(map.get("a"), None);
+        assert_eq!(map.len(), 1);
+        assert_eq!(map.keys().collect::<Vec<_>>(), vec!["b"]);
+    }
+
+    #[test]
+    fn can_converge_concurrent_puts_and_removes() {
+        let (mut op_id1, mut op_id2) = new_op_ids();
+
+        // concurrent puts to the same key are ordered by cuid
+        let mut map1 = MapCrdt::default();
+        map1.put("k", "a".into(), &op_id1);
+        map1.put("k", "b".into(), &op_id2);
+        let mut map2 = MapCrdt::default();
+        map2.put("k", "b".into(), &op_id2);
+        map2.put("k", "a".into(), &op_id1);
+        assert_eq!(map1.get("k"), Some(&Primitive::from("b")));
+        assert_eq!(map1.get("k"), map2.get("k"));
+
+        // a remove wins over an older put arriving later, thanks to the tombstone
+        op_id1.next_lamport();
+        op_id1.next_lamport();
+        map1.remove("k", &op_id1);
+        op_id2.next_lamport();
+        map1.put("k", "c".into(), &op_id2);
+        map2.put("k", "c".into(), &op_id2);
+        map2.remove("k", &op_id1);
+        assert_eq!(map1.get("k"), None);
+        assert_eq!(map2.get("k"), None);
+        assert_eq!((map1.len(), map2.len()), (0, 0));
+
+        // a newer put revives the removed key
+        op_id2.next_lamport();
+        op_id2.next_lamport();
+        map1.put("k", "d".into(), &op_id2);
+        assert_eq!(map1.get("k"), Some(&Primitive::from("d")));
+        assert_eq!(map1.len(), 1);
+    }
+
+    #[test]
+    fn can_serialize_and_deserialize_map_crdt() {
+        let (mut op_id, _) = new_op_ids();
+        let mut map = MapCrdt::default();
+        map.put("a", 1.into(), &op_id);
+        op_id.next_lamport();
+        map.put("b", vec![2u8].into(), &op_id);
+        op_id.next_lamport();
+        map.remove("a", &op_id);
+
+        let bytes = map.to_bytes();
+        let deserialized = MapCrdt::from_bytes(&bytes).unwrap();
+        assert_eq!(deserialized.len(), 1);
+        assert_eq!(
+            deserialized.iter().collect::<Vec<_>>(),
+            map.iter().collect::<Vec<_>>()
+        );
+        // the tombstone is kept
+        assert_eq!(deserialized.entries["a"].op_id, op_id);
+        assert!(MapCrdt::from_bytes(&bytes[1..]).is_err());
+    }
+}
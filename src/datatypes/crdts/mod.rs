@@ -2,27 +2,68 @@
 use crate::operations::body::OperationBody;
 use crate::{
     DataType, DatatypeError,
-    datatypes::{common::ReturnType, crdts::counter_crdt::CounterCrdt},
+    datatypes::{
+        common::ReturnType,
+        crdts::{
+            counter_crdt::CounterCrdt, document_crdt::DocumentCrdt, list_crdt::ListCrdt,
+            map_crdt::MapCrdt, set_crdt::SetCrdt, text_crdt::TextCrdt, variable_crdt::VariableCrdt,
+        },
+    },
     errors::err,
     operations::Operation,
+    types::operation_id::OperationId,
 };
 
 pub mod counter_crdt;
+pub mod document_crdt;
+pub mod list_crdt;
+pub mod map_crdt;
+pub mod set_crdt;
+pub mod text_crdt;
+pub mod variable_crdt;
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Crdt {
     Counter(CounterCrdt),
+    Variable(VariableCrdt),
+    List(ListCrdt),
+    Map(MapCrdt),
+    Document(DocumentCrdt),
+    Text(TextCrdt),
+    Set(SetCrdt),
 }
 
 impl Crdt {
     pub fn new(r#type: DataType) -> Self {
         match r#type {
             DataType::Counter => Crdt::Counter(CounterCrdt::default()),
-            _ => unreachable!("invalid type"),
+            DataType::Variable => Crdt::Variable(VariableCrdt::default()),
+            DataType::List => Crdt::List(ListCrdt::default()),
+            DataType::Map => Crdt::Map(MapCrdt::default()),
+            DataType::Document => Crdt::Document(DocumentCrdt::default()),
+            DataType::Text => Crdt::Text(TextCrdt::default()),
+            DataType::Set => Crdt::Set(SetCrdt::default()),
         }
     }
 
-    pub fn execute_local_operation(&mut self, op: &Operation) -> Result<ReturnType, DatatypeError> {
+    pub fn get_type(&self) -> DataType {
+        match self {
+            Crdt::Counter(_) => DataType::Counter,
+            Crdt::Variable(_) => DataType::Variable,
+            Crdt::List(_) => DataType::List,
+            Crdt::Map(_) => DataType::Map,
+            Crdt::Document(_) => DataType::Document,
+            Crdt::Text(_) => DataType::Text,
+            Crdt::Set(_) => DataType::Set,
+        }
+    }
+
+    pub fn execute_local_operation(
+        &mut self,
+        op: &mut Operation,
+        op_id: &OperationId,
+    ) -> Result<ReturnType, DatatypeError> {
         #[cfg(test)]
         {
             if let OperationBody::Delay4Test(body) = &op.body {
@@ -34,41 +75,98 @@ impl Crdt {
         }
         match self {
             Crdt::Counter(c) => c.execute_local_operation(op),
+            Crdt::Variable(v) => v.execute_local_operation(op, op_id),
+            Crdt::List(l) => l.execute_local_operation(op, op_id),
+            Crdt::Map(m) => m.execute_local_operation(op, op_id),
+            Crdt::Document(d) => d.execute_local_operation(op, op_id),
+            Crdt::Text(t) => t.execute_local_operation(op, op_id),
+            Crdt::Set(s) => s.execute_local_operation(op, op_id),
+        }
+    }
+
+    pub fn execute_remote_operation(
+        &mut self,
+        op: &Operation,
+        op_id: &OperationId,
+    ) -> Result<ReturnType, DatatypeError> {
+        #[cfg(test)]
+        {
+            if let OperationBody::Delay4Test(_) = &op.body {
+                return Ok(ReturnType::None);
+            }
+        }
+        match self {
+            Crdt::Counter(c) => c.execute_remote_operation(op),
+            Crdt::Variable(v) => v.execute_remote_operation(op, op_id),
+            Crdt::List(l) => l.execute_remote_operation(op, op_id),
+            Crdt::Map(m) => m.execute_remote_operation(op, op_id),
+            Crdt::Document(d) => d.execute_remote_operation(op, op_id),
+            Crdt::Text(t) => t.execute_remote_operation(op, op_id),
+            Crdt::Set(s) => s.execute_remote_operation(op, op_id),
         }
     }
 
     pub fn serialize(&self) -> Box<[u8]> {
         match self {
             Self::Counter(c) => Box::new(c.to_bytes()),
+            Self::Variable(v) => v.to_bytes().into_boxed_slice(),
+            Self::List(l) => l.to_bytes().into_boxed_slice(),
+            Self::Map(m) => m.to_bytes().into_boxed_slice(),
+            Self::Document(d) => d.to_bytes().into_boxed_slice(),
+            Self::Text(t) => t.to_bytes().into_boxed_slice(),
+            Self::Set(s) => s.to_bytes().into_boxed_slice(),
         }
     }
 
-    pub fn deserialize(&mut self, serialized: &[u8]) {
-        match self {
-            Self::Counter(c) => {
-                if serialized.len() != 8 {
+    /// Replaces this CRDT with the one deserialized from `serialized`, which is left
+    /// unchanged if `serialized` is malformed.
+    pub fn deserialize(&mut self, serialized: &[u8]) -> Result<(), DatatypeError> {
+        let malformed = |name: &str, e: DatatypeError| {
+            err!(
+                DatatypeError::FailedToDeserialize,
+                format!("{name} crdt: {e}")
+            )
+        };
+        *self = match self {
+            Self::Counter(_) => {
+                let bytes: &[u8; 8] = serialized.try_into().map_err(|_| {
                     err!(
                         DatatypeError::FailedToDeserialize,
-                        format!(
-                            "counter crdt: {serialized:?}, and will recover to counter value 0"
-                        )
-                    );
-                    *c = CounterCrdt::default();
-                    return;
-                }
-                let mut array = [0u8; 8];
-                array.copy_from_slice(serialized);
-                *c = CounterCrdt::from_bytes(&array)
+                        format!("counter crdt: {} bytes instead of 8", serialized.len())
+                    )
+                })?;
+                Self::Counter(CounterCrdt::from_bytes(bytes))
             }
-        }
+            Self::Variable(_) => Self::Variable(
+                VariableCrdt::from_bytes(serialized).map_err(|e| malformed("variable", e))?,
+            ),
+            Self::List(_) => {
+                Self::List(ListCrdt::from_bytes(serialized).map_err(|e| malformed("list", e))?)
+            }
+            Self::Map(_) => {
+                Self::Map(MapCrdt::from_bytes(serialized).map_err(|e| malformed("map", e))?)
+            }
+            Self::Document(_) => Self::Document(
+                DocumentCrdt::from_bytes(serialized).map_err(|e| malformed("document", e))?,
+            ),
+            Self::Text(_) => {
+                Self::Text(TextCrdt::from_bytes(serialized).map_err(|e| malformed("text", e))?)
+            }
+            Self::Set(_) => {
+                Self::Set(SetCrdt::from_bytes(serialized).map_err(|e| malformed("set", e))?)
+            }
+        };
+        Ok(())
     }
 }
 
 #[cfg(test)]
 mod tests_crdts {
     use crate::{
-        DataType,
+        DataType, DatatypeError, Primitive,
         datatypes::crdts::{Crdt, counter_crdt::CounterCrdt},
+        operations::Operation,
+        types::{operation_id::OperationId, uid::Cuid},
     };
 
     #[test]
@@ -79,13 +177,98 @@ mod tests_crdts {
 
         let mut crdt2 = Crdt::new(DataType::Counter);
         let serialized = crdt1.serialize();
-        crdt2.deserialize(&serialized);
+        crdt2.deserialize(&serialized).unwrap();
+
+        let Crdt::Counter(c) = &crdt2 else {
+            unreachable!()
+        };
+        assert_eq!(c.value(), 100);
+        assert_eq!(
+            crdt2.deserialize("{}".as_bytes()).unwrap_err(),
+            DatatypeError::FailedToDeserialize("".into())
+        );
 
-        let Crdt::Counter(c) = &crdt2;
+        let Crdt::Counter(c) = &crdt2 else {
+            unreachable!()
+        };
         assert_eq!(c.value(), 100);
-        crdt2.deserialize("{}".as_bytes());
+    }
+
+    #[test]
+    fn can_serialize_and_deserialize_variable() {
+        let mut op_id = OperationId::new_with_cuid(&Cuid::new());
+        op_id.next_lamport();
+        let mut crdt1 = Crdt::new(DataType::Variable);
+        crdt1
+            .execute_local_operation(&mut Operation::new_variable_set("yam".into()), &op_id)
+            .unwrap();
+
+        let mut crdt2 = Crdt::new(DataType::Variable);
+        crdt2.deserialize(&crdt1.serialize()).unwrap();
+        let Crdt::Variable(v) = &crdt2 else {
+            unreachable!()
+        };
+        assert_eq!(v.value(), &Primitive::from("yam"));
+
+        assert!(crdt2.deserialize("{}".as_bytes()).is_err());
+        let Crdt::Variable(v) = &crdt2 else {
+            unreachable!()
+        };
+        assert_eq!(v.value(), &Primitive::from("yam"));
+    }
+
+    #[test]
+    fn can_serialize_and_deserialize_list() {
+        let mut op_id = OperationId::new_with_cuid(&Cuid::new());
+        op_id.next_lamport();
+        let mut crdt1 = Crdt::new(DataType::List);
+        crdt1
+            .execute_local_operation(
+                &mut Operation::new_list_insert(0, vec![1.into(), 2.into()]),
+                &op_id,
+            )
+            .unwrap();
+
+        let mut crdt2 = Crdt::new(DataType::List);
+        crdt2.deserialize(&crdt1.serialize()).unwrap();
+        let Crdt::List(l) = &crdt2 else {
+            unreachable!()
+        };
+        assert_eq!(l.len(), 2);
+
+        assert!(crdt2.deserialize("{}".as_bytes()).is_err());
+        let Crdt::List(l) = &crdt2 else {
+            unreachable!()
+        };
+        assert_eq!(l.len(), 2);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn can_serialize_snapshots_as_json() {
+        let mut op_id = OperationId::new_with_cuid(&Cuid::new());
+        op_id.next_lamport();
+        let mut crdt1 = Crdt::new(DataType::List);
+        crdt1
+            .execute_local_operation(
+                &mut Operation::new_list_insert(0, vec![1.into(), "a".into()]),
+                &op_id,
+            )
+            .unwrap();
+        op_id.next_lamport();
+        crdt1
+            .execute_local_operation(&mut Operation::new_list_delete(0, 1), &op_id)
+            .unwrap();
+
+        let json = serde_json::to_string(&crdt1).unwrap();
+        let crdt2 = serde_json::from_str::<Crdt>(&json).unwrap();
+        let Crdt::List(l) = &crdt2 else {
+            unreachable!()
+        };
+        assert_eq!(l.iter().cloned().collect::<Vec<_>>(), vec!["a".into()]);
+        assert_eq!(crdt1.serialize(), crdt2.serialize());
 
-        let Crdt::Counter(c) = &crdt2;
-        assert_eq!(c.value(), 0);
+        let json = serde_json::to_string(&Crdt::new(DataType::Variable)).unwrap();
+        assert!(json.starts_with("{\"Variable\":"));
     }
 }
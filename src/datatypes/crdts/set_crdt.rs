@@ -0,0 +1,237 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::{
+    DatatypeError,
+    datatypes::common::ReturnType,
+    errors::err,
+    operations::{Operation, body::OperationBody},
+    types::{element_id::ElementId, operation_id::OperationId, set_element::SetElement},
+    utils::bytes::{BytesReader, BytesWriter},
+};
+
+/// An observed-remove set (OR-Set) with add-wins semantics.
+///
+/// Every add tags the element with an [`ElementId`] derived from the [`OperationId`] of
+/// the add, and a remove deletes only the tags it has observed. Therefore, an add
+/// concurrent with a remove survives it, and every client converges to the same set.
+#[derive(Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SetCrdt {
+    /// The alive tags of each element; an element without tags is not kept.
+    elements: BTreeMap<SetElement, BTreeSet<ElementId>>,
+}
+
+impl SetCrdt {
+    pub fn contains(&self, element: &SetElement) -> bool {
+        self.elements.contains_key(element)
+    }
+
+    pub fn len(&self) -> usize {
+        self.elements.len()
+    }
+
+    /// Returns the elements in ascending order.
+    pub fn iter(&self) -> impl Iterator<Item = &SetElement> {
+        self.elements.keys()
+    }
+
+    /// Adds `element` tagged by `op_id`, and returns true if it was not contained.
+    pub fn add(&mut self, element: &SetElement, op_id: &OperationId) -> bool {
+        let tag = ElementId::new(op_id, 0);
+        match self.elements.get_mut(element) {
+            Some(tags) => {
+                tags.insert(tag);
+                false
+            }
+            None => {
+                self.elements.insert(element.clone(), BTreeSet::from([tag]));
+                true
+            }
+        }
+    }
+
+    /// Removes all the observed tags of `element`, and returns them.
+    pub fn remove(&mut self, element: &SetElement) -> Vec<ElementId> {
+        self.elements
+            .remove(element)
+            .map(|tags| tags.into_iter().collect())
+            .unwrap_or_default()
+    }
+
+    /// Removes `tags` from `element`, and returns true if it is no longer contained.
+    pub fn remove_tags(&mut self, element: &SetElement, tags: &[ElementId]) -> bool {
+        let Some(alive) = self.elements.get_mut(element) else {
+            return false;
+        };
+        tags.iter().for_each(|t| {
+            alive.remove(t);
+        });
+        if alive.is_empty() {
+            self.elements.remove(element);
+            return true;
+        }
+        false
+    }
+
+    pub fn execute_local_operation(
+        &mut self,
+        op: &mut Operation,
+        op_id: &OperationId,
+    ) -> Result<ReturnType, DatatypeError> {
+        match op.body {
+            OperationBody::SetAdd(ref body) => Ok(ReturnType::Set(self.add(&body.element, op_id))),
+            OperationBody::SetRemove(ref mut body) => {
+                body.tags = self.remove(&body.element);
+                Ok(ReturnType::Set(!body.tags.is_empty()))
+            }
+            #[allow(unreachable_patterns)]
+            _ => Err(err!(
+                DatatypeError::FailedToExecuteOperation,
+                format!("{} cannot be executed on a set", op.body)
+            )),
+        }
+    }
+
+    pub fn execute_remote_operation(
+        &mut self,
+        op: &Operation,
+        op_id: &OperationId,
+    ) -> Result<ReturnType, DatatypeError> {
+        match op.body {
+            OperationBody::SetAdd(ref body) => Ok(ReturnType::Set(self.add(&body.element, op_id))),
+            OperationBody::SetRemove(ref body) => {
+                Ok(ReturnType::Set(self.remove_tags(&body.element, &body.tags)))
+            }
+            #[allow(unreachable_patterns)]
+            _ => Err(err!(
+                DatatypeError::FailedToExecuteOperation,
+                format!("{} cannot be executed on a set", op.body)
+            )),
+        }
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut writer = BytesWriter::new();
+        writer.write_u32(self.elements.len() as u32);
+        for (element, tags) in self.elements.iter() {
+            element.write_to(&mut writer);
+            writer.write_u32(tags.len() as u32);
+            tags.iter().for_each(|t| t.write_to(&mut writer));
+        }
+        writer.into_bytes()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DatatypeError> {
+        let mut reader = BytesReader::new(bytes);
+        let mut set = Self::default();
+        for _ in 0..reader.read_u32()? {
+            let element = SetElement::read_from(&mut reader)?;
+            let tags = (0..reader.read_u32()?)
+                .map(|_| ElementId::read_from(&mut reader))
+                .collect::<Result<BTreeSet<_>, _>>()?;
+            if tags.is_empty() {
+                return Err(DatatypeError::FailedToDeserialize(format!(
+                    "no tags of set element {element}"
+                )));
+            }
+            set.elements.insert(element, tags);
+        }
+        if !reader.is_empty() {
+            return Err(DatatypeError::FailedToDeserialize(
+                "trailing bytes in set crdt".to_owned(),
+            ));
+        }
+        Ok(set)
+    }
+}
+
+#[cfg(test)]
+mod tests_set_crdt {
+    use crate::{
+        datatypes::crdts::set_crdt::SetCrdt,
+        operations::Operation,
+        types::{operation_id::OperationId, set_element::SetElement, uid::Cuid},
+    };
+
+    fn execute(set: &mut SetCrdt, op_id: &mut OperationId, mut op: Operation) -> Operation {
+        op_id.next_lamport();
+        set.execute_local_operation(&mut op, op_id).unwrap();
+        op
+    }
+
+    #[test]
+    fn can_add_and_remove() {
+        let mut op_id = OperationId::new_with_cuid(&Cuid::new());
+        let mut set = SetCrdt::default();
+        op_id.next_lamport();
+        assert!(set.add(&"a".into(), &op_id));
+        op_id.next_lamport();
+        assert!(!set.add(&"a".into(), &op_id));
+        assert!(set.add(&vec![1u8].into(), &op_id));
+        assert_eq!(set.len(), 2);
+        assert_eq!(set.remove(&"a".into()).len(), 2);
+        assert!(set.remove(&"a".into()).is_empty());
+        assert!(!set.contains(&"a".into()));
+        assert_eq!(
+            set.iter().cloned().collect::<Vec<_>>(),
+            vec![SetElement::from(vec![1u8])]
+        );
+    }
+
+    #[test]
+    fn can_converge_concurrent_adds_and_removes() {
+        let mut op_id1 = OperationId::new_with_cuid(&Cuid::try_from("0000000000000001").unwrap());
+        let mut op_id2 = OperationId::new_with_cuid(&Cuid::try_from("0000000000000002").unwrap());
+        let mut set1 = SetCrdt::default();
+        let mut set2 = SetCrdt::default();
+        let op = execute(&mut set1, &mut op_id1, Operation::new_set_add("a".into()));
+        set2.execute_remote_operation(&op, &op_id1).unwrap();
+        op_id2.lamport = op_id1.lamport;
+
+        // the concurrent add wins over the remove
+        let add1 = execute(&mut set1, &mut op_id1, Operation::new_set_add("a".into()));
+        let remove2 = execute(
+            &mut set2,
+            &mut op_id2,
+            Operation::new_set_remove("a".into()),
+        );
+        assert!(!set2.contains(&"a".into()));
+        set1.execute_remote_operation(&remove2, &op_id2).unwrap();
+        set2.execute_remote_operation(&add1, &op_id1).unwrap();
+        assert!(set1.contains(&"a".into()));
+        assert!(set2.contains(&"a".into()));
+
+        // the removes of the same element by both remove it
+        let remove1 = execute(
+            &mut set1,
+            &mut op_id1,
+            Operation::new_set_remove("a".into()),
+        );
+        let remove2 = execute(
+            &mut set2,
+            &mut op_id2,
+            Operation::new_set_remove("a".into()),
+        );
+        set1.execute_remote_operation(&remove2, &op_id2).unwrap();
+        set2.execute_remote_operation(&remove1, &op_id1).unwrap();
+        assert_eq!((set1.len(), set2.len()), (0, 0));
+    }
+
+    #[test]
+    fn can_serialize_and_deserialize_set_crdt() {
+        let mut op_id = OperationId::new_with_cuid(&Cuid::new());
+        let mut set = SetCrdt::default();
+        execute(&mut set, &mut op_id, Operation::new_set_add("a".into()));
+        execute(&mut set, &mut op_id, Operation::new_set_add("a".into()));
+        execute(
+            &mut set,
+            &mut op_id,
+            Operation::new_set_add(vec![0u8].into()),
+        );
+
+        let bytes = set.to_bytes();
+        let deserialized = SetCrdt::from_bytes(&bytes).unwrap();
+        assert_eq!(deserialized.elements, set.elements);
+        assert!(SetCrdt::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+    }
+}
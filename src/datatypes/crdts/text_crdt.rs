@@ -0,0 +1,493 @@
+use std::fmt::{Display, Formatter};
+
+use crate::{
+    DatatypeError,
+    datatypes::common::ReturnType,
+    errors::err,
+    operations::{Operation, body::OperationBody},
+    types::{element_id::ElementId, operation_id::OperationId},
+    utils::bytes::{BytesReader, BytesWriter},
+};
+
+/// A run of characters inserted together by an operation, where the `i`-th character is
+/// identified by `id` with `offset + i`.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct TextRun {
+    id: ElementId,
+    /// The number of characters.
+    len: u32,
+    /// `None` is a tombstone of deleted characters.
+    text: Option<String>,
+}
+
+impl TextRun {
+    fn id_at(&self, k: u32) -> ElementId {
+        ElementId {
+            lamport: self.id.lamport,
+            cuid: self.id.cuid.clone(),
+            offset: self.id.offset + k,
+        }
+    }
+
+    /// Returns the index of `id` in this run, if it contains `id`.
+    fn index_of(&self, id: &ElementId) -> Option<u32> {
+        (self.id.lamport == id.lamport
+            && self.id.cuid == id.cuid
+            && self.id.offset <= id.offset
+            && id.offset < self.id.offset + self.len)
+            .then(|| id.offset - self.id.offset)
+    }
+
+    /// Returns true if `next` continues this run in the same state.
+    fn is_continued_by(&self, next: &TextRun) -> bool {
+        self.id.lamport == next.id.lamport
+            && self.id.cuid == next.id.cuid
+            && self.id.offset + self.len == next.id.offset
+            && self.text.is_some() == next.text.is_some()
+    }
+
+    /// Splits off the characters from `k`, which must be in `1..len`.
+    fn split_off(&mut self, k: u32) -> TextRun {
+        let text = self.text.as_mut().map(|t| {
+            let at = t.char_indices().nth(k as usize).map_or(t.len(), |(i, _)| i);
+            t.split_off(at)
+        });
+        let right = TextRun {
+            id: self.id_at(k),
+            len: self.len - k,
+            text,
+        };
+        self.len = k;
+        right
+    }
+}
+
+/// A replicated growable array (RGA) of characters.
+///
+/// It works like [`super::list_crdt::ListCrdt`], but the characters inserted by an
+/// operation are kept together in a run, which is split only when another operation
+/// inserts into or deletes a part of it. Deleted runs keep only their ids and lengths,
+/// so that a large text costs memory in proportion to its number of edits rather than
+/// its number of characters.
+///
+/// Positions and lengths count Unicode scalar values, i.e., `char`s.
+#[derive(Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TextCrdt {
+    runs: Vec<TextRun>,
+    size: usize,
+}
+
+impl TextCrdt {
+    pub fn len(&self) -> usize {
+        self.size
+    }
+
+    fn check_bound(&self, pos: usize, len: usize) -> Result<(), DatatypeError> {
+        if pos + len > self.size {
+            return Err(err!(
+                DatatypeError::FailedToExecuteOperation,
+                format!("out of bound: pos={pos}, len={len}, size={}", self.size)
+            ));
+        }
+        Ok(())
+    }
+
+    /// Returns the index of the run and the index in the run of the character at `pos`.
+    fn locate_pos(&self, mut pos: usize) -> Option<(usize, u32)> {
+        for (idx, run) in self.runs.iter().enumerate() {
+            if run.text.is_none() {
+                continue;
+            }
+            if pos < run.len as usize {
+                return Some((idx, pos as u32));
+            }
+            pos -= run.len as usize;
+        }
+        None
+    }
+
+    /// Returns the index of the run and the index in the run of the character `id`.
+    fn locate_id(&self, id: &ElementId) -> Option<(usize, u32)> {
+        self.runs
+            .iter()
+            .enumerate()
+            .find_map(|(idx, run)| run.index_of(id).map(|k| (idx, k)))
+    }
+
+    /// Splits the run at `idx` so that a run starts from its `k`-th character,
+    /// and returns the index of that run.
+    fn split(&mut self, idx: usize, k: u32) -> usize {
+        if k == 0 {
+            return idx;
+        }
+        if k < self.runs[idx].len {
+            let right = self.runs[idx].split_off(k);
+            self.runs.insert(idx + 1, right);
+        }
+        idx + 1
+    }
+
+    /// Inserts `text` at `pos`, and returns the character after which it is inserted.
+    pub fn insert_by_pos(
+        &mut self,
+        pos: usize,
+        text: &str,
+        op_id: &OperationId,
+    ) -> Result<Option<ElementId>, DatatypeError> {
+        self.check_bound(pos, 0)?;
+        let target = match pos {
+            0 => None,
+            _ => self
+                .locate_pos(pos - 1)
+                .map(|(idx, k)| self.runs[idx].id_at(k)),
+        };
+        self.insert_by_target(target.as_ref(), text, op_id)?;
+        Ok(target)
+    }
+
+    pub fn insert_by_target(
+        &mut self,
+        target: Option<&ElementId>,
+        text: &str,
+        op_id: &OperationId,
+    ) -> Result<(), DatatypeError> {
+        if text.is_empty() {
+            return Ok(());
+        }
+        let id = ElementId::new(op_id, 0);
+        if self.locate_id(&id).is_some() {
+            // already inserted
+            return Ok(());
+        }
+        let mut idx = match target {
+            None => 0,
+            Some(t) => match self.locate_id(t) {
+                Some((idx, k)) => self.split(idx, k + 1),
+                None => {
+                    return Err(err!(
+                        DatatypeError::FailedToExecuteOperation,
+                        format!("no target character: {t}")
+                    ));
+                }
+            },
+        };
+        while idx < self.runs.len() && self.runs[idx].id > id {
+            idx += 1;
+        }
+        let len = text.chars().count();
+        self.runs.insert(
+            idx,
+            TextRun {
+                id,
+                len: len as u32,
+                text: Some(text.to_owned()),
+            },
+        );
+        self.size += len;
+        Ok(())
+    }
+
+    /// Deletes `len` characters from `pos`, and returns the deleted spans and text.
+    pub fn delete_by_pos(
+        &mut self,
+        pos: usize,
+        len: usize,
+    ) -> Result<(Vec<(ElementId, u32)>, String), DatatypeError> {
+        self.check_bound(pos, len)?;
+        let mut targets = vec![];
+        let mut deleted = String::new();
+        let Some((idx, k)) = self.locate_pos(pos).filter(|_| len > 0) else {
+            return Ok((targets, deleted));
+        };
+        let mut idx = self.split(idx, k);
+        let mut remaining = len as u32;
+        while remaining > 0 {
+            if self.runs[idx].text.is_some() {
+                self.split(idx, remaining);
+                let run = &mut self.runs[idx];
+                deleted.push_str(&run.text.take().unwrap_or_default());
+                targets.push((run.id.clone(), run.len));
+                remaining -= run.len;
+                self.size -= run.len as usize;
+            }
+            idx += 1;
+        }
+        Ok((targets, deleted))
+    }
+
+    /// Deletes the characters of `targets` that still exist, and returns their text.
+    pub fn delete_by_targets(&mut self, targets: &[(ElementId, u32)]) -> String {
+        let mut deleted = String::new();
+        for (start, len) in targets {
+            let mut id = start.clone();
+            let end = start.offset + len;
+            while id.offset < end {
+                let Some((idx, k)) = self.locate_id(&id) else {
+                    break;
+                };
+                let idx = self.split(idx, k);
+                self.split(idx, end - id.offset);
+                let run = &mut self.runs[idx];
+                if let Some(text) = run.text.take() {
+                    deleted.push_str(&text);
+                    self.size -= run.len as usize;
+                }
+                id.offset += run.len;
+            }
+        }
+        deleted
+    }
+
+    /// Returns the character before `pos`, or `None` for the head; `pos` is clamped to
+    /// the length.
+    pub fn anchor_of_pos(&self, pos: usize) -> Option<ElementId> {
+        match pos.min(self.size) {
+            0 => None,
+            pos => self
+                .locate_pos(pos - 1)
+                .map(|(idx, k)| self.runs[idx].id_at(k)),
+        }
+    }
+
+    /// Returns the position right after the character `anchor`, or after the characters
+    /// before it if it has been deleted.
+    pub fn pos_of_anchor(&self, anchor: Option<&ElementId>) -> Option<usize> {
+        let Some(anchor) = anchor else {
+            return Some(0);
+        };
+        let mut pos = 0;
+        for run in self.runs.iter() {
+            if let Some(k) = run.index_of(anchor) {
+                return Some(pos + run.text.as_ref().map_or(0, |_| k as usize + 1));
+            }
+            if run.text.is_some() {
+                pos += run.len as usize;
+            }
+        }
+        None
+    }
+
+    pub fn execute_local_operation(
+        &mut self,
+        op: &mut Operation,
+        op_id: &OperationId,
+    ) -> Result<ReturnType, DatatypeError> {
+        match op.body {
+            OperationBody::TextEdit(ref mut body) => {
+                let (targets, deleted) = self.delete_by_pos(body.pos, body.len)?;
+                body.target = self.insert_by_pos(body.pos, &body.text, op_id)?;
+                body.targets = targets;
+                Ok(ReturnType::Text(deleted))
+            }
+            #[allow(unreachable_patterns)]
+            _ => Err(err!(
+                DatatypeError::FailedToExecuteOperation,
+                format!("{} cannot be executed on a text", op.body)
+            )),
+        }
+    }
+
+    pub fn execute_remote_operation(
+        &mut self,
+        op: &Operation,
+        op_id: &OperationId,
+    ) -> Result<ReturnType, DatatypeError> {
+        match op.body {
+            OperationBody::TextEdit(ref body) => {
+                let deleted = self.delete_by_targets(&body.targets);
+                self.insert_by_target(body.target.as_ref(), &body.text, op_id)?;
+                Ok(ReturnType::Text(deleted))
+            }
+            #[allow(unreachable_patterns)]
+            _ => Err(err!(
+                DatatypeError::FailedToExecuteOperation,
+                format!("{} cannot be executed on a text", op.body)
+            )),
+        }
+    }
+
+    /// Serializes the runs, joining again the ones split but still in the same state.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut joined: Vec<TextRun> = Vec::with_capacity(self.runs.len());
+        for run in self.runs.iter() {
+            match joined.last_mut() {
+                Some(last) if last.is_continued_by(run) => {
+                    last.len += run.len;
+                    if let (Some(t), Some(r)) = (last.text.as_mut(), run.text.as_ref()) {
+                        t.push_str(r);
+                    }
+                }
+                _ => joined.push(run.clone()),
+            }
+        }
+        let mut writer = BytesWriter::new();
+        writer.write_u32(joined.len() as u32);
+        for run in joined.iter() {
+            run.id.write_to(&mut writer);
+            writer.write_u32(run.len);
+            writer.write_bool(run.text.is_some());
+            if let Some(text) = &run.text {
+                writer.write_str(text);
+            }
+        }
+        writer.into_bytes()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DatatypeError> {
+        let mut reader = BytesReader::new(bytes);
+        let mut text = Self::default();
+        for _ in 0..reader.read_u32()? {
+            let id = ElementId::read_from(&mut reader)?;
+            let len = reader.read_u32()?;
+            let run_text = match reader.read_bool()? {
+                true => Some(reader.read_string()?),
+                false => None,
+            };
+            if len == 0
+                || run_text
+                    .as_ref()
+                    .is_some_and(|t| t.chars().count() != len as usize)
+            {
+                return Err(DatatypeError::FailedToDeserialize(format!(
+                    "invalid length of text run {id}: {len}"
+                )));
+            }
+            if run_text.is_some() {
+                text.size += len as usize;
+            }
+            text.runs.push(TextRun {
+                id,
+                len,
+                text: run_text,
+            });
+        }
+        if !reader.is_empty() {
+            return Err(DatatypeError::FailedToDeserialize(
+                "trailing bytes in text crdt".to_owned(),
+            ));
+        }
+        Ok(text)
+    }
+}
+
+impl Display for TextCrdt {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.runs
+            .iter()
+            .filter_map(|r| r.text.as_deref())
+            .try_for_each(|t| f.write_str(t))
+    }
+}
+
+#[cfg(test)]
+mod tests_text_crdt {
+    use crate::{
+        datatypes::{common::ReturnType, crdts::text_crdt::TextCrdt},
+        operations::Operation,
+        types::{operation_id::OperationId, uid::Cuid},
+    };
+
+    fn edit(
+        text: &mut TextCrdt,
+        op_id: &mut OperationId,
+        pos: usize,
+        len: usize,
+        s: &str,
+    ) -> Operation {
+        op_id.next_lamport();
+        let mut op = Operation::new_text_edit(pos, len, s.to_owned());
+        text.execute_local_operation(&mut op, op_id).unwrap();
+        op
+    }
+
+    #[test]
+    fn can_edit_text_in_runs() {
+        let mut op_id = OperationId::new_with_cuid(&Cuid::new());
+        let mut text = TextCrdt::default();
+        edit(&mut text, &mut op_id, 0, 0, "hello world");
+        assert_eq!(text.runs.len(), 1);
+        edit(&mut text, &mut op_id, 5, 0, ",");
+        assert_eq!(text.to_string(), "hello, world");
+        assert_eq!(text.runs.len(), 3);
+
+        op_id.next_lamport();
+        let (targets, deleted) = text.delete_by_pos(4, 3).unwrap();
+        assert_eq!(deleted, "o, ");
+        assert_eq!(targets.len(), 3);
+        assert_eq!(text.to_string(), "hellworld");
+        assert!(text.delete_by_pos(5, 5).is_err());
+
+        edit(&mut text, &mut op_id, 0, 4, "안녕 🍠");
+        assert_eq!(text.to_string(), "안녕 🍠world");
+        assert_eq!(text.len(), 9);
+        edit(&mut text, &mut op_id, 3, 1, "🙂");
+        assert_eq!(text.to_string(), "안녕 🙂world");
+        assert_eq!(text.len(), 9);
+
+        // the characters of the same run are serialized together
+        let deserialized = TextCrdt::from_bytes(&text.to_bytes()).unwrap();
+        assert_eq!(deserialized.to_string(), text.to_string());
+        assert_eq!(deserialized.len(), text.len());
+        assert!(deserialized.runs.len() < text.runs.len());
+        assert!(TextCrdt::from_bytes(&text.to_bytes()[1..]).is_err());
+    }
+
+    #[test]
+    fn can_converge_concurrent_edits() {
+        let mut op_id1 = OperationId::new_with_cuid(&Cuid::try_from("0000000000000001").unwrap());
+        let mut op_id2 = OperationId::new_with_cuid(&Cuid::try_from("0000000000000002").unwrap());
+        let mut text1 = TextCrdt::default();
+        let mut text2 = TextCrdt::default();
+
+        let op = edit(&mut text1, &mut op_id1, 0, 0, "abcdef");
+        text2.execute_remote_operation(&op, &op_id1).unwrap();
+        op_id2.lamport = op_id1.lamport;
+
+        let op1 = edit(&mut text1, &mut op_id1, 1, 3, "X");
+        let op2 = edit(&mut text2, &mut op_id2, 2, 3, "YZ");
+        let ReturnType::Text(deleted) = text1.execute_remote_operation(&op2, &op_id2).unwrap()
+        else {
+            unreachable!()
+        };
+        assert_eq!(deleted, "e");
+        text2.execute_remote_operation(&op1, &op_id1).unwrap();
+        assert_eq!(text1.to_string(), "aXYZf");
+        assert_eq!(text1.to_string(), text2.to_string());
+        assert_eq!(text1.len(), text2.len());
+
+        // applying the same operation again changes nothing
+        text2.execute_remote_operation(&op1, &op_id1).unwrap();
+        assert_eq!(text2.to_string(), "aXYZf");
+    }
+
+    #[test]
+    fn can_translate_positions_of_anchors() {
+        let mut op_id1 = OperationId::new_with_cuid(&Cuid::try_from("0000000000000001").unwrap());
+        let mut op_id2 = OperationId::new_with_cuid(&Cuid::try_from("0000000000000002").unwrap());
+        let mut text1 = TextCrdt::default();
+        let mut text2 = TextCrdt::default();
+        let op = edit(&mut text1, &mut op_id1, 0, 0, "hello world");
+        text2.execute_remote_operation(&op, &op_id1).unwrap();
+        op_id2.lamport = op_id1.lamport;
+
+        let anchor = text2.anchor_of_pos(6);
+        let head = text2.anchor_of_pos(0);
+        assert_eq!(head, None);
+        let op = edit(&mut text1, &mut op_id1, 0, 0, ">> ");
+        text2.execute_remote_operation(&op, &op_id1).unwrap();
+        assert_eq!(text2.pos_of_anchor(anchor.as_ref()), Some(9));
+        assert_eq!(text2.pos_of_anchor(head.as_ref()), Some(0));
+
+        // the anchor is deleted
+        let op = edit(&mut text1, &mut op_id1, 6, 4, "");
+        text2.execute_remote_operation(&op, &op_id1).unwrap();
+        assert_eq!(text2.to_string(), ">> helorld");
+        assert_eq!(text2.pos_of_anchor(anchor.as_ref()), Some(6));
+
+        assert_eq!(text2.anchor_of_pos(100), text2.anchor_of_pos(10));
+        let unknown = TextCrdt::default().anchor_of_pos(0);
+        assert_eq!(text2.pos_of_anchor(unknown.as_ref()), Some(0));
+    }
+}
@@ -0,0 +1,135 @@
+use crate::{
+    DatatypeError,
+    datatypes::common::ReturnType,
+    errors::err,
+    operations::{Operation, body::OperationBody},
+    types::{operation_id::OperationId, primitive::Primitive},
+    utils::bytes::{BytesReader, BytesWriter},
+};
+
+/// A last-writer-wins register.
+///
+/// Concurrent sets are resolved by comparing the [`OperationId`]s of the writers,
+/// that is, the lamport clock first and then the cuid.
+#[derive(Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VariableCrdt {
+    value: Primitive,
+    op_id: OperationId,
+}
+
+impl VariableCrdt {
+    /// Sets the value if `op_id` is newer than that of the last writer, and returns the
+    /// previous value, or `None` if it is not set since `op_id` is stale.
+    pub fn set(&mut self, value: Primitive, op_id: &OperationId) -> Option<Primitive> {
+        if op_id > &self.op_id {
+            self.op_id = op_id.clone();
+            return Some(std::mem::replace(&mut self.value, value));
+        }
+        None
+    }
+
+    pub fn value(&self) -> &Primitive {
+        &self.value
+    }
+
+    pub fn execute_local_operation(
+        &mut self,
+        op: &Operation,
+        op_id: &OperationId,
+    ) -> Result<ReturnType, DatatypeError> {
+        match op.body {
+            OperationBody::VariableSet(ref body) => {
+                // a stale set changes nothing, and thus has nothing to undo.
+                Ok(self
+                    .set(body.value.clone(), op_id)
+                    .map_or(ReturnType::None, ReturnType::Variable))
+            }
+            #[allow(unreachable_patterns)]
+            _ => Err(err!(
+                DatatypeError::FailedToExecuteOperation,
+                format!("{} cannot be executed on a variable", op.body)
+            )),
+        }
+    }
+
+    pub fn execute_remote_operation(
+        &mut self,
+        op: &Operation,
+        op_id: &OperationId,
+    ) -> Result<ReturnType, DatatypeError> {
+        self.execute_local_operation(op, op_id)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut writer = BytesWriter::new();
+        self.op_id.write_to(&mut writer);
+        self.value.write_to(&mut writer);
+        writer.into_bytes()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DatatypeError> {
+        let mut reader = BytesReader::new(bytes);
+        let op_id = OperationId::read_from(&mut reader)?;
+        let value = Primitive::read_from(&mut reader)?;
+        if !reader.is_empty() {
+            return Err(DatatypeError::FailedToDeserialize(
+                "trailing bytes in variable crdt".to_owned(),
+            ));
+        }
+        Ok(Self { value, op_id })
+    }
+}
+
+#[cfg(test)]
+mod tests_variable_crdt {
+    use crate::{
+        datatypes::crdts::variable_crdt::VariableCrdt,
+        types::{operation_id::OperationId, primitive::Primitive, uid::Cuid},
+    };
+
+    #[test]
+    fn can_resolve_concurrent_sets_by_operation_id() {
+        let cuid1 = Cuid::try_from("0000000000000001").unwrap();
+        let cuid2 = Cuid::try_from("0000000000000002").unwrap();
+        let mut op_id1 = OperationId::new_with_cuid(&cuid1);
+        let mut op_id2 = OperationId::new_with_cuid(&cuid2);
+        op_id1.next_lamport();
+        op_id2.next_lamport();
+
+        let mut v1 = VariableCrdt::default();
+        v1.set("a".into(), &op_id1);
+        v1.set("b".into(), &op_id2);
+
+        let mut v2 = VariableCrdt::default();
+        v2.set("b".into(), &op_id2);
+        v2.set("a".into(), &op_id1);
+
+        // the same lamport is ordered by cuid, so both converge to "b"
+        assert_eq!(v1.value(), &Primitive::from("b"));
+        assert_eq!(v1.value(), v2.value());
+
+        op_id1.next_lamport();
+        assert_eq!(Some(Primitive::from("b")), v1.set("c".into(), &op_id1));
+        assert_eq!(v1.value(), &Primitive::from("c"));
+
+        // a set of a stale operation id is not applied.
+        assert_eq!(None, v1.set("d".into(), &op_id2));
+        assert_eq!(None, v1.set("d".into(), &op_id1));
+        assert_eq!(v1.value(), &Primitive::from("c"));
+    }
+
+    #[test]
+    fn can_serialize_and_deserialize_variable_crdt() {
+        let mut op_id = OperationId::new_with_cuid(&Cuid::new());
+        op_id.next_lamport();
+        let mut variable = VariableCrdt::default();
+        variable.set(Primitive::from(3.5), &op_id);
+
+        let bytes = variable.to_bytes();
+        let deserialized = VariableCrdt::from_bytes(&bytes).unwrap();
+        assert_eq!(deserialized.value(), variable.value());
+        assert_eq!(deserialized.op_id, variable.op_id);
+        assert!(VariableCrdt::from_bytes(&bytes[1..]).is_err());
+    }
+}
@@ -1,4 +1,10 @@
-use crate::{DataType, DatatypeState, datatypes::transactional::TransactionalDatatype};
+use std::{error::Error, future::Future, sync::Arc};
+
+use crate::{
+    DataType, DatatypeError, DatatypeState,
+    datatypes::transactional::TransactionalDatatype,
+    operations::{Operation, transaction::Transaction},
+};
 
 /// The `Datatype` trait defines the common interface for all
 /// conflict-free datatypes (e.g., Counter, Register).
@@ -8,6 +14,9 @@ use crate::{DataType, DatatypeState, datatypes::transactional::TransactionalData
 /// - a **type**: an enum variant of [`DataType`] describing the kind of datatype,
 /// - a **state**: a [`DatatypeState`] indicating the current lifecycle/status.
 ///
+/// Handlers can be registered to react to the changes of a datatype. They are called
+/// in order on a thread of the SDK runtime, never on the thread changing the datatype.
+/// A handler capturing a handle of its own datatype keeps the datatype alive.
 ///
 /// # Example
 /// ```
@@ -24,6 +33,92 @@ pub trait Datatype {
     fn get_key(&self) -> &str;
     fn get_type(&self) -> DataType;
     fn get_state(&self) -> DatatypeState;
+
+    /// Registers a handler called with the old and new states when the state changes,
+    /// e.g., from [`DatatypeState::DueToSubscribe`] to [`DatatypeState::Subscribed`].
+    fn on_state_change(
+        &self,
+        handler: impl Fn(DatatypeState, DatatypeState) + Send + Sync + 'static,
+    );
+
+    /// Registers a handler called with the operations of other clients when they are applied.
+    fn on_remote_operations(&self, handler: impl Fn(Vec<Operation>) + Send + Sync + 'static);
+
+    /// Registers a handler called with the transactions of other clients marked as events,
+    /// e.g., by [`Counter::as_event`](crate::Counter::as_event), when they are applied.
+    fn on_remote_event(&self, handler: impl Fn(Arc<Transaction>) + Send + Sync + 'static);
+
+    /// Registers a handler called when an error occurs in the background,
+    /// e.g., when the server rejects the datatype.
+    fn on_error(&self, handler: impl Fn(DatatypeError) + Send + Sync + 'static);
+
+    /// Registers a hook called with each local transaction right before it is committed.
+    ///
+    /// Unlike handlers, hooks are called in order on the thread committing the
+    /// transaction, while the datatype is locked for it; thus, they can read the
+    /// datatype, but must not change it. If a hook returns an error, the transaction is
+    /// rolled back, and fails with [`DatatypeError::FailedTransaction`].
+    ///
+    /// # Examples
+    /// ```
+    /// use syncyam::{Client, Datatype};
+    /// let client = Client::builder("test-collection", "test-client").build().unwrap();
+    /// let counter = client.create_counter("test-counter").unwrap();
+    /// counter.before_commit(|tx| match tx.tag() {
+    ///     Some("forbidden") => Err("forbidden transaction".into()),
+    ///     _ => Ok(()),
+    /// });
+    /// assert!(counter.transaction("forbidden", |c| Ok(c.increase())).is_err());
+    /// assert_eq!(counter.increase(), 1);
+    /// ```
+    fn before_commit(
+        &self,
+        hook: impl Fn(&Transaction) -> Result<(), Box<dyn Error + Send + Sync>> + Send + Sync + 'static,
+    );
+
+    /// Registers a handler called with each local transaction after it is committed,
+    /// e.g., to keep an audit trail.
+    fn after_commit(&self, handler: impl Fn(Arc<Transaction>) + Send + Sync + 'static);
+
+    /// Waits until the datatype becomes [`DatatypeState::Subscribed`] by a synchronization.
+    ///
+    /// It never completes if the server keeps rejecting the datatype, so it is
+    /// usually awaited with a timeout, e.g., `tokio::time::timeout`.
+    fn wait_until_subscribed(&self) -> impl Future<Output = ()> + Send;
+
+    /// Exports a snapshot of the current contents, which another client can import by
+    /// [`Client::import_datatype`](crate::Client::import_datatype).
+    ///
+    /// The snapshot is encoded in the versioned binary format with the key, type, and
+    /// [`Duid`](crate::Duid) of this datatype, but without its history. Instead, it
+    /// records the last transaction of each client included in it.
+    fn export_snapshot(&self) -> Vec<u8>;
+
+    /// Undoes the last step of the local changes, and returns whether anything is undone.
+    ///
+    /// The changes are not removed from the history, but reverted by their inverse
+    /// operations executed as a new transaction, which is synchronized like any other.
+    /// The transactions with the same tag in a row are undone together as a step.
+    ///
+    /// # Examples
+    /// ```
+    /// use syncyam::{Client, Datatype};
+    /// let client = Client::builder("test-collection", "test-client").build().unwrap();
+    /// let counter = client.create_counter("test-counter").unwrap();
+    /// counter.increase_by(2);
+    /// counter.transaction("typing", |c| { c.increase_by(3); Ok(()) }).unwrap();
+    /// counter.transaction("typing", |c| { c.increase_by(4); Ok(()) }).unwrap();
+    /// assert!(counter.undo());
+    /// assert_eq!(counter.get_value(), 2);
+    /// assert!(counter.redo());
+    /// assert_eq!(counter.get_value(), 9);
+    /// ```
+    fn undo(&self) -> bool;
+
+    /// Redoes the last step undone, and returns whether anything is redone.
+    ///
+    /// The steps undone can no longer be redone once another local change is made.
+    fn redo(&self) -> bool;
 }
 
 pub trait DatatypeBlanket {
@@ -45,6 +140,52 @@ where
     fn get_state(&self) -> DatatypeState {
         self.get_core().get_state()
     }
+
+    fn on_state_change(
+        &self,
+        handler: impl Fn(DatatypeState, DatatypeState) + Send + Sync + 'static,
+    ) {
+        self.get_core().on_state_change(handler)
+    }
+
+    fn on_remote_operations(&self, handler: impl Fn(Vec<Operation>) + Send + Sync + 'static) {
+        self.get_core().on_remote_operations(handler)
+    }
+
+    fn on_remote_event(&self, handler: impl Fn(Arc<Transaction>) + Send + Sync + 'static) {
+        self.get_core().on_remote_event(handler)
+    }
+
+    fn on_error(&self, handler: impl Fn(DatatypeError) + Send + Sync + 'static) {
+        self.get_core().on_error(handler)
+    }
+
+    fn before_commit(
+        &self,
+        hook: impl Fn(&Transaction) -> Result<(), Box<dyn Error + Send + Sync>> + Send + Sync + 'static,
+    ) {
+        self.get_core().before_commit(hook)
+    }
+
+    fn after_commit(&self, handler: impl Fn(Arc<Transaction>) + Send + Sync + 'static) {
+        self.get_core().after_commit(handler)
+    }
+
+    fn wait_until_subscribed(&self) -> impl Future<Output = ()> + Send {
+        self.get_core().wait_until_subscribed()
+    }
+
+    fn export_snapshot(&self) -> Vec<u8> {
+        self.get_core().export_snapshot()
+    }
+
+    fn undo(&self) -> bool {
+        self.get_core().undo()
+    }
+
+    fn redo(&self) -> bool {
+        self.get_core().redo()
+    }
 }
 
 #[cfg(test)]
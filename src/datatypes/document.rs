@@ -0,0 +1,394 @@
+use std::{error::Error, sync::Arc, time::Duration};
+
+use crate::{
+    DataType, DatatypeError, DatatypeState, IntoString,
+    clients::client::ClientInfo,
+    datatypes::{
+        common::ReturnType,
+        crdts::{Crdt, document_crdt::DocumentCrdt},
+        datatype::DatatypeBlanket,
+        datatype_instrument,
+        transactional::{TransactionContext, TransactionalDatatype},
+    },
+    operations::Operation,
+    types::document_value::DocumentValue,
+};
+
+/// A document is a conflict-free JSON-like tree of objects, arrays, and primitives.
+///
+/// Values are addressed by a path in the form of a JSON pointer, such as `/users/0/name`,
+/// where the empty path is the root object. The keys of an object are last-writer-wins
+/// registers, and arrays converge like [`crate::List`].
+#[derive(Clone)]
+pub struct Document {
+    datatype: Arc<TransactionalDatatype>,
+    tx_ctx: Arc<TransactionContext>,
+}
+
+impl Document {
+    pub(crate) fn new(key: String, state: DatatypeState, client_info: Arc<ClientInfo>) -> Self {
+        Document {
+            datatype: Arc::new(TransactionalDatatype::new(
+                &key,
+                DataType::Document,
+                state,
+                client_info,
+            )),
+            tx_ctx: Default::default(),
+        }
+    }
+
+    pub(crate) fn from_core(datatype: Arc<TransactionalDatatype>) -> Self {
+        Document {
+            datatype,
+            tx_ctx: Default::default(),
+        }
+    }
+
+    /// Returns a handle of this document operating in the transaction of `tx_ctx`.
+    pub(crate) fn with_tx_ctx(&self, tx_ctx: Arc<TransactionContext>) -> Self {
+        Document {
+            datatype: self.datatype.clone(),
+            tx_ctx,
+        }
+    }
+
+    fn execute(&self, op: Operation) -> Result<Vec<Option<DocumentValue>>, DatatypeError> {
+        match self
+            .datatype
+            .execute_local_operation_as_tx(self.tx_ctx.clone(), op)?
+        {
+            ReturnType::Document(values) => Ok(values),
+            _ => Ok(vec![]),
+        }
+    }
+
+    fn read<R>(&self, f: impl FnOnce(&DocumentCrdt) -> R) -> R {
+        let mutable = self.datatype.mutable.read();
+        let Crdt::Document(d) = &mutable.crdt else {
+            unreachable!()
+        };
+        f(d)
+    }
+
+    datatype_instrument! {
+    /// Puts `value` to `key` of the object at `path`.
+    ///
+    /// # Returns
+    ///
+    /// The previous value of `key`, or `None` if it did not exist
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DatatypeError::FailedToExecuteOperation`] if `path` is not an object.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use syncyam::{Client, DocumentValue};
+    /// let client = Client::builder("test-collection", "test-client").build().unwrap();
+    /// let doc = client.create_document("test-doc").unwrap();
+    /// doc.put_to_object("", "user", DocumentValue::from_iter([("name", "yam")])).unwrap();
+    /// assert_eq!(doc.put_to_object("/user", "name", "syncyam").unwrap(), Some("yam".into()));
+    /// assert_eq!(doc.get_by_path("/user/name"), Some("syncyam".into()));
+    /// assert!(doc.put_to_object("/user/name", "k", 1).is_err());
+    /// ```
+    pub fn put_to_object(
+        &self,
+        path: &str,
+        key: impl IntoString,
+        value: impl Into<DocumentValue>,
+    ) -> Result<Option<DocumentValue>, DatatypeError> {
+        let entries = vec![(key.into(), value.into())];
+        self.execute(Operation::new_document_put_to_object(path.to_owned(), entries, false))
+            .map(|mut olds| olds.pop().flatten())
+    }}
+
+    datatype_instrument! {
+    /// Removes `key` from the object at `path`.
+    ///
+    /// # Returns
+    ///
+    /// The removed value, or `None` if `key` did not exist
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use syncyam::Client;
+    /// let client = Client::builder("test-collection", "test-client").build().unwrap();
+    /// let doc = client.create_document("test-doc").unwrap();
+    /// doc.put_to_object("", "k1", 1).unwrap();
+    /// assert_eq!(doc.remove_in_object("", "k1").unwrap(), Some(1.into()));
+    /// assert_eq!(doc.get_by_path("/k1"), None);
+    /// ```
+    pub fn remove_in_object(
+        &self,
+        path: &str,
+        key: impl IntoString,
+    ) -> Result<Option<DocumentValue>, DatatypeError> {
+        self.execute(Operation::new_document_remove_in_object(path.to_owned(), key.into()))
+            .map(|mut olds| olds.pop().flatten())
+    }}
+
+    datatype_instrument! {
+    /// Inserts `values` at `pos` of the array at `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DatatypeError::FailedToExecuteOperation`] if `path` is not an array,
+    /// or `pos` is greater than its length.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use syncyam::{Client, DocumentValue};
+    /// let client = Client::builder("test-collection", "test-client").build().unwrap();
+    /// let doc = client.create_document("test-doc").unwrap();
+    /// doc.put_to_object("", "tags", DocumentValue::new_array()).unwrap();
+    /// doc.insert_to_array("/tags", 0, ["a", "c"]).unwrap();
+    /// doc.insert_to_array("/tags", 1, ["b"]).unwrap();
+    /// assert_eq!(doc.get_by_path("/tags/1"), Some("b".into()));
+    /// assert!(doc.insert_to_array("/tags", 5, [1]).is_err());
+    /// ```
+    pub fn insert_to_array<V: Into<DocumentValue>>(
+        &self,
+        path: &str,
+        pos: usize,
+        values: impl IntoIterator<Item = V>,
+    ) -> Result<(), DatatypeError> {
+        let values = values.into_iter().map(Into::into).collect();
+        self.execute(Operation::new_document_insert_to_array(path.to_owned(), pos, values))
+            .map(|_| ())
+    }}
+
+    datatype_instrument! {
+    /// Deletes `len` elements from `pos` of the array at `path`.
+    ///
+    /// # Returns
+    ///
+    /// The deleted values
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use syncyam::{Client, DocumentValue};
+    /// let client = Client::builder("test-collection", "test-client").build().unwrap();
+    /// let doc = client.create_document("test-doc").unwrap();
+    /// doc.put_to_object("", "tags", DocumentValue::Array(vec![1.into(), 2.into()])).unwrap();
+    /// assert_eq!(doc.delete_in_array("/tags", 0, 1).unwrap(), vec![1.into()]);
+    /// assert_eq!(doc.get_by_path("/tags"), Some(DocumentValue::Array(vec![2.into()])));
+    /// ```
+    pub fn delete_in_array(
+        &self,
+        path: &str,
+        pos: usize,
+        len: usize,
+    ) -> Result<Vec<DocumentValue>, DatatypeError> {
+        self.execute(Operation::new_document_delete_in_array(path.to_owned(), pos, len))
+            .map(|deleted| deleted.into_iter().flatten().collect())
+    }}
+
+    /// Returns the value at `path`, or `None` if it does not exist.
+    pub fn get_by_path(&self, path: &str) -> Option<DocumentValue> {
+        self.read(|d| d.get_by_path(path))
+    }
+
+    /// Returns the value of the whole document, which is an object.
+    pub fn to_value(&self) -> DocumentValue {
+        self.read(|d| d.to_value())
+    }
+
+    /// Exports the whole document as a JSON value.
+    #[cfg(feature = "json")]
+    pub fn to_json(&self) -> serde_json::Value {
+        self.to_value().into()
+    }
+
+    datatype_instrument! {
+    /// Replaces the whole document with the JSON `object` as a single operation.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DatatypeError::FailedToExecuteOperation`] if `object` is not an object.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use syncyam::Client;
+    /// let client = Client::builder("test-collection", "test-client").build().unwrap();
+    /// let doc = client.create_document("test-doc").unwrap();
+    /// doc.put_to_object("", "old", 1).unwrap();
+    /// doc.set_json(serde_json::json!({"users": [{"name": "yam"}]})).unwrap();
+    /// assert_eq!(doc.to_json(), serde_json::json!({"users": [{"name": "yam"}]}));
+    /// assert!(doc.set_json(serde_json::json!([1, 2])).is_err());
+    /// ```
+    #[cfg(feature = "json")]
+    pub fn set_json(&self, object: serde_json::Value) -> Result<(), DatatypeError> {
+        let serde_json::Value::Object(object) = object else {
+            return Err(crate::errors::err!(
+                DatatypeError::FailedToExecuteOperation,
+                "a document can be set only with an object"
+            ));
+        };
+        let entries = object.into_iter().map(|(k, v)| (k, v.into())).collect();
+        self.execute(Operation::new_document_put_to_object(String::new(), entries, true))
+            .map(|_| ())
+    }}
+
+    /// Returns a handle of this document whose operations and transactions wait at most
+    /// `timeout` for another transaction of the document, overriding the lock timeout of
+    /// the client.
+    ///
+    /// Once the timeout elapses, they fail with [`DatatypeError::LockTimeout`].
+    pub fn with_lock_timeout(&self, timeout: Duration) -> Self {
+        let tx_ctx = TransactionContext::with_lock_timeout(self.tx_ctx.clone(), timeout);
+        self.with_tx_ctx(Arc::new(tx_ctx))
+    }
+
+    /// Returns a handle of this document whose transactions are marked as events, which
+    /// other clients receive by [`Datatype::on_remote_event`](crate::Datatype::on_remote_event).
+    pub fn as_event(&self) -> Self {
+        self.with_tx_ctx(Arc::new(TransactionContext::for_event(self.tx_ctx.clone())))
+    }
+
+    datatype_instrument! {
+    /// Executes multiple operations atomically within a transaction.
+    ///
+    /// If the transaction function returns an error or panics, all operations within
+    /// the transaction are rolled back, leaving the document unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use syncyam::{Client, DocumentValue};
+    /// let client = Client::builder("test-collection", "test-client").build().unwrap();
+    /// let doc = client.create_document("test-doc").unwrap();
+    /// let result = doc.transaction("failing-update", |d| {
+    ///     d.put_to_object("", "list", DocumentValue::new_array())?;
+    ///     d.insert_to_array("/list", 1, [1])?; // out of bound
+    ///     Ok(())
+    /// });
+    /// assert!(result.is_err());
+    /// assert_eq!(doc.to_value(), DocumentValue::new_object()); // unchanged
+    /// ```
+    pub fn transaction<T, R>(
+        &self,
+        tag: impl IntoString,
+        tx_func: T,
+    ) -> Result<R, DatatypeError>
+    where
+        T: FnOnce(Self) -> Result<R, Box<dyn Error + Send + Sync>> + Send + Sync + 'static,
+    {
+        let this_tx_ctx = Arc::new(TransactionContext::new_within(tag, self.tx_ctx.clone()));
+        let this_tx_ctx_clone = this_tx_ctx.clone();
+        let do_tx_func = move || {
+            let mut document_clone = self.clone();
+            document_clone.tx_ctx = this_tx_ctx_clone.clone();
+            match tx_func(document_clone) {
+                Ok(r) => Ok(r),
+                Err(e) => Err(DatatypeError::FailedTransaction(e.to_string())),
+            }
+        };
+        self.datatype
+            .do_transaction(this_tx_ctx, do_tx_func)
+    }}
+
+    datatype_instrument! {
+    /// Executes multiple operations atomically within a transaction like
+    /// [`Document::transaction`], but yields to the executor instead of blocking the thread
+    /// while another transaction of this document is running.
+    pub async fn transaction_async<T, R>(
+        &self,
+        tag: impl IntoString,
+        tx_func: T,
+    ) -> Result<R, DatatypeError>
+    where
+        T: FnOnce(Self) -> Result<R, Box<dyn Error + Send + Sync>> + Send + Sync + 'static,
+    {
+        let this_tx_ctx = Arc::new(TransactionContext::new_within(tag, self.tx_ctx.clone()));
+        let this_tx_ctx_clone = this_tx_ctx.clone();
+        let do_tx_func = move || {
+            let mut document_clone = self.clone();
+            document_clone.tx_ctx = this_tx_ctx_clone.clone();
+            match tx_func(document_clone) {
+                Ok(r) => Ok(r),
+                Err(e) => Err(DatatypeError::FailedTransaction(e.to_string())),
+            }
+        };
+        self.datatype
+            .do_transaction_async(this_tx_ctx, do_tx_func)
+            .await
+    }}
+}
+
+impl DatatypeBlanket for Document {
+    fn get_core(&self) -> &TransactionalDatatype {
+        self.datatype.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests_document {
+    use tracing::instrument;
+
+    use crate::{
+        DataType, DocumentValue,
+        datatypes::{datatype::Datatype, document::Document},
+    };
+
+    #[test]
+    fn can_assert_send_and_sync_traits() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Document>();
+    }
+
+    #[test]
+    #[instrument]
+    fn can_edit_document_atomically() {
+        let doc = Document::new(
+            module_path!().to_owned(),
+            Default::default(),
+            Default::default(),
+        );
+        assert_eq!(doc.get_type(), DataType::Document);
+        let result1 = doc.transaction("success", |d| {
+            d.put_to_object("", "user", DocumentValue::new_object())?;
+            d.put_to_object("/user", "name", "yam")?;
+            d.put_to_object("/user", "tags", DocumentValue::new_array())?;
+            d.insert_to_array("/user/tags", 0, ["a", "b"])?;
+            Ok(())
+        });
+        assert!(result1.is_ok());
+        assert_eq!(doc.get_by_path("/user/tags/1"), Some("b".into()));
+
+        let result2 = doc.transaction("failure", |d| {
+            d.remove_in_object("/user", "name")?;
+            d.delete_in_array("/user/tags", 1, 2)?;
+            Ok(())
+        });
+        assert!(result2.is_err());
+        assert_eq!(doc.get_by_path("/user/name"), Some("yam".into()));
+        assert_eq!(
+            doc.get_by_path("/user/tags"),
+            Some(DocumentValue::Array(vec!["a".into(), "b".into()]))
+        );
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn can_export_and_import_json() {
+        let doc = Document::new(
+            module_path!().to_owned(),
+            Default::default(),
+            Default::default(),
+        );
+        let json = serde_json::json!({
+            "title": "yam",
+            "items": [{"id": 1, "done": false}, {"id": 2, "done": true}],
+        });
+        doc.set_json(json.clone()).unwrap();
+        assert_eq!(doc.to_json(), json);
+        doc.put_to_object("/items/0", "done", true).unwrap();
+        assert_eq!(doc.to_json()["items"][0]["done"], serde_json::json!(true));
+    }
+}
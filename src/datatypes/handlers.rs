@@ -0,0 +1,357 @@
+use std::{error::Error, sync::Arc};
+
+use parking_lot::{Mutex, RwLock};
+use tokio::sync::mpsc::{UnboundedSender, unbounded_channel};
+use tracing::warn;
+
+use crate::{
+    DatatypeError, DatatypeState,
+    operations::{Operation, transaction::Transaction},
+    utils::{runtime::get_or_init_runtime, unwind::catch_unwind},
+};
+
+const HANDLER_RUNTIME_GROUP: &str = "handlers";
+
+type StateChangeHandler = Arc<dyn Fn(DatatypeState, DatatypeState) + Send + Sync>;
+type RemoteOperationsHandler = Arc<dyn Fn(Vec<Operation>) + Send + Sync>;
+type ErrorHandler = Arc<dyn Fn(DatatypeError) + Send + Sync>;
+type TransactionHandler = Arc<dyn Fn(Arc<Transaction>) + Send + Sync>;
+type BeforeCommitHook =
+    Arc<dyn Fn(&Transaction) -> Result<(), Box<dyn Error + Send + Sync>> + Send + Sync>;
+
+/// An event of a datatype delivered to the registered handlers.
+pub enum DatatypeEvent {
+    StateChanged(DatatypeState, DatatypeState),
+    RemoteOperations(Vec<Operation>),
+    RemoteEvent(Arc<Transaction>),
+    Committed(Arc<Transaction>),
+    Error(DatatypeError),
+}
+
+#[derive(Default, Clone)]
+struct HandlerSet {
+    on_state_change: Vec<StateChangeHandler>,
+    on_remote_operations: Vec<RemoteOperationsHandler>,
+    on_remote_event: Vec<TransactionHandler>,
+    after_commit: Vec<TransactionHandler>,
+    on_error: Vec<ErrorHandler>,
+}
+
+/// EventDispatcher delivers the events of a datatype to its handlers.
+///
+/// Events are delivered in order by a task on the SDK runtime, which is started when
+/// the first handler is registered, so that handlers never run on the thread of
+/// the caller changing the datatype. A handler that panics is logged, and the events
+/// keep being delivered. In contrast, the hooks called before commits run on the thread
+/// of the caller, since they can reject the commits.
+#[derive(Default)]
+pub struct EventDispatcher {
+    handlers: Arc<RwLock<HandlerSet>>,
+    sender: Mutex<Option<UnboundedSender<DatatypeEvent>>>,
+    before_commit: RwLock<Vec<BeforeCommitHook>>,
+}
+
+impl EventDispatcher {
+    pub fn add_state_change_handler(&self, handler: StateChangeHandler) {
+        self.handlers.write().on_state_change.push(handler);
+        self.ensure_started();
+    }
+
+    pub fn add_remote_operations_handler(&self, handler: RemoteOperationsHandler) {
+        self.handlers.write().on_remote_operations.push(handler);
+        self.ensure_started();
+    }
+
+    pub fn add_remote_event_handler(&self, handler: TransactionHandler) {
+        self.handlers.write().on_remote_event.push(handler);
+        self.ensure_started();
+    }
+
+    pub fn add_after_commit_handler(&self, handler: TransactionHandler) {
+        self.handlers.write().after_commit.push(handler);
+        self.ensure_started();
+    }
+
+    pub fn add_error_handler(&self, handler: ErrorHandler) {
+        self.handlers.write().on_error.push(handler);
+        self.ensure_started();
+    }
+
+    pub fn add_before_commit_hook(&self, hook: BeforeCommitHook) {
+        self.before_commit.write().push(hook);
+    }
+
+    pub fn has_before_commit_hooks(&self) -> bool {
+        !self.before_commit.read().is_empty()
+    }
+
+    /// Calls the hooks with `tx` to commit in order, and returns the first error.
+    pub fn before_commit(&self, tx: &Transaction) -> Result<(), Box<dyn Error + Send + Sync>> {
+        // hooks are cloned, so that they can register other hooks.
+        let hooks = self.before_commit.read().clone();
+        hooks.iter().try_for_each(|hook| hook(tx))
+    }
+
+    /// Sends `event` to the handlers; it is dropped if no handler has been registered.
+    pub fn dispatch(&self, event: DatatypeEvent) {
+        if let Some(sender) = self.sender.lock().as_ref() {
+            let _ = sender.send(event);
+        }
+    }
+
+    fn ensure_started(&self) {
+        let mut sender = self.sender.lock();
+        if sender.is_some() {
+            return;
+        }
+        let (tx, mut rx) = unbounded_channel();
+        let handlers = self.handlers.clone();
+        get_or_init_runtime(HANDLER_RUNTIME_GROUP).spawn(async move {
+            while let Some(event) = rx.recv().await {
+                // handlers are cloned, so that they can register other handlers.
+                let handlers = handlers.read().clone();
+                match event {
+                    DatatypeEvent::StateChanged(old, new) => {
+                        handlers
+                            .on_state_change
+                            .iter()
+                            .for_each(|h| call_handler(|| h(old, new)));
+                    }
+                    DatatypeEvent::RemoteOperations(ops) => {
+                        handlers
+                            .on_remote_operations
+                            .iter()
+                            .for_each(|h| call_handler(|| h(ops.clone())));
+                    }
+                    DatatypeEvent::RemoteEvent(tx) => {
+                        handlers
+                            .on_remote_event
+                            .iter()
+                            .for_each(|h| call_handler(|| h(tx.clone())));
+                    }
+                    DatatypeEvent::Committed(tx) => {
+                        handlers
+                            .after_commit
+                            .iter()
+                            .for_each(|h| call_handler(|| h(tx.clone())));
+                    }
+                    DatatypeEvent::Error(err) => {
+                        handlers
+                            .on_error
+                            .iter()
+                            .for_each(|h| call_handler(|| h(err.clone())));
+                    }
+                }
+            }
+        });
+        *sender = Some(tx);
+    }
+}
+
+/// Calls a handler, so that its panic is logged instead of ending the dispatching task.
+fn call_handler(handler: impl FnOnce()) {
+    if let Err(msg) = catch_unwind(handler) {
+        warn!("handler panicked: {msg}");
+    }
+}
+
+#[cfg(test)]
+mod tests_handlers {
+    use std::{sync::Arc, thread, time::Duration};
+
+    use parking_lot::Mutex;
+    use tracing::instrument;
+
+    use crate::{
+        Client, ClientError, Datatype, DatatypeError, DatatypeState,
+        operations::body::OperationBody, server::MemoryServer,
+    };
+
+    fn new_client(server: &Arc<MemoryServer>, alias: &str) -> Client {
+        Client::builder(module_path!(), alias)
+            .with_transport(server.clone())
+            .with_sync_interval(Duration::from_secs(3600))
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    #[instrument]
+    fn can_call_handlers_off_the_caller_thread() {
+        let server = Arc::new(MemoryServer::new());
+        let client1 = new_client(&server, "c1");
+        let client2 = new_client(&server, "c2");
+        let counter1 = client1.create_counter("k1").unwrap();
+        let counter2 = client2.subscribe_counter("k1").unwrap();
+
+        let states = Arc::new(Mutex::new(vec![]));
+        let deltas = Arc::new(Mutex::new(vec![]));
+        let (states_clone, deltas_clone) = (states.clone(), deltas.clone());
+        let caller = thread::current().id();
+        counter2.on_state_change(move |old, new| {
+            assert_ne!(thread::current().id(), caller);
+            states_clone.lock().push((old, new));
+        });
+        counter2.on_remote_operations(move |ops| {
+            for op in ops {
+                if let OperationBody::CounterIncrease(body) = op.body {
+                    deltas_clone.lock().push(body.delta);
+                }
+            }
+        });
+
+        counter1.increase_by(2);
+        counter1.increase_by(3);
+        client1.sync().unwrap();
+        client2.sync().unwrap();
+        awaitility::at_most(Duration::from_secs(3))
+            .until(|| deltas.lock().len() == 2 && !states.lock().is_empty());
+        assert_eq!(*deltas.lock(), vec![2, 3]);
+        assert_eq!(
+            *states.lock(),
+            vec![(DatatypeState::DueToSubscribe, DatatypeState::Subscribed)]
+        );
+
+        // local operations are not delivered
+        counter2.increase();
+        client2.sync().unwrap();
+        thread::sleep(Duration::from_millis(10));
+        assert_eq!(deltas.lock().len(), 2);
+        assert_eq!(counter2.get_value(), 6);
+    }
+
+    #[test]
+    #[instrument]
+    fn can_keep_calling_handlers_after_panic() {
+        let client = Client::builder(module_path!(), module_path!())
+            .build()
+            .unwrap();
+        let counter = client.create_counter("k1").unwrap();
+        let (panicked, committed) = (Arc::new(Mutex::new(0)), Arc::new(Mutex::new(vec![])));
+        let (panicked_clone, committed_clone) = (panicked.clone(), committed.clone());
+        counter.after_commit(move |_| {
+            *panicked_clone.lock() += 1;
+            panic!("after_commit");
+        });
+        counter.after_commit(move |tx| committed_clone.lock().push(tx.cseq()));
+
+        counter.increase();
+        counter.increase();
+        awaitility::at_most(Duration::from_secs(3)).until(|| committed.lock().len() == 2);
+        assert_eq!(*committed.lock(), vec![1, 2]);
+        assert_eq!(*panicked.lock(), 2);
+    }
+
+    #[test]
+    #[instrument]
+    fn can_call_commit_hooks() {
+        let client = Client::builder(module_path!(), module_path!())
+            .build()
+            .unwrap();
+        let counter = client.create_counter("k1").unwrap();
+        let variable = client.create_variable("k2").unwrap();
+        let caller = thread::current().id();
+        counter.before_commit(move |tx| {
+            assert_eq!(thread::current().id(), caller);
+            match tx.tag() {
+                Some("rejected") => Err("rejected".into()),
+                _ => Ok(()),
+            }
+        });
+        let committed = Arc::new(Mutex::new(vec![]));
+        let committed_clone = committed.clone();
+        counter.after_commit(move |tx| {
+            let deltas: Vec<_> = tx
+                .iter()
+                .filter_map(|op| match &op.body {
+                    OperationBody::CounterIncrease(body) => Some(body.delta),
+                    _ => None,
+                })
+                .collect();
+            let tag = tx.tag().map(str::to_owned);
+            committed_clone.lock().push((tag, tx.cseq(), deltas));
+        });
+
+        counter.increase_by(1);
+        let result = counter.transaction("rejected", |c| Ok(c.increase_by(2)));
+        assert!(matches!(result, Err(DatatypeError::FailedTransaction(_))));
+        counter
+            .transaction("accepted", |c| {
+                c.increase_by(3);
+                // nested transactions are committed with the outer one.
+                Ok(c.transaction("rejected", |c| Ok(c.increase_by(4)))?)
+            })
+            .unwrap();
+        assert!(counter.undo());
+        assert_eq!(counter.get_value(), 1);
+
+        let result = client.transaction("rejected", &["k1", "k2"], |tx| {
+            let variable = tx.get_datatype("k2").unwrap().ensure_variable().unwrap();
+            let counter = tx.get_datatype("k1").unwrap().ensure_counter().unwrap();
+            variable.set(1);
+            Ok(counter.increase_by(5))
+        });
+        assert!(matches!(result, Err(ClientError::FailedTransaction(_))));
+        assert!(variable.get().is_null());
+
+        awaitility::at_most(Duration::from_secs(3)).until(|| committed.lock().len() == 3);
+        assert_eq!(
+            *committed.lock(),
+            vec![
+                (None, 1, vec![1]),
+                (Some("accepted".to_owned()), 2, vec![3, 4]),
+                (Some("undo".to_owned()), 3, vec![-4, -3]),
+            ]
+        );
+    }
+
+    #[test]
+    #[instrument]
+    fn can_call_remote_event_handlers() {
+        let server = Arc::new(MemoryServer::new());
+        let client1 = new_client(&server, "c1");
+        let client2 = new_client(&server, "c2");
+        let list1 = client1.create_list("k1").unwrap();
+        let list2 = client2.subscribe_list("k1").unwrap();
+        let events = Arc::new(Mutex::new(vec![]));
+        let events_clone = events.clone();
+        list2.on_remote_event(move |tx| {
+            assert!(tx.is_event());
+            events_clone.lock().push(tx.tag().map(str::to_owned));
+        });
+
+        list1.insert(0, ["a"]).unwrap();
+        list1.as_event().insert(1, ["b"]).unwrap();
+        list1
+            .as_event()
+            .transaction("added", |l| Ok(l.insert(2, ["c", "d"])?))
+            .unwrap();
+        list1
+            .transaction("not_event", |l| {
+                // only the transactions begun by the handle are marked.
+                Ok(l.as_event().insert(4, ["e"])?)
+            })
+            .unwrap();
+        client1.sync().unwrap();
+        client2.sync().unwrap();
+        assert_eq!(list2.len(), 5);
+        awaitility::at_most(Duration::from_secs(3)).until(|| events.lock().len() == 2);
+        thread::sleep(Duration::from_millis(10));
+        assert_eq!(*events.lock(), vec![None, Some("added".to_owned())]);
+    }
+
+    #[test]
+    #[instrument]
+    fn can_call_error_handlers() {
+        let server = Arc::new(MemoryServer::new());
+        let client = new_client(&server, "c1");
+        let variable = client.subscribe_variable("k1").unwrap();
+        let errors = Arc::new(Mutex::new(vec![]));
+        let errors_clone = errors.clone();
+        variable.on_error(move |e| errors_clone.lock().push(e));
+
+        assert!(client.sync().is_err());
+        awaitility::at_most(Duration::from_secs(3)).until(|| !errors.lock().is_empty());
+        assert_eq!(errors.lock()[0], DatatypeError::FailedToSync("".into()));
+    }
+}
@@ -0,0 +1,427 @@
+use std::{error::Error, sync::Arc, time::Duration};
+
+use crate::{
+    DataType, DatatypeError, DatatypeState, IntoString,
+    clients::client::ClientInfo,
+    datatypes::{
+        common::ReturnType,
+        crdts::{Crdt, list_crdt::ListCrdt},
+        datatype::DatatypeBlanket,
+        datatype_instrument,
+        transactional::{TransactionContext, TransactionalDatatype},
+    },
+    operations::Operation,
+    types::primitive::Primitive,
+};
+
+/// A list is a conflict-free ordered sequence of [`Primitive`] values.
+///
+/// It is implemented as a replicated growable array (RGA), so that concurrent
+/// inserts at the same position converge to the same order on every client.
+#[derive(Clone)]
+pub struct List {
+    datatype: Arc<TransactionalDatatype>,
+    tx_ctx: Arc<TransactionContext>,
+}
+
+impl List {
+    pub(crate) fn new(key: String, state: DatatypeState, client_info: Arc<ClientInfo>) -> Self {
+        List {
+            datatype: Arc::new(TransactionalDatatype::new(
+                &key,
+                DataType::List,
+                state,
+                client_info,
+            )),
+            tx_ctx: Default::default(),
+        }
+    }
+
+    pub(crate) fn from_core(datatype: Arc<TransactionalDatatype>) -> Self {
+        List {
+            datatype,
+            tx_ctx: Default::default(),
+        }
+    }
+
+    /// Returns a handle of this list operating in the transaction of `tx_ctx`.
+    pub(crate) fn with_tx_ctx(&self, tx_ctx: Arc<TransactionContext>) -> Self {
+        List {
+            datatype: self.datatype.clone(),
+            tx_ctx,
+        }
+    }
+
+    fn execute(&self, op: Operation) -> Result<Vec<Primitive>, DatatypeError> {
+        match self
+            .datatype
+            .execute_local_operation_as_tx(self.tx_ctx.clone(), op)?
+        {
+            ReturnType::List(values) => Ok(values),
+            _ => Ok(vec![]),
+        }
+    }
+
+    fn read<R>(&self, f: impl FnOnce(&ListCrdt) -> R) -> R {
+        let mutable = self.datatype.mutable.read();
+        let Crdt::List(l) = &mutable.crdt else {
+            unreachable!()
+        };
+        f(l)
+    }
+
+    datatype_instrument! {
+    /// Inserts `values` at `pos`, shifting the following elements to the right.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DatatypeError::FailedToExecuteOperation`] if `pos` is greater than the length.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use syncyam::{Client, Primitive};
+    /// let client = Client::builder("test-collection", "test-client").build().unwrap();
+    /// let list = client.create_list("test-list").unwrap();
+    /// list.insert(0, ["a", "c"]).unwrap();
+    /// list.insert(1, ["b"]).unwrap();
+    /// assert_eq!(list.to_vec(), vec!["a".into(), "b".into(), "c".into()]);
+    /// assert!(list.insert(10, [1]).is_err());
+    /// ```
+    pub fn insert<V: Into<Primitive>>(
+        &self,
+        pos: usize,
+        values: impl IntoIterator<Item = V>,
+    ) -> Result<(), DatatypeError> {
+        let values = values.into_iter().map(Into::into).collect();
+        self.execute(Operation::new_list_insert(pos, values))
+            .map(|_| ())
+    }}
+
+    datatype_instrument! {
+    /// Deletes `len` elements from `pos`.
+    ///
+    /// # Returns
+    ///
+    /// The deleted values
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use syncyam::{Client, Primitive};
+    /// let client = Client::builder("test-collection", "test-client").build().unwrap();
+    /// let list = client.create_list("test-list").unwrap();
+    /// list.insert(0, [1, 2, 3]).unwrap();
+    /// assert_eq!(list.delete(0, 2).unwrap(), vec![1.into(), 2.into()]);
+    /// assert_eq!(list.len(), 1);
+    /// ```
+    pub fn delete(&self, pos: usize, len: usize) -> Result<Vec<Primitive>, DatatypeError> {
+        self.execute(Operation::new_list_delete(pos, len))
+    }}
+
+    datatype_instrument! {
+    /// Replaces the value at `pos`.
+    ///
+    /// # Returns
+    ///
+    /// The previous value at `pos`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use syncyam::{Client, Primitive};
+    /// let client = Client::builder("test-collection", "test-client").build().unwrap();
+    /// let list = client.create_list("test-list").unwrap();
+    /// list.insert(0, [1]).unwrap();
+    /// assert_eq!(list.update(0, "one").unwrap(), Primitive::Int(1));
+    /// assert_eq!(list.get(0), Some("one".into()));
+    /// ```
+    pub fn update(&self, pos: usize, value: impl Into<Primitive>) -> Result<Primitive, DatatypeError> {
+        self.execute(Operation::new_list_update(pos, value.into()))
+            .map(|mut old| old.pop().unwrap_or_default())
+    }}
+
+    /// Returns the value at `pos`, or `None` if it is out of bound.
+    pub fn get(&self, pos: usize) -> Option<Primitive> {
+        self.read(|l| l.get(pos).cloned())
+    }
+
+    /// Returns the number of elements in the list.
+    pub fn len(&self) -> usize {
+        self.read(|l| l.len())
+    }
+
+    /// Returns true if the list has no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns all values in the list.
+    pub fn to_vec(&self) -> Vec<Primitive> {
+        self.read(|l| l.iter().cloned().collect())
+    }
+
+    /// Returns an iterator over a snapshot of the values in the list.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use syncyam::{Client, Primitive};
+    /// let client = Client::builder("test-collection", "test-client").build().unwrap();
+    /// let list = client.create_list("test-list").unwrap();
+    /// list.insert(0, [1, 2, 3]).unwrap();
+    /// assert_eq!(list.iter().filter_map(|v| match v {
+    ///     Primitive::Int(i) => Some(i),
+    ///     _ => None,
+    /// }).sum::<i64>(), 6);
+    /// ```
+    pub fn iter(&self) -> std::vec::IntoIter<Primitive> {
+        self.to_vec().into_iter()
+    }
+
+    /// Returns a handle of this list whose operations and transactions wait at most
+    /// `timeout` for another transaction of the list, overriding the lock timeout of
+    /// the client.
+    ///
+    /// Once the timeout elapses, they fail with [`DatatypeError::LockTimeout`].
+    pub fn with_lock_timeout(&self, timeout: Duration) -> Self {
+        let tx_ctx = TransactionContext::with_lock_timeout(self.tx_ctx.clone(), timeout);
+        self.with_tx_ctx(Arc::new(tx_ctx))
+    }
+
+    /// Returns a handle of this list whose transactions are marked as events, which
+    /// other clients receive by [`Datatype::on_remote_event`](crate::Datatype::on_remote_event).
+    pub fn as_event(&self) -> Self {
+        self.with_tx_ctx(Arc::new(TransactionContext::for_event(self.tx_ctx.clone())))
+    }
+
+    datatype_instrument! {
+    /// Executes multiple operations atomically within a transaction.
+    ///
+    /// If the transaction function returns an error or panics, all operations within
+    /// the transaction are rolled back, leaving the list unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use syncyam::{Client, Primitive};
+    /// let client = Client::builder("test-collection", "test-client").build().unwrap();
+    /// let list = client.create_list("test-list").unwrap();
+    /// let result = list.transaction("failing-update", |l| {
+    ///     l.insert(0, [1, 2])?;
+    ///     l.delete(5, 1)?; // out of bound
+    ///     Ok(())
+    /// });
+    /// assert!(result.is_err());
+    /// assert!(list.is_empty()); // unchanged
+    /// ```
+    pub fn transaction<T, R>(
+        &self,
+        tag: impl IntoString,
+        tx_func: T,
+    ) -> Result<R, DatatypeError>
+    where
+        T: FnOnce(Self) -> Result<R, Box<dyn Error + Send + Sync>> + Send + Sync + 'static,
+    {
+        let this_tx_ctx = Arc::new(TransactionContext::new_within(tag, self.tx_ctx.clone()));
+        let this_tx_ctx_clone = this_tx_ctx.clone();
+        let do_tx_func = move || {
+            let mut list_clone = self.clone();
+            list_clone.tx_ctx = this_tx_ctx_clone.clone();
+            match tx_func(list_clone) {
+                Ok(r) => Ok(r),
+                Err(e) => Err(DatatypeError::FailedTransaction(e.to_string())),
+            }
+        };
+        self.datatype
+            .do_transaction(this_tx_ctx, do_tx_func)
+    }}
+
+    datatype_instrument! {
+    /// Executes multiple operations atomically within a transaction like
+    /// [`List::transaction`], but yields to the executor instead of blocking the thread
+    /// while another transaction of this list is running.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use syncyam::Client;
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let client = Client::builder("test-collection", "test-client").build().unwrap();
+    /// let list = client.create_list_async("test-list").await.unwrap();
+    /// let result = list
+    ///     .transaction_async("update", |l| {
+    ///         l.insert(0, [1, 2])?;
+    ///         Ok(())
+    ///     })
+    ///     .await;
+    /// assert!(result.is_ok());
+    /// assert_eq!(list.len(), 2);
+    /// # }
+    /// ```
+    pub async fn transaction_async<T, R>(
+        &self,
+        tag: impl IntoString,
+        tx_func: T,
+    ) -> Result<R, DatatypeError>
+    where
+        T: FnOnce(Self) -> Result<R, Box<dyn Error + Send + Sync>> + Send + Sync + 'static,
+    {
+        let this_tx_ctx = Arc::new(TransactionContext::new_within(tag, self.tx_ctx.clone()));
+        let this_tx_ctx_clone = this_tx_ctx.clone();
+        let do_tx_func = move || {
+            let mut list_clone = self.clone();
+            list_clone.tx_ctx = this_tx_ctx_clone.clone();
+            match tx_func(list_clone) {
+                Ok(r) => Ok(r),
+                Err(e) => Err(DatatypeError::FailedTransaction(e.to_string())),
+            }
+        };
+        self.datatype
+            .do_transaction_async(this_tx_ctx, do_tx_func)
+            .await
+    }}
+}
+
+impl DatatypeBlanket for List {
+    fn get_core(&self) -> &TransactionalDatatype {
+        self.datatype.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests_list {
+    use tracing::instrument;
+
+    use crate::{
+        DataType, Primitive,
+        datatypes::{datatype::Datatype, list::List},
+    };
+
+    #[test]
+    fn can_assert_send_and_sync_traits() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<List>();
+    }
+
+    #[test]
+    #[instrument]
+    fn can_use_list_operations() {
+        let list = List::new(
+            module_path!().to_owned(),
+            Default::default(),
+            Default::default(),
+        );
+        assert_eq!(list.get_type(), DataType::List);
+        assert!(list.is_empty());
+        list.insert(0, [1, 2, 3]).unwrap();
+        list.insert(3, ["x"]).unwrap();
+        assert_eq!(list.delete(0, 1).unwrap(), vec![Primitive::Int(1)]);
+        assert_eq!(list.update(2, true).unwrap(), Primitive::from("x"));
+        assert_eq!(
+            list.to_vec(),
+            vec![Primitive::Int(2), Primitive::Int(3), Primitive::Bool(true)]
+        );
+        assert_eq!(list.get(1), Some(Primitive::Int(3)));
+        assert_eq!(list.iter().count(), 3);
+
+        assert!(list.insert(4, [0]).is_err());
+        assert!(list.delete(2, 2).is_err());
+        assert!(list.update(3, 0).is_err());
+        assert_eq!(list.len(), 3);
+    }
+
+    #[test]
+    #[instrument]
+    fn can_use_transaction() {
+        let list = List::new(
+            module_path!().to_owned(),
+            Default::default(),
+            Default::default(),
+        );
+        let result1 = list.transaction("success", |l| {
+            l.insert(0, ["a", "b", "c"])?;
+            l.delete(1, 1)?;
+            Ok(())
+        });
+        assert!(result1.is_ok());
+        assert_eq!(list.to_vec(), vec!["a".into(), "c".into()]);
+
+        let result2 = list.transaction("failure", |l| {
+            l.insert(1, ["d"])?;
+            l.update(0, "e")?;
+            l.delete(10, 1)?;
+            Ok(())
+        });
+        assert!(result2.is_err());
+        assert_eq!(list.to_vec(), vec!["a".into(), "c".into()]);
+
+        list.insert(2, ["f"]).unwrap();
+        assert_eq!(list.to_vec(), vec!["a".into(), "c".into(), "f".into()]);
+    }
+
+    #[test]
+    #[instrument]
+    fn can_nest_transactions_with_savepoints() {
+        let list = List::new(
+            module_path!().to_owned(),
+            Default::default(),
+            Default::default(),
+        );
+        list.insert(0, ["a", "b"]).unwrap();
+        list.transaction("outer", |l| {
+            l.insert(0, ["z"])?;
+            l.transaction("inner_success", |l| {
+                l.insert(3, ["c"])?;
+                Ok(())
+            })?;
+            let inner_failure: Result<(), _> = l.transaction("inner_failure", |l| {
+                l.delete(0, 1)?;
+                l.insert(0, ["x"])?;
+                l.transaction("innermost_success", |l| {
+                    l.update(1, "y")?;
+                    Ok(())
+                })?;
+                Err("inner failure".into())
+            });
+            assert!(inner_failure.is_err());
+            assert_eq!(
+                l.to_vec(),
+                vec!["z".into(), "a".into(), "b".into(), "c".into()]
+            );
+
+            l.transaction("inner_partial", |l| {
+                l.update(0, "d")?;
+                let innermost_failure = l.transaction("innermost_failure", |l| {
+                    l.delete(10, 1)?;
+                    Ok(())
+                });
+                assert!(innermost_failure.is_err());
+                l.delete(1, 1)?;
+                Ok(())
+            })?;
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(list.to_vec(), vec!["d".into(), "b".into(), "c".into()]);
+        let txs = list.datatype.mutable.read().rollback.transactions().clone();
+        assert_eq!(txs.len(), 2);
+        assert_eq!(txs[1].iter().count(), 4);
+
+        let outer_failure: Result<(), _> = list.transaction("outer_failure", |l| {
+            l.transaction("inner_success", |l| {
+                l.insert(0, ["e"])?;
+                Ok(())
+            })?;
+            Err("outer failure".into())
+        });
+        assert!(outer_failure.is_err());
+        assert_eq!(list.to_vec(), vec!["d".into(), "b".into(), "c".into()]);
+
+        assert!(list.undo());
+        assert_eq!(list.to_vec(), vec!["a".into(), "b".into()]);
+        assert!(list.undo());
+        assert!(list.is_empty());
+    }
+}
@@ -0,0 +1,339 @@
+use std::{error::Error, sync::Arc, time::Duration};
+
+use crate::{
+    DataType, DatatypeError, DatatypeState, IntoString,
+    clients::client::ClientInfo,
+    datatypes::{
+        common::ReturnType,
+        crdts::{Crdt, map_crdt::MapCrdt},
+        datatype::DatatypeBlanket,
+        datatype_instrument,
+        transactional::{TransactionContext, TransactionalDatatype},
+    },
+    operations::Operation,
+    types::primitive::Primitive,
+};
+
+/// A map is a conflict-free collection of [`Primitive`] values indexed by string keys.
+///
+/// Each key is a last-writer-wins register, so that concurrent writes to the same key
+/// converge to the value of the latest writer on every client.
+#[derive(Clone)]
+pub struct Map {
+    datatype: Arc<TransactionalDatatype>,
+    tx_ctx: Arc<TransactionContext>,
+}
+
+impl Map {
+    pub(crate) fn new(key: String, state: DatatypeState, client_info: Arc<ClientInfo>) -> Self {
+        Map {
+            datatype: Arc::new(TransactionalDatatype::new(
+                &key,
+                DataType::Map,
+                state,
+                client_info,
+            )),
+            tx_ctx: Default::default(),
+        }
+    }
+
+    pub(crate) fn from_core(datatype: Arc<TransactionalDatatype>) -> Self {
+        Map {
+            datatype,
+            tx_ctx: Default::default(),
+        }
+    }
+
+    /// Returns a handle of this map operating in the transaction of `tx_ctx`.
+    pub(crate) fn with_tx_ctx(&self, tx_ctx: Arc<TransactionContext>) -> Self {
+        Map {
+            datatype: self.datatype.clone(),
+            tx_ctx,
+        }
+    }
+
+    fn execute(&self, op: Operation) -> Vec<Option<Primitive>> {
+        match self
+            .datatype
+            .execute_local_operation_as_tx(self.tx_ctx.clone(), op)
+        {
+            Ok(ReturnType::Map(olds)) => olds,
+            _ => vec![],
+        }
+    }
+
+    fn read<R>(&self, f: impl FnOnce(&MapCrdt) -> R) -> R {
+        let mutable = self.datatype.mutable.read();
+        let Crdt::Map(m) = &mutable.crdt else {
+            unreachable!()
+        };
+        f(m)
+    }
+
+    datatype_instrument! {
+    /// Puts `value` to `key`.
+    ///
+    /// # Returns
+    ///
+    /// The previous value of `key`, or `None` if it did not exist
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use syncyam::{Client, Primitive};
+    /// let client = Client::builder("test-collection", "test-client").build().unwrap();
+    /// let map = client.create_map("test-map").unwrap();
+    /// assert_eq!(map.put("k1", "yam"), None);
+    /// assert_eq!(map.put("k1", 42), Some("yam".into()));
+    /// assert_eq!(map.get("k1"), Some(Primitive::Int(42)));
+    /// ```
+    pub fn put(&self, key: impl IntoString, value: impl Into<Primitive>) -> Option<Primitive> {
+        self.execute(Operation::new_map_put(vec![(key.into(), value.into())]))
+            .pop()
+            .flatten()
+    }}
+
+    datatype_instrument! {
+    /// Puts all `entries` as a single operation; the last one wins among the same keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use syncyam::Client;
+    /// let client = Client::builder("test-collection", "test-client").build().unwrap();
+    /// let map = client.create_map("test-map").unwrap();
+    /// map.put_all([("a", 1), ("b", 2), ("a", 3)]);
+    /// assert_eq!(map.keys(), vec!["a", "b"]);
+    /// assert_eq!(map.get("a"), Some(3.into()));
+    /// ```
+    pub fn put_all<K: IntoString, V: Into<Primitive>>(
+        &self,
+        entries: impl IntoIterator<Item = (K, V)>,
+    ) {
+        let entries = entries
+            .into_iter()
+            .map(|(k, v)| (k.into(), v.into()))
+            .collect();
+        self.execute(Operation::new_map_put(entries));
+    }}
+
+    datatype_instrument! {
+    /// Removes `key` from the map.
+    ///
+    /// # Returns
+    ///
+    /// The removed value, or `None` if `key` did not exist
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use syncyam::Client;
+    /// let client = Client::builder("test-collection", "test-client").build().unwrap();
+    /// let map = client.create_map("test-map").unwrap();
+    /// map.put("k1", true);
+    /// assert_eq!(map.remove("k1"), Some(true.into()));
+    /// assert_eq!(map.remove("k1"), None);
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn remove(&self, key: impl IntoString) -> Option<Primitive> {
+        self.execute(Operation::new_map_remove(key.into()))
+            .pop()
+            .flatten()
+    }}
+
+    /// Returns the value of `key`, or `None` if it does not exist.
+    pub fn get(&self, key: &str) -> Option<Primitive> {
+        self.read(|m| m.get(key).cloned())
+    }
+
+    /// Returns true if the map contains `key`.
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.read(|m| m.get(key).is_some())
+    }
+
+    /// Returns the keys in the map in ascending order.
+    pub fn keys(&self) -> Vec<String> {
+        self.read(|m| m.keys().cloned().collect())
+    }
+
+    /// Returns the number of keys in the map.
+    pub fn len(&self) -> usize {
+        self.read(|m| m.len())
+    }
+
+    /// Returns true if the map has no keys.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns a handle of this map whose operations and transactions wait at most
+    /// `timeout` for another transaction of the map, overriding the lock timeout of
+    /// the client.
+    ///
+    /// Once the timeout elapses, they fail with [`DatatypeError::LockTimeout`].
+    pub fn with_lock_timeout(&self, timeout: Duration) -> Self {
+        let tx_ctx = TransactionContext::with_lock_timeout(self.tx_ctx.clone(), timeout);
+        self.with_tx_ctx(Arc::new(tx_ctx))
+    }
+
+    /// Returns a handle of this map whose transactions are marked as events, which
+    /// other clients receive by [`Datatype::on_remote_event`](crate::Datatype::on_remote_event).
+    pub fn as_event(&self) -> Self {
+        self.with_tx_ctx(Arc::new(TransactionContext::for_event(self.tx_ctx.clone())))
+    }
+
+    datatype_instrument! {
+    /// Executes multiple operations atomically within a transaction.
+    ///
+    /// If the transaction function returns an error or panics, all operations within
+    /// the transaction are rolled back, leaving the map unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use syncyam::Client;
+    /// let client = Client::builder("test-collection", "test-client").build().unwrap();
+    /// let map = client.create_map("test-map").unwrap();
+    /// let result: Result<(), _> = map.transaction("failing-update", |m| {
+    ///     m.put("k1", 1);
+    ///     Err("something went wrong".into())
+    /// });
+    /// assert!(result.is_err());
+    /// assert!(map.is_empty()); // unchanged
+    /// ```
+    pub fn transaction<T, R>(
+        &self,
+        tag: impl IntoString,
+        tx_func: T,
+    ) -> Result<R, DatatypeError>
+    where
+        T: FnOnce(Self) -> Result<R, Box<dyn Error + Send + Sync>> + Send + Sync + 'static,
+    {
+        let this_tx_ctx = Arc::new(TransactionContext::new_within(tag, self.tx_ctx.clone()));
+        let this_tx_ctx_clone = this_tx_ctx.clone();
+        let do_tx_func = move || {
+            let mut map_clone = self.clone();
+            map_clone.tx_ctx = this_tx_ctx_clone.clone();
+            match tx_func(map_clone) {
+                Ok(r) => Ok(r),
+                Err(e) => Err(DatatypeError::FailedTransaction(e.to_string())),
+            }
+        };
+        self.datatype
+            .do_transaction(this_tx_ctx, do_tx_func)
+    }}
+
+    datatype_instrument! {
+    /// Executes multiple operations atomically within a transaction like
+    /// [`Map::transaction`], but yields to the executor instead of blocking the thread
+    /// while another transaction of this map is running.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use syncyam::Client;
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let client = Client::builder("test-collection", "test-client").build().unwrap();
+    /// let map = client.create_map_async("test-map").await.unwrap();
+    /// let result = map
+    ///     .transaction_async("update", |m| {
+    ///         m.put("k1", 1);
+    ///         m.remove("k1");
+    ///         Ok(())
+    ///     })
+    ///     .await;
+    /// assert!(result.is_ok());
+    /// assert!(map.is_empty());
+    /// # }
+    /// ```
+    pub async fn transaction_async<T, R>(
+        &self,
+        tag: impl IntoString,
+        tx_func: T,
+    ) -> Result<R, DatatypeError>
+    where
+        T: FnOnce(Self) -> Result<R, Box<dyn Error + Send + Sync>> + Send + Sync + 'static,
+    {
+        let this_tx_ctx = Arc::new(TransactionContext::new_within(tag, self.tx_ctx.clone()));
+        let this_tx_ctx_clone = this_tx_ctx.clone();
+        let do_tx_func = move || {
+            let mut map_clone = self.clone();
+            map_clone.tx_ctx = this_tx_ctx_clone.clone();
+            match tx_func(map_clone) {
+                Ok(r) => Ok(r),
+                Err(e) => Err(DatatypeError::FailedTransaction(e.to_string())),
+            }
+        };
+        self.datatype
+            .do_transaction_async(this_tx_ctx, do_tx_func)
+            .await
+    }}
+}
+
+impl DatatypeBlanket for Map {
+    fn get_core(&self) -> &TransactionalDatatype {
+        self.datatype.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests_map {
+    use tracing::instrument;
+
+    use crate::{
+        DataType, Primitive,
+        datatypes::{datatype::Datatype, map::Map},
+    };
+
+    #[test]
+    fn can_assert_send_and_sync_traits() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Map>();
+    }
+
+    #[test]
+    #[instrument]
+    fn can_use_map_operations() {
+        let map = Map::new(
+            module_path!().to_owned(),
+            Default::default(),
+            Default::default(),
+        );
+        assert_eq!(map.get_type(), DataType::Map);
+        assert_eq!(map.put("a", 1), None);
+        map.put_all([("b", Primitive::from("two")), ("c", Primitive::Null)]);
+        assert_eq!(map.len(), 3);
+        assert!(map.contains_key("c"));
+        assert_eq!(map.remove("a"), Some(1.into()));
+        assert_eq!(map.keys(), vec!["b", "c"]);
+        assert_eq!(map.get("a"), None);
+        assert_eq!(map.put("a", 3), None);
+        assert_eq!(map.len(), 3);
+    }
+
+    #[test]
+    #[instrument]
+    fn can_use_transaction() {
+        let map = Map::new(
+            module_path!().to_owned(),
+            Default::default(),
+            Default::default(),
+        );
+        let result1 = map.transaction("success", |m| {
+            m.put("a", 1);
+            m.put("b", 2);
+            Ok(())
+        });
+        assert!(result1.is_ok());
+        assert_eq!(map.len(), 2);
+
+        let result2: Result<(), _> = map.transaction("failure", |m| {
+            m.remove("a");
+            m.put("c", 3);
+            Err("failed".into())
+        });
+        assert!(result2.is_err());
+        assert_eq!(map.keys(), vec!["a", "b"]);
+    }
+}
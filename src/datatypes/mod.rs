@@ -3,9 +3,18 @@ pub mod common;
 pub mod counter;
 mod crdts;
 pub mod datatype;
+pub mod document;
+mod handlers;
+pub mod list;
+pub mod map;
 mod mutable;
-mod rollback;
-mod transactional;
+pub(crate) mod record;
+pub(crate) mod rollback;
+pub mod set;
+pub mod text;
+pub(crate) mod transactional;
+pub(crate) mod undo;
+pub mod variable;
 
 macro_rules! datatype_instrument {
     ($(#[$attr:meta])* $vis:vis fn $name:ident $($rest:tt)*) => {
@@ -21,13 +30,33 @@ macro_rules! datatype_instrument {
         )]
         $vis fn $name $($rest)*
     };
+    ($(#[$attr:meta])* $vis:vis async fn $name:ident $($rest:tt)*) => {
+        $(#[$attr])*
+        #[tracing::instrument(skip_all,
+            fields(
+                syncyam.col=%self.datatype.attr.client_info.collection,
+                syncyam.cl=%self.datatype.attr.client_info.alias,
+                syncyam.cuid=%self.datatype.attr.client_info.cuid,
+                syncyam.dt=%self.datatype.attr.key,
+                syncyam.duid=%self.datatype.attr.duid,
+            )
+        )]
+        $vis async fn $name $($rest)*
+    };
 }
 
 use std::sync::Arc;
 
 pub(crate) use datatype_instrument;
 
-use crate::{Counter, DataType, Datatype, DatatypeState, clients::client::ClientInfo};
+use crate::{
+    Counter, DataType, Datatype, DatatypeState, Document, List, Map, Set, Text, Variable,
+    clients::client::ClientInfo,
+    datatypes::{
+        datatype::DatatypeBlanket,
+        transactional::{TransactionContext, TransactionalDatatype},
+    },
+};
 
 /// A typed wrapper for concrete datatypes managed by the client.
 ///
@@ -36,6 +65,12 @@ use crate::{Counter, DataType, Datatype, DatatypeState, clients::client::ClientI
 #[derive(Clone)]
 pub enum DatatypeSet {
     Counter(Counter),
+    Variable(Variable),
+    List(List),
+    Map(Map),
+    Document(Document),
+    Text(Text),
+    Set(Set),
 }
 
 impl DatatypeSet {
@@ -43,6 +78,12 @@ impl DatatypeSet {
     pub fn get_type(&self) -> DataType {
         match self {
             DatatypeSet::Counter(_) => DataType::Counter,
+            DatatypeSet::Variable(_) => DataType::Variable,
+            DatatypeSet::List(_) => DataType::List,
+            DatatypeSet::Map(_) => DataType::Map,
+            DatatypeSet::Document(_) => DataType::Document,
+            DatatypeSet::Text(_) => DataType::Text,
+            DatatypeSet::Set(_) => DataType::Set,
         }
     }
 
@@ -51,6 +92,12 @@ impl DatatypeSet {
     pub fn get_state(&self) -> DatatypeState {
         match self {
             DatatypeSet::Counter(cnt) => cnt.get_state(),
+            DatatypeSet::Variable(var) => var.get_state(),
+            DatatypeSet::List(list) => list.get_state(),
+            DatatypeSet::Map(map) => map.get_state(),
+            DatatypeSet::Document(doc) => doc.get_state(),
+            DatatypeSet::Text(text) => text.get_state(),
+            DatatypeSet::Set(set) => set.get_state(),
         }
     }
 
@@ -68,9 +115,43 @@ impl DatatypeSet {
             DataType::Counter => {
                 DatatypeSet::Counter(Counter::new(key.to_owned(), state, client_info))
             }
-            _ => {
-                todo!()
+            DataType::Variable => {
+                DatatypeSet::Variable(Variable::new(key.to_owned(), state, client_info))
+            }
+            DataType::List => DatatypeSet::List(List::new(key.to_owned(), state, client_info)),
+            DataType::Map => DatatypeSet::Map(Map::new(key.to_owned(), state, client_info)),
+            DataType::Document => {
+                DatatypeSet::Document(Document::new(key.to_owned(), state, client_info))
             }
+            DataType::Text => DatatypeSet::Text(Text::new(key.to_owned(), state, client_info)),
+            DataType::Set => DatatypeSet::Set(Set::new(key.to_owned(), state, client_info)),
+        }
+    }
+
+    /// Creates a [`DatatypeSet`] wrapping an existing [`TransactionalDatatype`],
+    /// e.g., one restored from the storage.
+    pub(crate) fn from_core(datatype: Arc<TransactionalDatatype>) -> Self {
+        match datatype.get_type() {
+            DataType::Counter => DatatypeSet::Counter(Counter::from_core(datatype)),
+            DataType::Variable => DatatypeSet::Variable(Variable::from_core(datatype)),
+            DataType::List => DatatypeSet::List(List::from_core(datatype)),
+            DataType::Map => DatatypeSet::Map(Map::from_core(datatype)),
+            DataType::Document => DatatypeSet::Document(Document::from_core(datatype)),
+            DataType::Text => DatatypeSet::Text(Text::from_core(datatype)),
+            DataType::Set => DatatypeSet::Set(Set::from_core(datatype)),
+        }
+    }
+
+    /// Returns the same variant with a handle operating in the transaction of `tx_ctx`.
+    pub(crate) fn with_tx_ctx(&self, tx_ctx: Arc<TransactionContext>) -> Self {
+        match self {
+            DatatypeSet::Counter(cnt) => DatatypeSet::Counter(cnt.with_tx_ctx(tx_ctx)),
+            DatatypeSet::Variable(var) => DatatypeSet::Variable(var.with_tx_ctx(tx_ctx)),
+            DatatypeSet::List(list) => DatatypeSet::List(list.with_tx_ctx(tx_ctx)),
+            DatatypeSet::Map(map) => DatatypeSet::Map(map.with_tx_ctx(tx_ctx)),
+            DatatypeSet::Document(doc) => DatatypeSet::Document(doc.with_tx_ctx(tx_ctx)),
+            DatatypeSet::Text(text) => DatatypeSet::Text(text.with_tx_ctx(tx_ctx)),
+            DatatypeSet::Set(set) => DatatypeSet::Set(set.with_tx_ctx(tx_ctx)),
         }
     }
 
@@ -80,6 +161,81 @@ impl DatatypeSet {
     pub fn ensure_counter(self) -> Option<Counter> {
         match self {
             DatatypeSet::Counter(cnt) => Some(cnt),
+            _ => None,
+        }
+    }
+
+    /// Ensure and return the internal datatype if the type matches `DataType::Variable`.
+    ///
+    /// If the type doesn't match, this returns None.
+    pub fn ensure_variable(self) -> Option<Variable> {
+        match self {
+            DatatypeSet::Variable(var) => Some(var),
+            _ => None,
+        }
+    }
+
+    /// Ensure and return the internal datatype if the type matches `DataType::List`.
+    ///
+    /// If the type doesn't match, this returns None.
+    pub fn ensure_list(self) -> Option<List> {
+        match self {
+            DatatypeSet::List(list) => Some(list),
+            _ => None,
+        }
+    }
+
+    /// Ensure and return the internal datatype if the type matches `DataType::Map`.
+    ///
+    /// If the type doesn't match, this returns None.
+    pub fn ensure_map(self) -> Option<Map> {
+        match self {
+            DatatypeSet::Map(map) => Some(map),
+            _ => None,
+        }
+    }
+
+    /// Ensure and return the internal datatype if the type matches `DataType::Document`.
+    ///
+    /// If the type doesn't match, this returns None.
+    pub fn ensure_document(self) -> Option<Document> {
+        match self {
+            DatatypeSet::Document(doc) => Some(doc),
+            _ => None,
+        }
+    }
+
+    /// Ensure and return the internal datatype if the type matches `DataType::Text`.
+    ///
+    /// If the type doesn't match, this returns None.
+    pub fn ensure_text(self) -> Option<Text> {
+        match self {
+            DatatypeSet::Text(text) => Some(text),
+            _ => None,
+        }
+    }
+
+    /// Ensure and return the internal datatype if the type matches `DataType::Set`.
+    ///
+    /// If the type doesn't match, this returns None.
+    pub fn ensure_set(self) -> Option<Set> {
+        match self {
+            DatatypeSet::Set(set) => Some(set),
+            _ => None,
+        }
+    }
+}
+
+impl DatatypeBlanket for DatatypeSet {
+    fn get_core(&self) -> &TransactionalDatatype {
+        match self {
+            DatatypeSet::Counter(cnt) => cnt.get_core(),
+            DatatypeSet::Variable(var) => var.get_core(),
+            DatatypeSet::List(list) => list.get_core(),
+            DatatypeSet::Map(map) => map.get_core(),
+            DatatypeSet::Document(doc) => doc.get_core(),
+            DatatypeSet::Text(text) => text.get_core(),
+            DatatypeSet::Set(set) => set.get_core(),
         }
     }
 }
@@ -100,8 +256,8 @@ mod tests_datatype_set {
             Default::default(),
         );
         let ds2 = ds1.clone();
-        let DatatypeSet::Counter(cnt1) = ds1;
-        let DatatypeSet::Counter(cnt2) = ds2;
+        let cnt1 = ds1.ensure_counter().unwrap();
+        let cnt2 = ds2.ensure_counter().unwrap();
 
         // Cloned DatatypeSet contains a cloned Counter (same variant, same key)
         assert_eq!(cnt1.get_key(), cnt2.get_key());
@@ -122,4 +278,29 @@ mod tests_datatype_set {
         let ptr2: *const TransactionalDatatype = cnt2.get_core();
         assert_eq!(ptr1, ptr2);
     }
+
+    #[test]
+    fn can_ensure_datatypes() {
+        let ds = DatatypeSet::new(
+            DataType::Variable,
+            "k1",
+            DatatypeState::DueToCreate,
+            Default::default(),
+        );
+        assert_eq!(ds.get_type(), DataType::Variable);
+        assert_eq!(ds.get_state(), DatatypeState::DueToCreate);
+        assert!(ds.clone().ensure_counter().is_none());
+        assert!(ds.clone().ensure_list().is_none());
+        assert!(ds.ensure_variable().is_some());
+
+        let ds = DatatypeSet::new(
+            DataType::List,
+            "k2",
+            DatatypeState::DueToSubscribe,
+            Default::default(),
+        );
+        assert_eq!(ds.get_type(), DataType::List);
+        assert_eq!(ds.get_state(), DatatypeState::DueToSubscribe);
+        assert!(ds.ensure_list().is_some());
+    }
 }
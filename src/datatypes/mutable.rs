@@ -1,14 +1,38 @@
-use std::sync::Arc;
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Arc,
+};
 
-use tracing::instrument;
+use tracing::{info, instrument};
 
 use crate::{
     DataType, DatatypeError, DatatypeState,
-    datatypes::{common::ReturnType, crdts::Crdt, rollback::RollbackData},
+    clients::client::ClientInfo,
+    datatypes::{
+        common::ReturnType,
+        crdts::Crdt,
+        record::{DatatypeRecord, DatatypeSnapshot, ExportedSnapshot},
+        rollback::RollbackData,
+        transactional::TransactionContext,
+        undo::UndoManager,
+    },
+    errors::err,
     operations::{Operation, transaction::Transaction},
-    types::operation_id::OperationId,
+    protocol::Checkpoint,
+    types::{
+        operation_id::OperationId,
+        uid::{Cuid, Duid},
+    },
 };
 
+/// A point in the ongoing transaction to which it can be rolled back, e.g., when a
+/// nested transaction fails.
+#[derive(Debug, Clone, Copy)]
+pub struct Savepoint {
+    operations: Option<usize>,
+    inverses: usize,
+}
+
 #[derive(Debug)]
 pub struct MutableDatatype {
     pub crdt: Crdt,
@@ -16,16 +40,26 @@ pub struct MutableDatatype {
     pub op_id: OperationId,
     pub transaction: Option<Transaction>,
     pub rollback: RollbackData,
+    pub checkpoint: Checkpoint,
+    pub undo: UndoManager,
+    remote_cseqs: HashMap<Cuid, u64>,
+    /// The pending local transactions checkpointed into the rollback data over the
+    /// budget, which are kept only to be pushed.
+    push_queue: VecDeque<Arc<Transaction>>,
 }
 
 impl MutableDatatype {
-    pub fn new(r#type: DataType, state: DatatypeState) -> Self {
+    pub fn new(r#type: DataType, state: DatatypeState, client_info: &ClientInfo) -> Self {
         Self {
             crdt: Crdt::new(r#type),
             state,
-            op_id: OperationId::new(),
+            op_id: OperationId::new_with_cuid(&client_info.cuid),
             transaction: Default::default(),
-            rollback: Default::default(),
+            rollback: RollbackData::new(client_info.rollback_budget.clone()),
+            checkpoint: Default::default(),
+            undo: UndoManager::new(client_info.undo_depth),
+            remote_cseqs: Default::default(),
+            push_queue: Default::default(),
         }
     }
 
@@ -38,74 +72,578 @@ impl MutableDatatype {
     pub fn do_rollback(&mut self) {
         self.op_id = self.rollback.op_id.clone();
         self.state = self.rollback.state;
-        self.crdt.deserialize(&self.rollback.crdt);
-        let transactions = self.rollback.transactions.clone();
+        if let Err(e) = self.crdt.deserialize(&self.rollback.crdt) {
+            // this cannot happen since the rollback data is serialized by itself or
+            // validated when restored.
+            unreachable!("failed to roll back: {e}");
+        }
+        self.transaction = None;
+        let transactions = self.rollback.transactions().clone();
 
         for tx in transactions.iter() {
             self.replay_transaction(tx);
         }
     }
 
-    pub fn end_transaction(&mut self, tag: Option<String>, committed: bool) {
+    /// Returns a savepoint at the current point of the ongoing transaction.
+    pub fn savepoint(&self) -> Savepoint {
+        Savepoint {
+            operations: self.transaction.as_ref().map(|tx| tx.iter().len()),
+            inverses: self.undo.savepoint(),
+        }
+    }
+
+    /// Rolls back the ongoing transaction to `savepoint`, keeping the operations
+    /// executed before it.
+    #[instrument(skip_all)]
+    pub fn rollback_to(&mut self, savepoint: Savepoint) {
+        let transaction = self.transaction.take();
+        self.do_rollback();
+        self.undo.rollback_to(savepoint.inverses);
+        if let (Some(mut tx), Some(len)) = (transaction, savepoint.operations) {
+            tx.truncate(len);
+            replay(&mut self.crdt, &mut self.op_id, &tx);
+            self.transaction = Some(tx);
+        }
+    }
+
+    /// Checkpoints the CRDT into the rollback data, dropping the transactions
+    /// acknowledged by the server from the history.
+    ///
+    /// The pending local transactions are kept in the history, since they are yet to be
+    /// pushed. If the history still exceeds the budget, however, they are checkpointed as
+    /// well and moved to the push queue, so that the history of the datatype is emptied.
+    #[instrument(skip_all)]
+    pub fn trim_rollback(&mut self) {
+        while self
+            .push_queue
+            .front()
+            .is_some_and(|tx| !self.is_pending(tx))
+        {
+            self.push_queue.pop_front();
+        }
+        let acknowledged = self
+            .rollback
+            .transactions()
+            .iter()
+            .take_while(|tx| !self.is_pending(tx))
+            .count();
+        self.checkpoint_rollback(acknowledged);
+        if self.rollback.is_over_budget() {
+            let len = self.rollback.transactions().len();
+            self.checkpoint_rollback(len);
+        }
+    }
+
+    /// Checkpoints the first `n` transactions of the history into the rollback data.
+    fn checkpoint_rollback(&mut self, n: usize) {
+        if n == 0 {
+            return;
+        }
+        let pending = self
+            .rollback
+            .transactions()
+            .iter()
+            .take(n)
+            .filter(|tx| self.is_pending(tx))
+            .cloned()
+            .collect::<Vec<_>>();
+        self.push_queue.extend(pending);
+        if n == self.rollback.transactions().len() && self.transaction.is_none() {
+            self.set_rollback();
+            return;
+        }
+        // otherwise, the checkpoint is made apart from the current CRDT, which has
+        // the rest of the history or the ongoing transaction executed.
+        let mut crdt = Crdt::new(self.crdt.get_type());
+        let mut op_id = self.rollback.op_id.clone();
+        if let Err(e) = crdt.deserialize(&self.rollback.crdt) {
+            unreachable!("failed to trim the rollback data: {e}");
+        }
+        for tx in self.rollback.drain_transactions(n) {
+            replay(&mut crdt, &mut op_id, &tx);
+        }
+        self.rollback.crdt = crdt.serialize();
+        self.rollback.op_id = op_id;
+    }
+
+    /// Ends the ongoing transaction, and returns the transaction if it is committed as
+    /// labeled by `tx_ctx`.
+    pub fn end_transaction(
+        &mut self,
+        tx_ctx: &TransactionContext,
+        committed: bool,
+    ) -> Option<Arc<Transaction>> {
         if committed {
+            self.undo.commit(tx_ctx.tag().map(str::to_owned));
             if let Some(mut tx) = self.transaction.take() {
-                tx.set_tag(tag);
+                tx_ctx.label(&mut tx);
                 let tx = Arc::new(tx);
-                self.rollback.push_transaction(tx);
+                self.rollback.push_transaction(tx.clone());
+                if self.rollback.is_over_budget() {
+                    self.trim_rollback();
+                }
+                return Some(tx);
             }
         } else {
+            self.undo.discard();
             self.do_rollback();
         }
+        None
     }
 
-    fn replay_local_operation(
-        &mut self,
-        op: &Operation,
-        op_id: &OperationId,
-    ) -> Result<ReturnType, DatatypeError> {
-        self.op_id.sync(op_id);
-        let result = self.crdt.execute_local_operation(op);
-        if result.is_err() {
-            // this cannot happen
-            unreachable!()
+    fn replay_transaction(&mut self, tx: &Arc<Transaction>) {
+        replay(&mut self.crdt, &mut self.op_id, tx);
+    }
+
+    /// Creates a snapshot from which this datatype can be restored.
+    pub fn to_snapshot(&self, r#type: DataType, duid: &Duid) -> DatatypeSnapshot {
+        DatatypeSnapshot {
+            r#type,
+            duid: duid.clone(),
+            state: self.state,
+            checkpoint: self.checkpoint,
+            remote_cseqs: self
+                .remote_cseqs
+                .iter()
+                .map(|(cuid, cseq)| (cuid.clone(), *cseq))
+                .collect(),
+            op_id: self.rollback.op_id.clone(),
+            crdt: self.rollback.crdt.clone(),
+            transactions: self.rollback.transactions().iter().cloned().collect(),
+            push_queue: self.push_queue.iter().cloned().collect(),
         }
-        result
     }
 
-    fn replay_transaction(&mut self, tx: &Arc<Transaction>) {
+    /// Restores this datatype from `snapshot` by replaying its transactions.
+    pub fn restore(&mut self, snapshot: DatatypeSnapshot) -> Result<(), DatatypeError> {
+        self.crdt.deserialize(&snapshot.crdt)?;
+        self.checkpoint = snapshot.checkpoint;
+        self.remote_cseqs = snapshot.remote_cseqs.into_iter().collect();
+        self.rollback.op_id = snapshot.op_id;
+        self.rollback.crdt = snapshot.crdt;
+        self.rollback.state = snapshot.state;
+        self.rollback.set_transactions(snapshot.transactions);
+        self.push_queue = snapshot.push_queue.into();
+        self.do_rollback();
+        Ok(())
+    }
+
+    /// Exports the current CRDT, which includes the transactions of this client up to its
+    /// last cseq as well as the remote ones.
+    pub fn export(&self, key: &str, r#type: DataType, duid: &Duid) -> ExportedSnapshot {
+        let mut remote_cseqs: Vec<_> = self
+            .remote_cseqs
+            .iter()
+            .map(|(cuid, cseq)| (cuid.clone(), *cseq))
+            .collect();
+        remote_cseqs.push((self.op_id.cuid.clone(), self.op_id.cseq));
+        ExportedSnapshot {
+            key: key.to_owned(),
+            r#type,
+            duid: duid.clone(),
+            op_id: self.op_id.clone(),
+            remote_cseqs,
+            crdt: self.crdt.serialize(),
+        }
+    }
+
+    /// Imports the CRDT of `snapshot`. The transactions included in it are skipped when
+    /// pulled from the server, even from the one of the exporting client.
+    pub fn import(&mut self, snapshot: &ExportedSnapshot) -> Result<(), DatatypeError> {
+        self.crdt.deserialize(&snapshot.crdt)?;
+        self.op_id.sync(&snapshot.op_id);
+        self.remote_cseqs = snapshot.remote_cseqs.iter().cloned().collect();
+        Ok(())
+    }
+
+    /// Applies a record logged after the snapshot.
+    pub fn apply_record(&mut self, record: DatatypeRecord) -> Result<(), DatatypeError> {
+        match record {
+            DatatypeRecord::Snapshot(snapshot) => self.restore(*snapshot)?,
+            DatatypeRecord::Transaction(tx) if *tx.cuid() == self.op_id.cuid => {
+                self.replay_transaction(&tx);
+                self.rollback.push_transaction(tx);
+            }
+            DatatypeRecord::Transaction(tx) => {
+                self.execute_remote_transaction(tx)?;
+            }
+            DatatypeRecord::Checkpoint(checkpoint) => self.checkpoint.sync(&checkpoint),
+            DatatypeRecord::State(state) => self.set_state(state)?,
+        }
+        Ok(())
+    }
+
+    /// Sets the state, which is also kept across rollbacks.
+    ///
+    /// Setting the current state again is allowed and changes nothing, whereas a change
+    /// not allowed by [`DatatypeState::can_transit_to`] fails.
+    pub fn set_state(&mut self, state: DatatypeState) -> Result<(), DatatypeError> {
+        let old = self.state;
+        if old == state {
+            return Ok(());
+        }
+        if !old.can_transit_to(state) {
+            return Err(err!(
+                DatatypeError::IllegalStateTransition,
+                format!("{old:?} -> {state:?}")
+            ));
+        }
+        info!("state: {old:?} -> {state:?}");
+        self.state = state;
+        self.rollback.state = state;
+        Ok(())
+    }
+
+    /// Returns whether `tx` is a local transaction not yet acknowledged by the server.
+    fn is_pending(&self, tx: &Transaction) -> bool {
+        *tx.cuid() == self.op_id.cuid && tx.cseq() > self.checkpoint.cseq
+    }
+
+    /// Returns the local transactions not yet acknowledged by the server.
+    pub fn pending_transactions(&self) -> Vec<Arc<Transaction>> {
+        self.push_queue
+            .iter()
+            .chain(self.rollback.transactions().iter())
+            .filter(|tx| self.is_pending(tx))
+            .cloned()
+            .collect()
+    }
+
+    /// Executes a transaction from another client.
+    ///
+    /// The lamport clock is synchronized with the operations, and the transaction is
+    /// ignored if it has been already executed or is issued by this client. Since the
+    /// transactions of each client are executed in the order of their cseqs, it fails
+    /// if any earlier transaction of the client is missing, so that the transactions
+    /// from the missing one are pulled again rather than skipped.
+    /// The executed transaction is placed in the rollback history before the pending
+    /// local transactions, which are thereby rebased over the remote transaction.
+    #[instrument(skip_all)]
+    pub fn execute_remote_transaction(
+        &mut self,
+        tx: Arc<Transaction>,
+    ) -> Result<bool, DatatypeError> {
         if *tx.cuid() == self.op_id.cuid {
-            let mut op_id = tx.get_op_id();
-            tx.iter().for_each(|op| {
-                op_id.lamport = op.lamport;
-                self.replay_local_operation(op, &op_id).unwrap();
-            });
-        } else {
-            // replay remote operation
+            return Ok(false);
+        }
+        let seen = self.remote_cseqs.get(tx.cuid()).copied().unwrap_or(0);
+        if tx.cseq() <= seen {
+            return Ok(false);
         }
+        if tx.cseq() != seen + 1 {
+            return Err(err!(
+                DatatypeError::FailedToSync,
+                format!(
+                    "missing transactions of {} after cseq {seen}: {tx}",
+                    tx.cuid()
+                )
+            ));
+        }
+        let mut op_id = tx.get_op_id();
+        for op in tx.iter() {
+            op_id.lamport = op.lamport;
+            self.op_id.sync(&op_id);
+            self.crdt.execute_remote_operation(op, &op_id)?;
+        }
+        self.remote_cseqs.insert(tx.cuid().clone(), tx.cseq());
+
+        let pos = self
+            .rollback
+            .transactions()
+            .iter()
+            .rposition(|t| !self.is_pending(t))
+            .map_or(0, |p| p + 1);
+        self.rollback.insert_transaction(pos, tx);
+        if self.rollback.is_over_budget() {
+            self.trim_rollback();
+        }
+        Ok(true)
     }
 
     #[instrument(skip_all)]
-    pub fn execute_local_operation(
+    pub fn execute_local_operation(&mut self, op: Operation) -> Result<ReturnType, DatatypeError> {
+        self.execute_operation(op, false)
+    }
+
+    /// Executes the inverse operations of the last step to undo, or to redo if `redo` is
+    /// true, in the ongoing transaction, and returns whether any of them is executed.
+    ///
+    /// The inverse operations no longer applicable, e.g., to an object removed by another
+    /// client, are skipped.
+    #[instrument(skip_all)]
+    pub fn execute_undo(&mut self, redo: bool) -> bool {
+        let Some(inverses) = self.undo.begin(redo) else {
+            return false;
+        };
+        for inverse in inverses {
+            match self.execute_operation(inverse.op.clone(), true) {
+                Ok(_) => self.undo.replace(&inverse, &self.op_id),
+                Err(e) => info!("skipped an inverse operation: {e}"),
+            }
+        }
+        self.transaction.is_some()
+    }
+
+    /// Executes `op` in the ongoing transaction, which begins if there is none.
+    ///
+    /// If `resolved` is true, `op` has its targets resolved already, and is executed
+    /// like a remote operation.
+    fn execute_operation(
         &mut self,
         mut op: Operation,
+        resolved: bool,
     ) -> Result<ReturnType, DatatypeError> {
+        if !self.state.is_writable() {
+            return Err(err!(
+                DatatypeError::ClosedDatatype,
+                format!("cannot execute {op} in {:?}", self.state)
+            ));
+        }
         let is_new_tx = self.transaction.is_none();
         if is_new_tx {
             self.transaction = Some(Transaction::new(&mut self.op_id));
         }
         op.set_lamport(self.op_id.next_lamport());
-        let result = self.crdt.execute_local_operation(&op);
-        if result.is_ok() {
-            if let Some(tx) = self.transaction.as_mut() {
-                tx.push_operation(op);
+        let result = match resolved {
+            true => self.crdt.execute_remote_operation(&op, &self.op_id),
+            false => self.crdt.execute_local_operation(&mut op, &self.op_id),
+        };
+        match &result {
+            Ok(ret) => {
+                self.undo.record(&op, ret, &self.op_id);
+                if let Some(tx) = self.transaction.as_mut() {
+                    tx.push_operation(op);
+                }
             }
-        } else {
-            if is_new_tx {
-                self.op_id.prev_cseq();
-                self.transaction = None;
+            Err(_) => {
+                if is_new_tx {
+                    self.op_id.prev_cseq();
+                    self.transaction = None;
+                }
+                self.op_id.prev_lamport();
             }
-            self.op_id.prev_lamport();
         }
         result
     }
 }
+
+/// Replays `tx` on `crdt`, synchronizing `op_id` with it.
+fn replay(crdt: &mut Crdt, op_id: &mut OperationId, tx: &Transaction) {
+    // Operations in the history have their targets resolved, so that both local and
+    // remote transactions are replayed in the same way as remote ones.
+    let mut tx_op_id = tx.get_op_id();
+    for op in tx.iter() {
+        tx_op_id.lamport = op.lamport;
+        op_id.sync(&tx_op_id);
+        if let Err(e) = crdt.execute_remote_operation(op, &tx_op_id) {
+            // this cannot happen since the transaction has been executed before.
+            unreachable!("failed to replay {tx}: {e}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_mutable_datatype {
+    use std::sync::Arc;
+
+    use crate::{
+        DataType, DatatypeError, DatatypeState,
+        clients::client::ClientInfo,
+        datatypes::{
+            crdts::Crdt, mutable::MutableDatatype, record::DatatypeRecord, rollback::RollbackBudget,
+        },
+        operations::{Operation, transaction::Transaction},
+        types::{operation_id::OperationId, uid::Cuid},
+    };
+
+    const STATES: [DatatypeState; 8] = [
+        DatatypeState::DueToCreate,
+        DatatypeState::DueToSubscribe,
+        DatatypeState::DueToSubscribeOrCreate,
+        DatatypeState::Subscribed,
+        DatatypeState::DueToUnsubscribe,
+        DatatypeState::Closed,
+        DatatypeState::DueToDelete,
+        DatatypeState::Deleted,
+    ];
+
+    #[test]
+    fn can_validate_every_state_transition() {
+        use DatatypeState::*;
+        let legal = [
+            (DueToCreate, Subscribed),
+            (DueToCreate, DueToUnsubscribe),
+            (DueToCreate, Closed),
+            (DueToCreate, DueToDelete),
+            (DueToCreate, Deleted),
+            (DueToSubscribe, Subscribed),
+            (DueToSubscribe, DueToUnsubscribe),
+            (DueToSubscribe, Closed),
+            (DueToSubscribe, DueToDelete),
+            (DueToSubscribe, Deleted),
+            (DueToSubscribeOrCreate, Subscribed),
+            (DueToSubscribeOrCreate, DueToUnsubscribe),
+            (DueToSubscribeOrCreate, Closed),
+            (DueToSubscribeOrCreate, DueToDelete),
+            (DueToSubscribeOrCreate, Deleted),
+            (Subscribed, DueToUnsubscribe),
+            (Subscribed, Closed),
+            (Subscribed, DueToDelete),
+            (Subscribed, Deleted),
+            (DueToUnsubscribe, Closed),
+            (DueToUnsubscribe, Deleted),
+            (Closed, DueToCreate),
+            (Closed, DueToSubscribe),
+            (Closed, DueToSubscribeOrCreate),
+            (DueToDelete, Deleted),
+        ];
+        let info = ClientInfo::default();
+        for old in STATES {
+            for new in STATES {
+                let mut mutable = MutableDatatype::new(DataType::Counter, old, &info);
+                let result = mutable.set_state(new);
+                if old == new {
+                    assert!(result.is_ok());
+                    assert_eq!(mutable.state, old);
+                } else if legal.contains(&(old, new)) {
+                    assert!(result.is_ok(), "{old:?} -> {new:?}");
+                    assert_eq!(mutable.state, new);
+                    assert_eq!(mutable.rollback.state, new);
+                } else {
+                    assert_eq!(
+                        result.unwrap_err(),
+                        DatatypeError::IllegalStateTransition("".into()),
+                        "{old:?} -> {new:?}"
+                    );
+                    assert_eq!(mutable.state, old);
+                }
+                assert_eq!(old.can_transit_to(new), legal.contains(&(old, new)));
+            }
+        }
+    }
+
+    #[test]
+    fn can_trim_rollback_over_budget() {
+        let budget = Arc::new(RollbackBudget::new(1, usize::MAX));
+        let info = ClientInfo {
+            rollback_budget: budget.clone(),
+            ..Default::default()
+        };
+        let mut mutable =
+            MutableDatatype::new(DataType::Counter, DatatypeState::DueToCreate, &info);
+        mutable.set_rollback();
+        mutable
+            .execute_local_operation(Operation::new_counter_increase(2))
+            .unwrap();
+        mutable.end_transaction(&Default::default(), true);
+        // the pending transaction is checkpointed over the budget, but kept to be pushed.
+        assert!(mutable.rollback.transactions().is_empty());
+        assert_eq!(budget.used(), 0);
+        assert_eq!(mutable.pending_transactions().len(), 1);
+
+        let mut remote_op_id = OperationId::new_with_cuid(&Cuid::new());
+        let mut remote_tx = Transaction::new(&mut remote_op_id);
+        remote_tx.push_operation(Operation::new_counter_increase(3));
+        assert!(
+            mutable
+                .execute_remote_transaction(Arc::new(remote_tx))
+                .unwrap()
+        );
+        assert!(mutable.rollback.transactions().is_empty());
+        let Crdt::Counter(c) = &mutable.crdt else {
+            unreachable!()
+        };
+        assert_eq!(c.value(), 5);
+
+        // rolled back to the checkpoint with the remote transaction
+        mutable
+            .execute_local_operation(Operation::new_counter_increase(4))
+            .unwrap();
+        mutable.end_transaction(&Default::default(), false);
+        let Crdt::Counter(c) = &mutable.crdt else {
+            unreachable!()
+        };
+        assert_eq!(c.value(), 5);
+        assert_eq!(mutable.pending_transactions().len(), 1);
+
+        // the push queue is restored from a snapshot.
+        let snapshot = mutable.to_snapshot(DataType::Counter, &Default::default());
+        let mut restored =
+            MutableDatatype::new(DataType::Counter, DatatypeState::DueToCreate, &info);
+        restored.restore(snapshot).unwrap();
+        assert_eq!(
+            restored.pending_transactions(),
+            mutable.pending_transactions()
+        );
+        let Crdt::Counter(c) = &restored.crdt else {
+            unreachable!()
+        };
+        assert_eq!(c.value(), 5);
+
+        mutable.checkpoint.cseq = 1;
+        mutable.trim_rollback();
+        assert!(mutable.pending_transactions().is_empty());
+        assert!(mutable.push_queue.is_empty());
+        mutable.do_rollback();
+        let Crdt::Counter(c) = &mutable.crdt else {
+            unreachable!()
+        };
+        assert_eq!(c.value(), 5);
+    }
+
+    #[test]
+    fn can_keep_rollback_within_budget_without_server() {
+        let budget = Arc::new(RollbackBudget::new(usize::MAX, 1000));
+        let info = ClientInfo {
+            rollback_budget: budget.clone(),
+            ..Default::default()
+        };
+        let mut mutable1 =
+            MutableDatatype::new(DataType::Counter, DatatypeState::DueToCreate, &info);
+        let mut mutable2 =
+            MutableDatatype::new(DataType::Counter, DatatypeState::DueToCreate, &info);
+        for mutable in [&mut mutable1, &mut mutable2] {
+            mutable.set_rollback();
+            for _ in 0..100 {
+                mutable
+                    .execute_local_operation(Operation::new_counter_increase(1))
+                    .unwrap();
+                mutable.end_transaction(&Default::default(), true);
+                assert!(budget.used() <= 1000);
+            }
+        }
+        // the pending transactions are never acknowledged, but trimmed from the history.
+        assert!(mutable1.rollback.size() + mutable2.rollback.size() <= 1000);
+        assert_eq!(mutable1.pending_transactions().len(), 100);
+        assert_eq!(mutable2.pending_transactions().len(), 100);
+
+        mutable1
+            .execute_local_operation(Operation::new_counter_increase(1000))
+            .unwrap();
+        mutable1.end_transaction(&Default::default(), false);
+        let Crdt::Counter(c) = &mutable1.crdt else {
+            unreachable!()
+        };
+        assert_eq!(c.value(), 100);
+    }
+
+    #[test]
+    fn can_reject_restoring_mismatched_operations() {
+        let mut mutable = MutableDatatype::new(
+            DataType::Map,
+            DatatypeState::DueToCreate,
+            &Default::default(),
+        );
+        let cuid = Cuid::new();
+        let mut remote_op_id = OperationId::new_with_cuid(&cuid);
+        let mut remote_tx = Transaction::new(&mut remote_op_id);
+        remote_tx.push_operation(Operation::new_counter_increase(1));
+        let result = mutable.apply_record(DatatypeRecord::Transaction(Arc::new(remote_tx)));
+        assert!(matches!(
+            result,
+            Err(DatatypeError::FailedToExecuteOperation(_))
+        ));
+        assert!(!mutable.remote_cseqs.contains_key(&cuid));
+    }
+}
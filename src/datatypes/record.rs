@@ -0,0 +1,331 @@
+use std::sync::Arc;
+
+use crate::{
+    DataType, DatatypeError, DatatypeState,
+    operations::transaction::Transaction,
+    protocol::Checkpoint,
+    types::{
+        operation_id::OperationId,
+        uid::{Cuid, Duid},
+    },
+    utils::{
+        bytes::{BytesReader, BytesWriter},
+        codec,
+    },
+};
+
+const TAG_SNAPSHOT: u8 = 0;
+const TAG_TRANSACTION: u8 = 1;
+const TAG_CHECKPOINT: u8 = 2;
+const TAG_STATE: u8 = 3;
+
+/// A snapshot from which a datatype can be restored.
+///
+/// It keeps the rollback data, that is, the serialized CRDT and the transactions executed
+/// after it, so that the restored datatype can still roll back and push its pending
+/// transactions.
+#[derive(Debug)]
+pub struct DatatypeSnapshot {
+    pub r#type: DataType,
+    pub duid: Duid,
+    pub state: DatatypeState,
+    pub checkpoint: Checkpoint,
+    pub remote_cseqs: Vec<(Cuid, u64)>,
+    pub op_id: OperationId,
+    pub crdt: Box<[u8]>,
+    pub transactions: Vec<Arc<Transaction>>,
+    /// The pending transactions checkpointed in `crdt`, which are yet to be pushed.
+    pub push_queue: Vec<Arc<Transaction>>,
+}
+
+/// A snapshot exported from a datatype, from which another client can import it, e.g.,
+/// to seed a new environment.
+///
+/// Unlike [`DatatypeSnapshot`], it keeps only the current CRDT of the datatype, with the
+/// [`OperationId`] that the operations on the imported one should follow.
+#[derive(Debug)]
+pub struct ExportedSnapshot {
+    pub key: String,
+    pub r#type: DataType,
+    pub duid: Duid,
+    pub op_id: OperationId,
+    /// The last cseq of every client whose transactions are in `crdt`, including the
+    /// exporting one, so that the imported datatype skips them when pulled again.
+    pub remote_cseqs: Vec<(Cuid, u64)>,
+    pub crdt: Box<[u8]>,
+}
+
+impl ExportedSnapshot {
+    pub fn encode(&self) -> Vec<u8> {
+        codec::encode(codec::KIND_EXPORTED_SNAPSHOT, |w| {
+            w.write_str(&self.key);
+            self.r#type.write_to(w);
+            self.duid.write_to(w);
+            self.op_id.write_to(w);
+            write_remote_cseqs(w, &self.remote_cseqs);
+            w.write_bytes(&self.crdt);
+        })
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, DatatypeError> {
+        codec::decode(bytes, codec::KIND_EXPORTED_SNAPSHOT, |r| {
+            // without the cseqs, the transactions in the snapshot would be executed again.
+            if r.version() < codec::WIRE_VERSION_EXPORTED_CSEQS {
+                return Err(DatatypeError::FailedToDeserialize(format!(
+                    "exported snapshot of wire version {} has no remote cseqs",
+                    r.version()
+                )));
+            }
+            Ok(Self {
+                key: r.read_string()?,
+                r#type: DataType::read_from(r)?,
+                duid: Duid::read_from(r)?,
+                op_id: OperationId::read_from(r)?,
+                remote_cseqs: read_remote_cseqs(r)?,
+                crdt: r.read_bytes()?.into(),
+            })
+        })
+    }
+}
+
+/// A record in the storage log of a datatype.
+///
+/// The log of a datatype starts with a [`DatatypeRecord::Snapshot`], followed by the
+/// changes made after it in the order they are applied.
+#[derive(Debug)]
+pub enum DatatypeRecord {
+    Snapshot(Box<DatatypeSnapshot>),
+    Transaction(Arc<Transaction>),
+    Checkpoint(Checkpoint),
+    State(DatatypeState),
+}
+
+impl DatatypeRecord {
+    pub fn encode(&self) -> Vec<u8> {
+        codec::encode(codec::KIND_DATATYPE_RECORD, |w| self.write_to(w))
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, DatatypeError> {
+        codec::decode(bytes, codec::KIND_DATATYPE_RECORD, Self::read_from)
+    }
+
+    fn write_to(&self, writer: &mut BytesWriter) {
+        match self {
+            DatatypeRecord::Snapshot(snapshot) => {
+                writer.write_u8(TAG_SNAPSHOT);
+                snapshot.r#type.write_to(writer);
+                snapshot.duid.write_to(writer);
+                snapshot.state.write_to(writer);
+                write_checkpoint(writer, &snapshot.checkpoint);
+                write_remote_cseqs(writer, &snapshot.remote_cseqs);
+                snapshot.op_id.write_to(writer);
+                writer.write_bytes(&snapshot.crdt);
+                writer.write_u32(snapshot.transactions.len() as u32);
+                snapshot
+                    .transactions
+                    .iter()
+                    .for_each(|tx| tx.write_to(writer));
+                writer.write_u32(snapshot.push_queue.len() as u32);
+                snapshot
+                    .push_queue
+                    .iter()
+                    .for_each(|tx| tx.write_to(writer));
+            }
+            DatatypeRecord::Transaction(tx) => {
+                writer.write_u8(TAG_TRANSACTION);
+                tx.write_to(writer);
+            }
+            DatatypeRecord::Checkpoint(checkpoint) => {
+                writer.write_u8(TAG_CHECKPOINT);
+                write_checkpoint(writer, checkpoint);
+            }
+            DatatypeRecord::State(state) => {
+                writer.write_u8(TAG_STATE);
+                state.write_to(writer);
+            }
+        }
+    }
+
+    fn read_from(reader: &mut BytesReader) -> Result<Self, DatatypeError> {
+        match reader.read_u8()? {
+            TAG_SNAPSHOT => {
+                let r#type = DataType::read_from(reader)?;
+                let duid = Duid::read_from(reader)?;
+                let state = DatatypeState::read_from(reader)?;
+                let checkpoint = read_checkpoint(reader)?;
+                let remote_cseqs = read_remote_cseqs(reader)?;
+                let op_id = OperationId::read_from(reader)?;
+                let crdt = reader.read_bytes()?.into();
+                let len = reader.read_u32()?;
+                let transactions = (0..len)
+                    .map(|_| Transaction::read_from(reader).map(Arc::new))
+                    .collect::<Result<_, _>>()?;
+                let len = match reader.version() >= codec::WIRE_VERSION_PUSH_QUEUE {
+                    true => reader.read_u32()?,
+                    false => 0,
+                };
+                let push_queue = (0..len)
+                    .map(|_| Transaction::read_from(reader).map(Arc::new))
+                    .collect::<Result<_, _>>()?;
+                Ok(DatatypeRecord::Snapshot(Box::new(DatatypeSnapshot {
+                    r#type,
+                    duid,
+                    state,
+                    checkpoint,
+                    remote_cseqs,
+                    op_id,
+                    crdt,
+                    transactions,
+                    push_queue,
+                })))
+            }
+            TAG_TRANSACTION => Ok(DatatypeRecord::Transaction(Arc::new(
+                Transaction::read_from(reader)?,
+            ))),
+            TAG_CHECKPOINT => Ok(DatatypeRecord::Checkpoint(read_checkpoint(reader)?)),
+            TAG_STATE => Ok(DatatypeRecord::State(DatatypeState::read_from(reader)?)),
+            tag => Err(DatatypeError::FailedToDeserialize(format!(
+                "unknown record tag: {tag}"
+            ))),
+        }
+    }
+}
+
+fn write_checkpoint(writer: &mut BytesWriter, checkpoint: &Checkpoint) {
+    writer.write_u64(checkpoint.sseq);
+    writer.write_u64(checkpoint.cseq);
+}
+
+fn read_checkpoint(reader: &mut BytesReader) -> Result<Checkpoint, DatatypeError> {
+    Ok(Checkpoint::new(reader.read_u64()?, reader.read_u64()?))
+}
+
+fn write_remote_cseqs(writer: &mut BytesWriter, remote_cseqs: &[(Cuid, u64)]) {
+    writer.write_u32(remote_cseqs.len() as u32);
+    for (cuid, cseq) in remote_cseqs.iter() {
+        cuid.write_to(writer);
+        writer.write_u64(*cseq);
+    }
+}
+
+fn read_remote_cseqs(reader: &mut BytesReader) -> Result<Vec<(Cuid, u64)>, DatatypeError> {
+    let len = reader.read_u32()?;
+    (0..len)
+        .map(|_| Ok((Cuid::read_from(reader)?, reader.read_u64()?)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests_record {
+    use std::sync::Arc;
+
+    use crate::{
+        DataType, DatatypeError, DatatypeState,
+        datatypes::record::{DatatypeRecord, DatatypeSnapshot, ExportedSnapshot},
+        operations::{Operation, transaction::Transaction},
+        protocol::Checkpoint,
+        types::{
+            operation_id::OperationId,
+            uid::{Cuid, Duid},
+        },
+    };
+
+    #[test]
+    fn can_encode_and_decode_records() {
+        let mut op_id = OperationId::new_with_cuid(&Cuid::new());
+        let mut tx = Transaction::new(&mut op_id);
+        tx.push_operation(Operation::new_counter_increase(3));
+        let tx = Arc::new(tx);
+        let snapshot = DatatypeSnapshot {
+            r#type: DataType::List,
+            duid: Duid::new(),
+            state: DatatypeState::Subscribed,
+            checkpoint: Checkpoint::new(3, 4),
+            remote_cseqs: vec![(Cuid::new(), 5)],
+            op_id: op_id.clone(),
+            crdt: vec![1, 2, 3].into(),
+            transactions: vec![tx.clone()],
+            push_queue: vec![tx.clone()],
+        };
+
+        let bytes = DatatypeRecord::Snapshot(Box::new(snapshot)).encode();
+        let DatatypeRecord::Snapshot(decoded) = DatatypeRecord::decode(&bytes).unwrap() else {
+            unreachable!()
+        };
+        assert_eq!(decoded.r#type, DataType::List);
+        assert_eq!(decoded.state, DatatypeState::Subscribed);
+        assert_eq!(decoded.checkpoint, Checkpoint::new(3, 4));
+        assert_eq!(decoded.remote_cseqs[0].1, 5);
+        assert_eq!(decoded.op_id, op_id);
+        assert_eq!(decoded.crdt.as_ref(), &[1, 2, 3]);
+        assert_eq!(decoded.transactions, vec![tx.clone()]);
+        assert_eq!(decoded.push_queue, vec![tx.clone()]);
+
+        // a snapshot of wire version 1 has no push queue.
+        let snapshot = DatatypeSnapshot {
+            r#type: DataType::Counter,
+            duid: Duid::new(),
+            state: DatatypeState::Subscribed,
+            checkpoint: Checkpoint::new(3, 4),
+            remote_cseqs: vec![],
+            op_id: op_id.clone(),
+            crdt: vec![1, 2, 3].into(),
+            transactions: vec![],
+            push_queue: vec![],
+        };
+        let mut bytes = DatatypeRecord::Snapshot(Box::new(snapshot)).encode();
+        bytes.truncate(bytes.len() - 4);
+        bytes[2] = 1;
+        let DatatypeRecord::Snapshot(decoded) = DatatypeRecord::decode(&bytes).unwrap() else {
+            unreachable!()
+        };
+        assert_eq!(decoded.crdt.as_ref(), &[1, 2, 3]);
+        assert!(decoded.push_queue.is_empty());
+
+        let bytes = DatatypeRecord::Transaction(tx.clone()).encode();
+        let DatatypeRecord::Transaction(decoded) = DatatypeRecord::decode(&bytes).unwrap() else {
+            unreachable!()
+        };
+        assert_eq!(decoded, tx);
+
+        let bytes = DatatypeRecord::State(DatatypeState::Closed).encode();
+        assert_eq!(
+            DatatypeRecord::decode(&bytes[..bytes.len() - 1]).unwrap_err(),
+            DatatypeError::FailedToDeserialize("".into())
+        );
+        let mut bytes = DatatypeRecord::Checkpoint(Checkpoint::new(1, 2)).encode();
+        bytes[4] = 99;
+        assert!(DatatypeRecord::decode(&bytes).is_err());
+    }
+
+    #[test]
+    fn can_encode_and_decode_exported_snapshots() {
+        let snapshot = ExportedSnapshot {
+            key: "k1".to_owned(),
+            r#type: DataType::Text,
+            duid: Duid::new(),
+            op_id: OperationId::new_with_cuid(&Cuid::new()),
+            remote_cseqs: vec![(Cuid::new(), 5)],
+            crdt: vec![1, 2, 3].into(),
+        };
+        let mut bytes = snapshot.encode();
+        let decoded = ExportedSnapshot::decode(&bytes).unwrap();
+        assert_eq!(decoded.key, "k1");
+        assert_eq!(decoded.r#type, DataType::Text);
+        assert_eq!(decoded.duid, snapshot.duid);
+        assert_eq!(decoded.op_id, snapshot.op_id);
+        assert_eq!(decoded.remote_cseqs, snapshot.remote_cseqs);
+        assert_eq!(decoded.crdt.as_ref(), &[1, 2, 3]);
+
+        // a snapshot exported before wire version 3 does not tell what it includes.
+        bytes[2] = 2;
+        assert!(ExportedSnapshot::decode(&bytes).is_err());
+
+        // a record is not an exported snapshot
+        let bytes = DatatypeRecord::State(DatatypeState::Closed).encode();
+        assert_eq!(
+            ExportedSnapshot::decode(&bytes).unwrap_err(),
+            DatatypeError::FailedToDeserialize("".into())
+        );
+    }
+}
@@ -1,27 +1,153 @@
-use std::{collections::VecDeque, fmt::Debug, sync::Arc};
+use std::{
+    collections::VecDeque,
+    fmt::Debug,
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+};
 
 use crate::{
-    DatatypeState, operations::transaction::Transaction, types::operation_id::OperationId,
+    DatatypeState,
+    operations::{MemoryMeasurable, transaction::Transaction},
+    types::operation_id::OperationId,
 };
 
+/// The default memory budget for the rollback history of each datatype, in bytes.
+pub const DEFAULT_DATATYPE_ROLLBACK_BUDGET: usize = 1 << 20;
+/// The default memory budget for the rollback histories of all datatypes of a client.
+pub const DEFAULT_CLIENT_ROLLBACK_BUDGET: usize = 64 << 20;
+
+/// The memory budget for the rollback histories, measured by the sizes of their
+/// transactions.
+///
+/// The histories of the datatypes of a client share a budget, so that the usage of the
+/// client is accounted across them. When a budget is exceeded, each datatype checkpoints
+/// its whole history the next time it commits, executes, or synchronizes a transaction;
+/// the histories of the other datatypes are thus trimmed only as they are used.
+#[derive(Debug)]
+pub struct RollbackBudget {
+    per_datatype: usize,
+    per_client: usize,
+    used: AtomicUsize,
+}
+
+impl RollbackBudget {
+    pub fn new(per_datatype: usize, per_client: usize) -> Self {
+        Self {
+            per_datatype,
+            per_client,
+            used: AtomicUsize::new(0),
+        }
+    }
+
+    /// Returns the memory used by the histories of all datatypes sharing this budget.
+    pub fn used(&self) -> usize {
+        self.used.load(Ordering::Relaxed)
+    }
+
+    fn is_exceeded(&self, datatype_size: usize) -> bool {
+        datatype_size > self.per_datatype || self.used() > self.per_client
+    }
+}
+
+impl Default for RollbackBudget {
+    fn default() -> Self {
+        Self::new(
+            DEFAULT_DATATYPE_ROLLBACK_BUDGET,
+            DEFAULT_CLIENT_ROLLBACK_BUDGET,
+        )
+    }
+}
+
+/// The data from which a datatype is rolled back: the serialized CRDT checkpointed
+/// with its operation id and state, and the transactions executed after it.
 #[derive(Default)]
 pub struct RollbackData {
     pub crdt: Box<[u8]>,
     pub op_id: OperationId,
     pub state: DatatypeState,
-    pub transactions: VecDeque<Arc<Transaction>>,
+    transactions: VecDeque<Arc<Transaction>>,
+    size: usize,
+    budget: Arc<RollbackBudget>,
 }
 
 impl RollbackData {
+    pub fn new(budget: Arc<RollbackBudget>) -> Self {
+        Self {
+            crdt: Default::default(),
+            op_id: Default::default(),
+            state: Default::default(),
+            transactions: Default::default(),
+            size: 0,
+            budget,
+        }
+    }
+
+    pub fn transactions(&self) -> &VecDeque<Arc<Transaction>> {
+        &self.transactions
+    }
+
+    /// Returns the memory used by the transactions in the history.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Returns whether the history exceeds the budget of the datatype or of the client.
+    pub fn is_over_budget(&self) -> bool {
+        self.budget.is_exceeded(self.size)
+    }
+
     pub fn push_transaction(&mut self, tx: Arc<Transaction>) {
+        self.add_size(tx.size());
         self.transactions.push_back(tx);
     }
 
+    pub fn insert_transaction(&mut self, index: usize, tx: Arc<Transaction>) {
+        self.add_size(tx.size());
+        self.transactions.insert(index, tx);
+    }
+
+    pub fn set_transactions(&mut self, transactions: Vec<Arc<Transaction>>) {
+        self.clear_transactions();
+        transactions
+            .into_iter()
+            .for_each(|tx| self.push_transaction(tx));
+    }
+
+    /// Removes and returns the first `n` transactions of the history.
+    pub fn drain_transactions(&mut self, n: usize) -> Vec<Arc<Transaction>> {
+        let drained: Vec<_> = self.transactions.drain(..n).collect();
+        self.sub_size(drained.iter().map(|tx| tx.size()).sum());
+        drained
+    }
+
     pub fn set(&mut self, op_id: &OperationId, crdt: Box<[u8]>, state: DatatypeState) {
         self.op_id = op_id.clone();
         self.state = state;
         self.crdt = crdt;
+        self.clear_transactions();
+    }
+
+    fn clear_transactions(&mut self) {
         self.transactions.clear();
+        self.sub_size(self.size);
+    }
+
+    fn add_size(&mut self, size: usize) {
+        self.size += size;
+        self.budget.used.fetch_add(size, Ordering::Relaxed);
+    }
+
+    fn sub_size(&mut self, size: usize) {
+        self.size -= size;
+        self.budget.used.fetch_sub(size, Ordering::Relaxed);
+    }
+}
+
+impl Drop for RollbackData {
+    fn drop(&mut self) {
+        self.sub_size(self.size);
     }
 }
 
@@ -29,6 +155,7 @@ impl Debug for RollbackData {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_map()
             .entry(&"tx len", &self.transactions.len())
+            .entry(&"tx size", &self.size)
             .entry(&"crdt size", &self.crdt.len())
             .finish()
     }
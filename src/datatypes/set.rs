@@ -0,0 +1,284 @@
+use std::{error::Error, sync::Arc, time::Duration};
+
+use crate::{
+    DataType, DatatypeError, DatatypeState, IntoString,
+    clients::client::ClientInfo,
+    datatypes::{
+        common::ReturnType,
+        crdts::{Crdt, set_crdt::SetCrdt},
+        datatype::DatatypeBlanket,
+        datatype_instrument,
+        transactional::{TransactionContext, TransactionalDatatype},
+    },
+    operations::Operation,
+    types::set_element::SetElement,
+};
+
+/// A set is a conflict-free collection of unique strings or bytes.
+///
+/// It is implemented as an observed-remove set (OR-Set), so that an add concurrent
+/// with a remove of the same element wins over the remove on every client.
+#[derive(Clone)]
+pub struct Set {
+    datatype: Arc<TransactionalDatatype>,
+    tx_ctx: Arc<TransactionContext>,
+}
+
+impl Set {
+    pub(crate) fn new(key: String, state: DatatypeState, client_info: Arc<ClientInfo>) -> Self {
+        Set {
+            datatype: Arc::new(TransactionalDatatype::new(
+                &key,
+                DataType::Set,
+                state,
+                client_info,
+            )),
+            tx_ctx: Default::default(),
+        }
+    }
+
+    pub(crate) fn from_core(datatype: Arc<TransactionalDatatype>) -> Self {
+        Set {
+            datatype,
+            tx_ctx: Default::default(),
+        }
+    }
+
+    /// Returns a handle of this set operating in the transaction of `tx_ctx`.
+    pub(crate) fn with_tx_ctx(&self, tx_ctx: Arc<TransactionContext>) -> Self {
+        Set {
+            datatype: self.datatype.clone(),
+            tx_ctx,
+        }
+    }
+
+    fn execute(&self, op: Operation) -> bool {
+        matches!(
+            self.datatype
+                .execute_local_operation_as_tx(self.tx_ctx.clone(), op),
+            Ok(ReturnType::Set(true))
+        )
+    }
+
+    fn read<R>(&self, f: impl FnOnce(&SetCrdt) -> R) -> R {
+        let mutable = self.datatype.mutable.read();
+        let Crdt::Set(s) = &mutable.crdt else {
+            unreachable!()
+        };
+        f(s)
+    }
+
+    datatype_instrument! {
+    /// Adds `element` to the set.
+    ///
+    /// # Returns
+    ///
+    /// True if the set did not contain `element`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use syncyam::Client;
+    /// let client = Client::builder("test-collection", "test-client").build().unwrap();
+    /// let set = client.create_set("test-set").unwrap();
+    /// assert!(set.add("yam"));
+    /// assert!(!set.add("yam"));
+    /// assert!(set.add(vec![1u8, 2]));
+    /// assert_eq!(set.len(), 2);
+    /// ```
+    pub fn add(&self, element: impl Into<SetElement>) -> bool {
+        self.execute(Operation::new_set_add(element.into()))
+    }}
+
+    datatype_instrument! {
+    /// Removes `element` from the set.
+    ///
+    /// # Returns
+    ///
+    /// True if the set contained `element`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use syncyam::Client;
+    /// let client = Client::builder("test-collection", "test-client").build().unwrap();
+    /// let set = client.create_set("test-set").unwrap();
+    /// set.add("yam");
+    /// assert!(set.remove("yam"));
+    /// assert!(!set.remove("yam"));
+    /// assert!(set.is_empty());
+    /// ```
+    pub fn remove(&self, element: impl Into<SetElement>) -> bool {
+        self.execute(Operation::new_set_remove(element.into()))
+    }}
+
+    /// Returns true if the set contains `element`.
+    pub fn contains(&self, element: impl Into<SetElement>) -> bool {
+        let element = element.into();
+        self.read(|s| s.contains(&element))
+    }
+
+    /// Returns the number of elements in the set.
+    pub fn len(&self) -> usize {
+        self.read(|s| s.len())
+    }
+
+    /// Returns true if the set has no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns a snapshot of the elements in ascending order.
+    pub fn to_vec(&self) -> Vec<SetElement> {
+        self.read(|s| s.iter().cloned().collect())
+    }
+
+    /// Returns an iterator over a snapshot of the elements in ascending order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use syncyam::{Client, SetElement};
+    /// let client = Client::builder("test-collection", "test-client").build().unwrap();
+    /// let set = client.create_set("test-set").unwrap();
+    /// set.add("b");
+    /// set.add("a");
+    /// assert_eq!(set.iter().collect::<Vec<_>>(), vec!["a".into(), "b".into()]);
+    /// ```
+    pub fn iter(&self) -> std::vec::IntoIter<SetElement> {
+        self.to_vec().into_iter()
+    }
+
+    /// Returns a handle of this set whose operations and transactions wait at most
+    /// `timeout` for another transaction of the set, overriding the lock timeout of
+    /// the client.
+    ///
+    /// Once the timeout elapses, they fail with [`DatatypeError::LockTimeout`].
+    pub fn with_lock_timeout(&self, timeout: Duration) -> Self {
+        let tx_ctx = TransactionContext::with_lock_timeout(self.tx_ctx.clone(), timeout);
+        self.with_tx_ctx(Arc::new(tx_ctx))
+    }
+
+    /// Returns a handle of this set whose transactions are marked as events, which
+    /// other clients receive by [`Datatype::on_remote_event`](crate::Datatype::on_remote_event).
+    pub fn as_event(&self) -> Self {
+        self.with_tx_ctx(Arc::new(TransactionContext::for_event(self.tx_ctx.clone())))
+    }
+
+    datatype_instrument! {
+    /// Executes multiple operations atomically within a transaction.
+    ///
+    /// If the transaction function returns an error or panics, all operations within
+    /// the transaction are rolled back, leaving the set unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use syncyam::Client;
+    /// let client = Client::builder("test-collection", "test-client").build().unwrap();
+    /// let set = client.create_set("test-set").unwrap();
+    /// let result: Result<(), _> = set.transaction("failing-update", |s| {
+    ///     s.add("yam");
+    ///     Err("something went wrong".into())
+    /// });
+    /// assert!(result.is_err());
+    /// assert!(set.is_empty()); // unchanged
+    /// ```
+    pub fn transaction<T, R>(
+        &self,
+        tag: impl IntoString,
+        tx_func: T,
+    ) -> Result<R, DatatypeError>
+    where
+        T: FnOnce(Self) -> Result<R, Box<dyn Error + Send + Sync>> + Send + Sync + 'static,
+    {
+        let this_tx_ctx = Arc::new(TransactionContext::new_within(tag, self.tx_ctx.clone()));
+        let this_tx_ctx_clone = this_tx_ctx.clone();
+        let do_tx_func = move || {
+            let mut set_clone = self.clone();
+            set_clone.tx_ctx = this_tx_ctx_clone.clone();
+            match tx_func(set_clone) {
+                Ok(r) => Ok(r),
+                Err(e) => Err(DatatypeError::FailedTransaction(e.to_string())),
+            }
+        };
+        self.datatype
+            .do_transaction(this_tx_ctx, do_tx_func)
+    }}
+
+    datatype_instrument! {
+    /// Executes multiple operations atomically within a transaction like
+    /// [`Set::transaction`], but yields to the executor instead of blocking the thread
+    /// while another transaction of this set is running.
+    pub async fn transaction_async<T, R>(
+        &self,
+        tag: impl IntoString,
+        tx_func: T,
+    ) -> Result<R, DatatypeError>
+    where
+        T: FnOnce(Self) -> Result<R, Box<dyn Error + Send + Sync>> + Send + Sync + 'static,
+    {
+        let this_tx_ctx = Arc::new(TransactionContext::new_within(tag, self.tx_ctx.clone()));
+        let this_tx_ctx_clone = this_tx_ctx.clone();
+        let do_tx_func = move || {
+            let mut set_clone = self.clone();
+            set_clone.tx_ctx = this_tx_ctx_clone.clone();
+            match tx_func(set_clone) {
+                Ok(r) => Ok(r),
+                Err(e) => Err(DatatypeError::FailedTransaction(e.to_string())),
+            }
+        };
+        self.datatype
+            .do_transaction_async(this_tx_ctx, do_tx_func)
+            .await
+    }}
+}
+
+impl DatatypeBlanket for Set {
+    fn get_core(&self) -> &TransactionalDatatype {
+        self.datatype.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests_set {
+    use tracing::instrument;
+
+    use crate::{
+        DataType,
+        datatypes::{datatype::Datatype, set::Set},
+    };
+
+    #[test]
+    fn can_assert_send_and_sync_traits() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Set>();
+    }
+
+    #[test]
+    #[instrument]
+    fn can_use_transaction() {
+        let set = Set::new(
+            module_path!().to_owned(),
+            Default::default(),
+            Default::default(),
+        );
+        assert_eq!(set.get_type(), DataType::Set);
+        let result1 = set.transaction("success", |s| {
+            s.add("a");
+            s.add(vec![0u8]);
+            Ok(())
+        });
+        assert!(result1.is_ok());
+        assert_eq!(set.len(), 2);
+
+        let result2: Result<(), _> = set.transaction("failure", |s| {
+            s.remove("a");
+            s.add("c");
+            Err("failed".into())
+        });
+        assert!(result2.is_err());
+        assert!(set.contains("a"));
+        assert!(!set.contains("c"));
+    }
+}
@@ -0,0 +1,393 @@
+use std::{
+    error::Error,
+    fmt::{Display, Formatter},
+    sync::Arc,
+    time::Duration,
+};
+
+use crate::{
+    DataType, DatatypeError, DatatypeState, IntoString,
+    clients::client::ClientInfo,
+    datatypes::{
+        common::ReturnType,
+        crdts::{Crdt, text_crdt::TextCrdt},
+        datatype::DatatypeBlanket,
+        datatype_instrument,
+        transactional::{TransactionContext, TransactionalDatatype},
+    },
+    operations::Operation,
+    types::element_id::ElementId,
+};
+
+/// A text is a conflict-free sequence of characters for collaborative editing.
+///
+/// Positions and lengths count Unicode scalar values, i.e., `char`s, rather than bytes.
+/// Concurrent edits converge like [`crate::List`], while the characters inserted
+/// together are stored as a run rather than one by one.
+#[derive(Clone)]
+pub struct Text {
+    datatype: Arc<TransactionalDatatype>,
+    tx_ctx: Arc<TransactionContext>,
+}
+
+/// A position in a [`Text`] that follows the edits before it, including remote ones.
+///
+/// It is anchored to the character before the position, so that it stays right after
+/// that character; if the character is deleted, it moves to where the character was.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TextCursor {
+    anchor: Option<ElementId>,
+}
+
+impl Text {
+    pub(crate) fn new(key: String, state: DatatypeState, client_info: Arc<ClientInfo>) -> Self {
+        Text {
+            datatype: Arc::new(TransactionalDatatype::new(
+                &key,
+                DataType::Text,
+                state,
+                client_info,
+            )),
+            tx_ctx: Default::default(),
+        }
+    }
+
+    pub(crate) fn from_core(datatype: Arc<TransactionalDatatype>) -> Self {
+        Text {
+            datatype,
+            tx_ctx: Default::default(),
+        }
+    }
+
+    /// Returns a handle of this text operating in the transaction of `tx_ctx`.
+    pub(crate) fn with_tx_ctx(&self, tx_ctx: Arc<TransactionContext>) -> Self {
+        Text {
+            datatype: self.datatype.clone(),
+            tx_ctx,
+        }
+    }
+
+    fn execute(&self, op: Operation) -> Result<String, DatatypeError> {
+        match self
+            .datatype
+            .execute_local_operation_as_tx(self.tx_ctx.clone(), op)?
+        {
+            ReturnType::Text(deleted) => Ok(deleted),
+            _ => Ok(String::new()),
+        }
+    }
+
+    fn read<R>(&self, f: impl FnOnce(&TextCrdt) -> R) -> R {
+        let mutable = self.datatype.mutable.read();
+        let Crdt::Text(t) = &mutable.crdt else {
+            unreachable!()
+        };
+        f(t)
+    }
+
+    datatype_instrument! {
+    /// Inserts `text` at `pos`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DatatypeError::FailedToExecuteOperation`] if `pos` is greater than the length.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use syncyam::Client;
+    /// let client = Client::builder("test-collection", "test-client").build().unwrap();
+    /// let text = client.create_text("test-text").unwrap();
+    /// text.insert(0, "hello").unwrap();
+    /// text.insert(5, " world").unwrap();
+    /// assert_eq!(text.to_string(), "hello world");
+    /// assert!(text.insert(12, "!").is_err());
+    /// ```
+    pub fn insert(&self, pos: usize, text: &str) -> Result<(), DatatypeError> {
+        self.execute(Operation::new_text_edit(pos, 0, text.to_owned()))
+            .map(|_| ())
+    }}
+
+    datatype_instrument! {
+    /// Deletes `len` characters from `pos`.
+    ///
+    /// # Returns
+    ///
+    /// The deleted text
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DatatypeError::FailedToExecuteOperation`] if the range is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use syncyam::Client;
+    /// let client = Client::builder("test-collection", "test-client").build().unwrap();
+    /// let text = client.create_text("test-text").unwrap();
+    /// text.insert(0, "안녕, world").unwrap();
+    /// assert_eq!(text.delete(2, 2).unwrap(), ", ");
+    /// assert_eq!(text.to_string(), "안녕world");
+    /// ```
+    pub fn delete(&self, pos: usize, len: usize) -> Result<String, DatatypeError> {
+        self.execute(Operation::new_text_edit(pos, len, String::new()))
+    }}
+
+    datatype_instrument! {
+    /// Replaces `len` characters from `pos` with `text` as a single operation.
+    ///
+    /// # Returns
+    ///
+    /// The replaced text
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DatatypeError::FailedToExecuteOperation`] if the range is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use syncyam::Client;
+    /// let client = Client::builder("test-collection", "test-client").build().unwrap();
+    /// let text = client.create_text("test-text").unwrap();
+    /// text.insert(0, "hello world").unwrap();
+    /// assert_eq!(text.replace(6, 5, "yam").unwrap(), "world");
+    /// assert_eq!(text.to_string(), "hello yam");
+    /// ```
+    pub fn replace(&self, pos: usize, len: usize, text: &str) -> Result<String, DatatypeError> {
+        self.execute(Operation::new_text_edit(pos, len, text.to_owned()))
+    }}
+
+    /// Returns the number of characters.
+    pub fn len(&self) -> usize {
+        self.read(|t| t.len())
+    }
+
+    /// Returns true if the text has no characters.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns a cursor at `pos`, which is clamped to the length.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use syncyam::Client;
+    /// let client = Client::builder("test-collection", "test-client").build().unwrap();
+    /// let text = client.create_text("test-text").unwrap();
+    /// text.insert(0, "world").unwrap();
+    /// let cursor = text.cursor(5);
+    /// text.insert(0, "hello ").unwrap();
+    /// assert_eq!(text.cursor_position(&cursor), Some(11));
+    /// ```
+    pub fn cursor(&self, pos: usize) -> TextCursor {
+        TextCursor {
+            anchor: self.read(|t| t.anchor_of_pos(pos)),
+        }
+    }
+
+    /// Returns the current position of `cursor`, or `None` if it is not from this text.
+    pub fn cursor_position(&self, cursor: &TextCursor) -> Option<usize> {
+        self.read(|t| t.pos_of_anchor(cursor.anchor.as_ref()))
+    }
+
+    /// Returns a handle of this text whose operations and transactions wait at most
+    /// `timeout` for another transaction of the text, overriding the lock timeout of
+    /// the client.
+    ///
+    /// Once the timeout elapses, they fail with [`DatatypeError::LockTimeout`].
+    pub fn with_lock_timeout(&self, timeout: Duration) -> Self {
+        let tx_ctx = TransactionContext::with_lock_timeout(self.tx_ctx.clone(), timeout);
+        self.with_tx_ctx(Arc::new(tx_ctx))
+    }
+
+    /// Returns a handle of this text whose transactions are marked as events, which
+    /// other clients receive by [`Datatype::on_remote_event`](crate::Datatype::on_remote_event).
+    pub fn as_event(&self) -> Self {
+        self.with_tx_ctx(Arc::new(TransactionContext::for_event(self.tx_ctx.clone())))
+    }
+
+    datatype_instrument! {
+    /// Executes multiple operations atomically within a transaction.
+    ///
+    /// If the transaction function returns an error or panics, all operations within
+    /// the transaction are rolled back, leaving the text unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use syncyam::Client;
+    /// let client = Client::builder("test-collection", "test-client").build().unwrap();
+    /// let text = client.create_text("test-text").unwrap();
+    /// let result = text.transaction("failing-update", |t| {
+    ///     t.insert(0, "hello")?;
+    ///     t.delete(3, 10)?; // out of bounds
+    ///     Ok(())
+    /// });
+    /// assert!(result.is_err());
+    /// assert!(text.is_empty()); // unchanged
+    /// ```
+    pub fn transaction<T, R>(
+        &self,
+        tag: impl IntoString,
+        tx_func: T,
+    ) -> Result<R, DatatypeError>
+    where
+        T: FnOnce(Self) -> Result<R, Box<dyn Error + Send + Sync>> + Send + Sync + 'static,
+    {
+        let this_tx_ctx = Arc::new(TransactionContext::new_within(tag, self.tx_ctx.clone()));
+        let this_tx_ctx_clone = this_tx_ctx.clone();
+        let do_tx_func = move || {
+            let mut text_clone = self.clone();
+            text_clone.tx_ctx = this_tx_ctx_clone.clone();
+            match tx_func(text_clone) {
+                Ok(r) => Ok(r),
+                Err(e) => Err(DatatypeError::FailedTransaction(e.to_string())),
+            }
+        };
+        self.datatype
+            .do_transaction(this_tx_ctx, do_tx_func)
+    }}
+
+    datatype_instrument! {
+    /// Executes multiple operations atomically within a transaction like
+    /// [`Text::transaction`], but yields to the executor instead of blocking the thread
+    /// while another transaction of this text is running.
+    pub async fn transaction_async<T, R>(
+        &self,
+        tag: impl IntoString,
+        tx_func: T,
+    ) -> Result<R, DatatypeError>
+    where
+        T: FnOnce(Self) -> Result<R, Box<dyn Error + Send + Sync>> + Send + Sync + 'static,
+    {
+        let this_tx_ctx = Arc::new(TransactionContext::new_within(tag, self.tx_ctx.clone()));
+        let this_tx_ctx_clone = this_tx_ctx.clone();
+        let do_tx_func = move || {
+            let mut text_clone = self.clone();
+            text_clone.tx_ctx = this_tx_ctx_clone.clone();
+            match tx_func(text_clone) {
+                Ok(r) => Ok(r),
+                Err(e) => Err(DatatypeError::FailedTransaction(e.to_string())),
+            }
+        };
+        self.datatype
+            .do_transaction_async(this_tx_ctx, do_tx_func)
+            .await
+    }}
+}
+
+impl Display for Text {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.read(|t| t.fmt(f))
+    }
+}
+
+impl DatatypeBlanket for Text {
+    fn get_core(&self) -> &TransactionalDatatype {
+        self.datatype.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests_text {
+    use std::sync::Arc;
+
+    use tracing::instrument;
+
+    use crate::{
+        DataType,
+        clients::client::ClientInfo,
+        datatypes::{datatype::Datatype, text::Text},
+        types::uid::Cuid,
+    };
+
+    fn new_text_with_cuid() -> Text {
+        let client_info = Arc::new(ClientInfo {
+            cuid: Cuid::new(),
+            ..Default::default()
+        });
+        Text::new(module_path!().to_owned(), Default::default(), client_info)
+    }
+
+    fn sync(from: &Text, to: &Text) {
+        let txs = from.datatype.mutable.read().rollback.transactions().clone();
+        for tx in txs {
+            to.datatype.execute_remote_transaction(tx).unwrap();
+        }
+    }
+
+    #[test]
+    fn can_assert_send_and_sync_traits() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Text>();
+    }
+
+    #[test]
+    #[instrument]
+    fn can_edit_text_and_roll_back() {
+        let text = Text::new(
+            module_path!().to_owned(),
+            Default::default(),
+            Default::default(),
+        );
+        assert_eq!(text.get_type(), DataType::Text);
+        text.insert(0, "hello world").unwrap();
+        let cursor = text.cursor(6);
+        let result = text.transaction("success", |t| {
+            t.replace(0, 5, "bye")?;
+            t.insert(3, ",")?;
+            Ok(())
+        });
+        assert!(result.is_ok());
+        assert_eq!(text.to_string(), "bye, world");
+        assert_eq!(text.cursor_position(&cursor), Some(5));
+
+        let result = text.transaction("failure", |t| {
+            t.delete(0, 5)?;
+            t.insert(10, "!")?;
+            Ok(())
+        });
+        assert!(result.is_err());
+        assert_eq!(text.to_string(), "bye, world");
+        assert_eq!(text.len(), 10);
+    }
+
+    #[test]
+    #[instrument]
+    fn can_undo_and_redo_over_remote_edits() {
+        let text1 = new_text_with_cuid();
+        let text2 = new_text_with_cuid();
+        text1.insert(0, "hello").unwrap();
+        sync(&text1, &text2);
+        for (pos, s) in [(5, " yam"), (9, "!")] {
+            text1
+                .transaction("typing", move |t| Ok(t.insert(pos, s)?))
+                .unwrap();
+        }
+        text2.insert(0, ">> ").unwrap();
+        sync(&text2, &text1);
+        assert_eq!(text1.to_string(), ">> hello yam!");
+
+        // the transactions with the same tag are undone together
+        assert!(text1.undo());
+        assert_eq!(text1.to_string(), ">> hello");
+        assert!(text1.redo());
+        assert_eq!(text1.to_string(), ">> hello yam!");
+        assert!(!text1.redo());
+
+        text1.replace(0, 9, "bye").unwrap();
+        assert_eq!(text1.to_string(), "byeyam!");
+        assert!(text1.undo());
+        assert_eq!(text1.to_string(), ">> hello yam!");
+        // the characters inserted again are deleted by undoing their insertion
+        assert!(text1.undo() && text1.undo());
+        assert_eq!(text1.to_string(), ">> ");
+        assert!(!text1.undo());
+
+        sync(&text1, &text2);
+        assert_eq!(text2.to_string(), text1.to_string());
+    }
+}
@@ -1,35 +1,118 @@
-use std::sync::Arc;
+use std::{
+    error::Error,
+    pin::pin,
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::{Duration, Instant},
+};
 
 use opentelemetry::KeyValue;
 use parking_lot::RwLock;
-use tracing::{info_span, instrument};
+use tokio::sync::Notify;
+use tracing::{Instrument, info_span, instrument, warn};
 use tracing_opentelemetry::OpenTelemetrySpanExt;
 
 use crate::{
     DataType, DatatypeState, IntoString,
     clients::client::ClientInfo,
-    datatypes::{common::ReturnType, datatype::Datatype, mutable::MutableDatatype},
-    errors::datatypes::DatatypeError,
-    operations::Operation,
-    types::uid::Duid,
-    utils::{defer_guard::DeferGuard, no_guard_mutex::NoGuardMutex},
+    datatypes::{
+        common::ReturnType,
+        datatype::Datatype,
+        handlers::{DatatypeEvent, EventDispatcher},
+        mutable::MutableDatatype,
+        record::{DatatypeRecord, ExportedSnapshot},
+    },
+    errors::{datatypes::DatatypeError, err},
+    operations::{Operation, transaction::Transaction},
+    protocol::PushPullPack,
+    types::uid::{Duid, Uid},
+    utils::{defer_guard::DeferGuard, no_guard_mutex::NoGuardMutex, unwind::catch_unwind},
 };
 
 #[derive(Debug, Default)]
 pub struct TransactionContext {
     tag: Option<String>,
+    group: Option<Uid>,
+    event: bool,
+    parent: Option<Arc<TransactionContext>>,
+    lock_timeout: Option<Duration>,
 }
 
 impl TransactionContext {
     pub fn new(tag: impl IntoString) -> Self {
         Self {
             tag: Some(tag.into()),
+            ..Default::default()
+        }
+    }
+
+    /// Creates a context of a transaction shared by several datatypes, whose transactions
+    /// are committed with `group`.
+    pub fn new_in_group(tag: impl IntoString, group: Uid) -> Self {
+        Self {
+            tag: Some(tag.into()),
+            group: Some(group),
+            ..Default::default()
+        }
+    }
+
+    /// Creates a context of a transaction begun with the handle of `parent`, which is
+    /// nested in the transaction of `parent` if it is ongoing.
+    pub fn new_within(tag: impl IntoString, parent: Arc<TransactionContext>) -> Self {
+        Self {
+            tag: Some(tag.into()),
+            group: None,
+            event: parent.event,
+            lock_timeout: parent.lock_timeout,
+            parent: Some(parent),
+        }
+    }
+
+    /// Creates a context for the handle of `parent` to wait at most `timeout` for another
+    /// transaction, which is nested in the transaction of `parent` if it is ongoing.
+    pub fn with_lock_timeout(parent: Arc<TransactionContext>, timeout: Duration) -> Self {
+        Self {
+            tag: None,
+            group: None,
+            event: parent.event,
+            lock_timeout: Some(timeout),
+            parent: Some(parent),
+        }
+    }
+
+    /// Creates a context for the handle of `parent` to begin transactions marked as
+    /// events, which is nested in the transaction of `parent` if it is ongoing.
+    pub fn for_event(parent: Arc<TransactionContext>) -> Self {
+        Self {
+            tag: None,
+            group: None,
+            event: true,
+            lock_timeout: parent.lock_timeout,
+            parent: Some(parent),
         }
     }
 
+    /// Sets the tag, the group, and the event flag of this context to `tx`.
+    pub fn label(&self, tx: &mut Transaction) {
+        tx.set_tag(self.tag.clone());
+        tx.set_group(self.group.clone());
+        tx.set_event(self.event);
+    }
+
+    pub fn tag(&self) -> Option<&str> {
+        self.tag.as_deref()
+    }
+
     pub fn has_tag(&self) -> bool {
         self.tag.is_some()
     }
+
+    /// Returns whether this context is `other` or nested in it.
+    fn is_within(&self, other: &TransactionContext) -> bool {
+        self == other || self.parent.as_ref().is_some_and(|p| p.is_within(other))
+    }
 }
 
 impl PartialEq for TransactionContext {
@@ -76,12 +159,20 @@ pub struct Attributes {
     pub client_info: Arc<ClientInfo>,
 }
 
+/// The number of records appended to the storage log of a datatype before it is
+/// compacted into a snapshot.
+const SNAPSHOT_INTERVAL: usize = 100;
+
 pub struct TransactionalDatatype {
     pub attr: Attributes,
     pub mutable: RwLock<MutableDatatype>,
     tx_ctx: RwLock<Option<Arc<TransactionContext>>>,
     op_mutex: NoGuardMutex,
     tx_mutex: NoGuardMutex,
+    appended_records: AtomicUsize,
+    events: EventDispatcher,
+    tx_ended: Notify,
+    subscribed: Notify,
 }
 
 impl Datatype for TransactionalDatatype {
@@ -96,6 +187,63 @@ impl Datatype for TransactionalDatatype {
     fn get_state(&self) -> DatatypeState {
         self.mutable.read().state
     }
+
+    fn on_state_change(
+        &self,
+        handler: impl Fn(DatatypeState, DatatypeState) + Send + Sync + 'static,
+    ) {
+        self.events.add_state_change_handler(Arc::new(handler));
+    }
+
+    fn on_remote_operations(&self, handler: impl Fn(Vec<Operation>) + Send + Sync + 'static) {
+        self.events.add_remote_operations_handler(Arc::new(handler));
+    }
+
+    fn on_remote_event(&self, handler: impl Fn(Arc<Transaction>) + Send + Sync + 'static) {
+        self.events.add_remote_event_handler(Arc::new(handler));
+    }
+
+    fn on_error(&self, handler: impl Fn(DatatypeError) + Send + Sync + 'static) {
+        self.events.add_error_handler(Arc::new(handler));
+    }
+
+    fn before_commit(
+        &self,
+        hook: impl Fn(&Transaction) -> Result<(), Box<dyn Error + Send + Sync>> + Send + Sync + 'static,
+    ) {
+        self.events.add_before_commit_hook(Arc::new(hook));
+    }
+
+    fn after_commit(&self, handler: impl Fn(Arc<Transaction>) + Send + Sync + 'static) {
+        self.events.add_after_commit_handler(Arc::new(handler));
+    }
+
+    async fn wait_until_subscribed(&self) {
+        loop {
+            // enabled before checking the state, so that no notification is missed.
+            let mut notified = pin!(self.subscribed.notified());
+            notified.as_mut().enable();
+            if self.get_state() == DatatypeState::Subscribed {
+                return;
+            }
+            notified.await;
+        }
+    }
+
+    fn export_snapshot(&self) -> Vec<u8> {
+        self.mutable
+            .read()
+            .export(&self.attr.key, self.attr.r#type, &self.attr.duid)
+            .encode()
+    }
+
+    fn undo(&self) -> bool {
+        self.execute_undo(false)
+    }
+
+    fn redo(&self) -> bool {
+        self.execute_undo(true)
+    }
 }
 
 impl TransactionalDatatype {
@@ -105,23 +253,138 @@ impl TransactionalDatatype {
         state: DatatypeState,
         client_info: Arc<ClientInfo>,
     ) -> Self {
+        Self::new_with_duid(key, r#type, state, Duid::new(), client_info)
+    }
+
+    fn new_with_duid(
+        key: &str,
+        r#type: DataType,
+        state: DatatypeState,
+        duid: Duid,
+        client_info: Arc<ClientInfo>,
+    ) -> Self {
+        let mutable = MutableDatatype::new(r#type, state, &client_info);
         let attr = Attributes {
             key: key.to_owned(),
             r#type,
-            duid: Duid::new(),
+            duid,
             client_info,
         };
         let transactional = Self {
             attr,
-            mutable: RwLock::new(MutableDatatype::new(r#type, state)),
+            mutable: RwLock::new(mutable),
             tx_ctx: Default::default(),
             op_mutex: Default::default(),
             tx_mutex: Default::default(),
+            appended_records: Default::default(),
+            events: Default::default(),
+            tx_ended: Default::default(),
+            subscribed: Default::default(),
         };
         transactional.set_rollback_data();
         transactional
     }
 
+    /// Restores a datatype from the `records` loaded from the storage.
+    ///
+    /// The first record must be a snapshot, and the following records are applied in order.
+    pub fn restore(
+        key: &str,
+        records: Vec<DatatypeRecord>,
+        client_info: Arc<ClientInfo>,
+    ) -> Result<Self, DatatypeError> {
+        let mut records = records.into_iter();
+        let Some(DatatypeRecord::Snapshot(snapshot)) = records.next() else {
+            return Err(err!(
+                DatatypeError::FailedToDeserialize,
+                format!("no snapshot for '{key}'")
+            ));
+        };
+        let transactional = Self::new_with_duid(
+            key,
+            snapshot.r#type,
+            snapshot.state,
+            snapshot.duid.clone(),
+            client_info,
+        );
+        {
+            let mut mutable = transactional.mutable.write();
+            mutable.restore(*snapshot)?;
+            for record in records {
+                mutable.apply_record(record)?;
+            }
+        }
+        Ok(transactional)
+    }
+
+    /// Imports a datatype from a `snapshot` exported by another client.
+    ///
+    /// The imported datatype is [`DatatypeState::DueToSubscribeOrCreate`] with the
+    /// [`Duid`] of the exported one, and its operations follow the exported ones. It
+    /// starts pulling from the beginning, since the server may not be the one of the
+    /// exporting client, but skips the transactions already in the snapshot.
+    pub fn import(
+        key: &str,
+        snapshot: ExportedSnapshot,
+        client_info: Arc<ClientInfo>,
+    ) -> Result<Self, DatatypeError> {
+        let transactional = Self::new_with_duid(
+            key,
+            snapshot.r#type,
+            DatatypeState::DueToSubscribeOrCreate,
+            snapshot.duid.clone(),
+            client_info,
+        );
+        transactional.mutable.write().import(&snapshot)?;
+        transactional.set_rollback_data();
+        Ok(transactional)
+    }
+
+    /// Writes a snapshot of this datatype to the storage, replacing its log.
+    pub fn persist_snapshot(&self) {
+        self.persist_snapshot_of(&self.mutable.read());
+    }
+
+    fn persist_snapshot_of(&self, mutable: &MutableDatatype) {
+        let Some(storage) = &self.attr.client_info.storage else {
+            return;
+        };
+        let info = &self.attr.client_info;
+        let snapshot = mutable.to_snapshot(self.attr.r#type, &self.attr.duid);
+        let record = DatatypeRecord::Snapshot(Box::new(snapshot));
+        if let Err(e) = storage.compact(
+            &info.collection,
+            &info.cuid,
+            &self.attr.key,
+            &record.encode(),
+        ) {
+            warn!("failed to persist the snapshot of '{}': {e}", self.attr.key);
+            return;
+        }
+        self.appended_records.store(0, Ordering::Release);
+    }
+
+    /// Appends `record` to the storage log, or compacts the log into a snapshot
+    /// once [`SNAPSHOT_INTERVAL`] records have been appended.
+    fn persist(&self, mutable: &MutableDatatype, record: DatatypeRecord) {
+        let Some(storage) = &self.attr.client_info.storage else {
+            return;
+        };
+        if self.appended_records.fetch_add(1, Ordering::AcqRel) + 1 >= SNAPSHOT_INTERVAL {
+            self.persist_snapshot_of(mutable);
+            return;
+        }
+        let info = &self.attr.client_info;
+        if let Err(e) = storage.append(
+            &info.collection,
+            &info.cuid,
+            &self.attr.key,
+            &record.encode(),
+        ) {
+            warn!("failed to persist {record:?} of '{}': {e}", self.attr.key);
+        }
+    }
+
     fn set_rollback_data(&self) {
         let mut mutable = self.mutable.write();
         mutable.set_rollback();
@@ -133,29 +396,36 @@ impl TransactionalDatatype {
         op: Operation,
     ) -> Result<ReturnType, DatatypeError> {
         let mut _defer_guard = None;
+        let mut began = false;
         let begin_span = info_span!("begin_operation");
         let g_begin_span = begin_span.enter();
+        let started = Instant::now();
+        let deadline = self.lock_deadline(&tx_ctx, started);
         loop {
             match self.begin_transaction(tx_ctx.clone()) {
                 BeginTransactionResult::BeginTx(dg) => {
-                    begin_span.add_event("BeginTx", vec![]);
+                    begin_span.add_event("BeginTx", vec![waited(started)]);
                     drop(g_begin_span);
                     _defer_guard = Some(dg);
+                    began = true;
                     break;
                 }
                 BeginTransactionResult::SameCtx => {
                     // After the first call inside do_transaction, subsequent execute_local_operation_as_tx calls in tx_func run as SameCtx.
                     // If execute_local_operation_as_tx is invoked from another thread, the tx_mutex can ensure exclusive execution.
-                    begin_span.add_event("SameCtx", vec![]);
+                    begin_span.add_event("SameCtx", vec![waited(started)]);
                     drop(g_begin_span);
                     _defer_guard = Some(DeferGuard::new());
                     break;
                 }
                 BeginTransactionResult::OtherCtx => {
-                    begin_span.add_event("OtherCtx", vec![]);
+                    begin_span.add_event("OtherCtx", vec![waited(started)]);
                     // For OtherCtx, begin_transaction is repeatedly attempted until it transitions to BeginTx.
-                    // If an operation is already running, the tx_mutex is locked, so we wait until we can acquire the tx_mutex lock.
-                    self.wait_for_mutex();
+                    // If an operation is already running, the op_mutex is locked, so we wait until we can acquire the op_mutex lock.
+                    self.wait_for(&self.op_mutex, deadline, started)
+                        .inspect_err(|_| {
+                            begin_span.add_event("LockTimeout", vec![waited(started)])
+                        })?;
                 }
             }
         }
@@ -164,21 +434,39 @@ impl TransactionalDatatype {
         defer_guard.add_defer_func(move |_committed| {
             self.op_mutex.unlock();
         });
-        let mut mutable = self.mutable.write();
-        let ret = mutable.execute_local_operation(op)?;
+        let ret = self.mutable.write().execute_local_operation(op)?;
+        if began {
+            self.check_before_commit()?;
+        }
         defer_guard.commit();
         Ok(ret)
     }
 
+    /// Executes the inverse operations of the last step to undo, or to redo if `redo` is
+    /// true, as a transaction, and returns whether any of them is executed.
+    #[instrument(skip(self))]
+    fn execute_undo(&self, redo: bool) -> bool {
+        let tag = if redo { "redo" } else { "undo" };
+        self.do_transaction(Arc::new(TransactionContext::new(tag)), || {
+            Ok(self.mutable.write().execute_undo(redo))
+        })
+        .unwrap_or(false)
+    }
+
     #[instrument(skip_all)]
-    fn end_transaction(&self, tag: Option<String>, committed: bool) {
+    fn end_transaction(&self, tx_ctx: &TransactionContext, committed: bool) {
         let mut mutable = self.mutable.write();
-        mutable.end_transaction(tag, committed);
+        if let Some(tx) = mutable.end_transaction(tx_ctx, committed) {
+            self.persist(&mutable, DatatypeRecord::Transaction(tx.clone()));
+            self.events.dispatch(DatatypeEvent::Committed(tx));
+        }
+        drop(mutable);
         self.tx_ctx.write().take();
         self.tx_mutex.unlock();
+        self.tx_ended.notify_waiters();
     }
 
-    fn begin_transaction(&self, tx_ctx: Arc<TransactionContext>) -> BeginTransactionResult {
+    fn begin_transaction(&self, tx_ctx: Arc<TransactionContext>) -> BeginTransactionResult<'_> {
         let mut self_tx_ctx = self.tx_ctx.write();
         // self.tx_ctx defaults to None when no transaction is active.
         // Once a transaction begins, self.tx_ctx is set to the current transaction context.
@@ -191,18 +479,21 @@ impl TransactionalDatatype {
                     // If an operation is executed without a transaction, tx_ctx does not have a tag.
                     // In this case, a new TransactionContext is created,
                     // so BeginTransactionResult::SameCtx cannot be returned for the execution of the concurrent operations.
-                    Arc::new(TransactionContext::default())
+                    Arc::new(TransactionContext {
+                        event: tx_ctx.event,
+                        ..Default::default()
+                    })
                 }
             };
             *self_tx_ctx = Some(curr_tx_ctx.clone());
 
             let mut defer_guard = DeferGuard::new();
             defer_guard.add_defer_func(move |committed| {
-                self.end_transaction(curr_tx_ctx.tag.to_owned(), committed);
+                self.end_transaction(&curr_tx_ctx, committed);
             });
             BeginTransactionResult::BeginTx(defer_guard)
-        } else if self_tx_ctx.as_ref().unwrap() == &tx_ctx {
-            // When execute_local_op_as_tx is called within tx_func of do_transaction, SameCtx should be returned.
+        } else if tx_ctx.is_within(self_tx_ctx.as_ref().unwrap()) {
+            // When execute_local_op_as_tx or do_transaction is called within tx_func of do_transaction, SameCtx should be returned.
             // This means self.tx_ctx is not replaced by end_transaction.
             BeginTransactionResult::SameCtx
         } else {
@@ -212,69 +503,542 @@ impl TransactionalDatatype {
         }
     }
 
-    pub fn do_transaction<F>(
+    pub fn do_transaction<F, R>(
         &self,
         tx_ctx: Arc<TransactionContext>,
         tx_func: F,
-    ) -> Result<(), DatatypeError>
+    ) -> Result<R, DatatypeError>
     where
-        F: FnOnce() -> Result<(), DatatypeError>,
+        F: FnOnce() -> Result<R, DatatypeError>,
     {
         let begin_span = info_span!("begin_transaction");
         let g_begin_span = begin_span.enter();
+        let started = Instant::now();
+        let deadline = self.lock_deadline(&tx_ctx, started);
         let mut retries = 0;
         loop {
             match self.begin_transaction(tx_ctx.clone()) {
                 BeginTransactionResult::BeginTx(mut dg) => {
                     self.tx_mutex.lock();
-                    begin_span.add_event("BeginTx", vec![KeyValue::new("retries", retries)]);
+                    begin_span.add_event(
+                        "BeginTx",
+                        vec![KeyValue::new("retries", retries), waited(started)],
+                    );
                     drop(g_begin_span);
                     let tx_func_span = info_span!("tx_func");
-                    return tx_func_span.in_scope(|| tx_func().inspect(|_x| dg.commit()));
+                    return tx_func_span.in_scope(|| {
+                        let r = Self::call_tx_func(tx_func)?;
+                        self.check_before_commit()?;
+                        dg.commit();
+                        Ok(r)
+                    });
                 }
                 BeginTransactionResult::SameCtx => {
-                    // do_transaction should not / cannot be called with the same tx_ctx from different threads
-                    unreachable!(
-                        "do_transaction should not be called concurrently with same context"
-                    );
+                    // do_transaction is called within tx_func of the ongoing transaction,
+                    // since a context nested in it is never used from other threads.
+                    begin_span.add_event("SameCtx", vec![KeyValue::new("retries", retries)]);
+                    drop(g_begin_span);
+                    return self.do_nested_transaction(tx_func);
                 }
                 BeginTransactionResult::OtherCtx => {
                     retries += 1;
-                    begin_span.add_event("OtherCtx", vec![KeyValue::new("retries", retries)]);
+                    begin_span.add_event(
+                        "OtherCtx",
+                        vec![KeyValue::new("retries", retries), waited(started)],
+                    );
                     // This can occur when the current transaction cannot begin due to any other concurrent operation or transaction.
-                    self.wait_for_tx_mutex();
+                    self.wait_for(&self.tx_mutex, deadline, started)
+                        .inspect_err(|_| {
+                            begin_span.add_event("LockTimeout", vec![waited(started)])
+                        })?;
                 }
             }
         }
     }
 
-    #[inline]
-    fn wait_for_mutex(&self) {
-        self.op_mutex.lock();
-        self.op_mutex.unlock();
+    /// Begins a transaction of `tx_ctx` after the ongoing one ends, which is ended by
+    /// dropping the returned guard, and committed if the guard is committed.
+    ///
+    /// It is used to run a transaction over several datatypes, e.g., by
+    /// [`Client::transaction`](crate::Client::transaction).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DatatypeError::LockTimeout`] if the ongoing transaction does not end
+    /// within the lock timeout, or [`DatatypeError::FailedTransaction`] if `tx_ctx` is
+    /// within the ongoing transaction.
+    pub fn lock_transaction(
+        &self,
+        tx_ctx: Arc<TransactionContext>,
+    ) -> Result<DeferGuard<'_>, DatatypeError> {
+        let started = Instant::now();
+        let deadline = self.lock_deadline(&tx_ctx, started);
+        loop {
+            match self.begin_transaction(tx_ctx.clone()) {
+                BeginTransactionResult::BeginTx(dg) => {
+                    self.tx_mutex.lock();
+                    return Ok(dg);
+                }
+                BeginTransactionResult::SameCtx => {
+                    // the ongoing transaction cannot be locked again by one nested in it.
+                    return Err(err!(
+                        DatatypeError::FailedTransaction,
+                        format!("{tx_ctx:?} is within the ongoing transaction")
+                    ));
+                }
+                BeginTransactionResult::OtherCtx => {
+                    self.wait_for(&self.tx_mutex, deadline, started)?
+                }
+            }
+        }
     }
 
-    #[inline]
-    fn wait_for_tx_mutex(&self) {
-        self.tx_mutex.lock();
-        self.tx_mutex.unlock();
+    /// Calls the hooks registered by [`Datatype::before_commit`] with the ongoing
+    /// transaction labeled as it is to be committed.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DatatypeError::FailedTransaction`] if any of the hooks rejects it.
+    pub fn check_before_commit(&self) -> Result<(), DatatypeError> {
+        if !self.events.has_before_commit_hooks() {
+            return Ok(());
+        }
+        let Some(tx_ctx) = self.tx_ctx.read().clone() else {
+            return Ok(());
+        };
+        // cloned, so that the hooks can read the datatype.
+        let Some(mut tx) = self.mutable.read().transaction.clone() else {
+            return Ok(());
+        };
+        tx_ctx.label(&mut tx);
+        catch_unwind(|| self.events.before_commit(&tx))
+            .map_err(|msg| {
+                err!(
+                    DatatypeError::FailedTransaction,
+                    format!("panicked before commit: {msg}")
+                )
+            })?
+            .map_err(|e| {
+                err!(
+                    DatatypeError::FailedTransaction,
+                    format!("rejected before commit: {e}")
+                )
+            })
+    }
+
+    /// Calls `tx_func`, turning its panic into [`DatatypeError::FailedTransaction`], so
+    /// that the transaction is rolled back and ended as if `tx_func` failed.
+    fn call_tx_func<F, R>(tx_func: F) -> Result<R, DatatypeError>
+    where
+        F: FnOnce() -> Result<R, DatatypeError>,
+    {
+        catch_unwind(tx_func).unwrap_or_else(|msg| {
+            Err(err!(
+                DatatypeError::FailedTransaction,
+                format!("panicked in transaction: {msg}")
+            ))
+        })
+    }
+
+    /// Executes `tx_func` nested in the ongoing transaction, which is rolled back to the
+    /// savepoint at the beginning if `tx_func` fails.
+    ///
+    /// The operations of the nested transaction are committed with the ongoing one.
+    fn do_nested_transaction<F, R>(&self, tx_func: F) -> Result<R, DatatypeError>
+    where
+        F: FnOnce() -> Result<R, DatatypeError>,
+    {
+        let savepoint = self.mutable.read().savepoint();
+        let tx_func_span = info_span!("nested_tx_func");
+        tx_func_span.in_scope(|| {
+            Self::call_tx_func(tx_func).inspect_err(|_| self.mutable.write().rollback_to(savepoint))
+        })
     }
+
+    /// Executes `tx_func` as a transaction like [`Self::do_transaction`], but yields to
+    /// the executor instead of blocking the thread while another transaction is running.
+    pub async fn do_transaction_async<F, R>(
+        &self,
+        tx_ctx: Arc<TransactionContext>,
+        tx_func: F,
+    ) -> Result<R, DatatypeError>
+    where
+        F: FnOnce() -> Result<R, DatatypeError>,
+    {
+        let begin_span = info_span!("begin_transaction_async");
+        let started = Instant::now();
+        let deadline = self.lock_deadline(&tx_ctx, started);
+        let mut retries = 0;
+        loop {
+            // enabled before beginning, so that the end of the other transaction is not missed.
+            let mut tx_ended = pin!(self.tx_ended.notified());
+            tx_ended.as_mut().enable();
+            match self.begin_transaction(tx_ctx.clone()) {
+                BeginTransactionResult::BeginTx(mut dg) => {
+                    self.tx_mutex.lock();
+                    begin_span.add_event(
+                        "BeginTx",
+                        vec![KeyValue::new("retries", retries), waited(started)],
+                    );
+                    let tx_func_span = info_span!("tx_func");
+                    return tx_func_span.in_scope(|| {
+                        let r = Self::call_tx_func(tx_func)?;
+                        self.check_before_commit()?;
+                        dg.commit();
+                        Ok(r)
+                    });
+                }
+                BeginTransactionResult::SameCtx => {
+                    begin_span.add_event("SameCtx", vec![KeyValue::new("retries", retries)]);
+                    return self.do_nested_transaction(tx_func);
+                }
+                BeginTransactionResult::OtherCtx => {
+                    retries += 1;
+                    begin_span.add_event(
+                        "OtherCtx",
+                        vec![KeyValue::new("retries", retries), waited(started)],
+                    );
+                }
+            }
+            // awaited out of the match, since the DeferGuard of BeginTx is not Send.
+            let tx_ended = tx_ended.as_mut().instrument(begin_span.clone());
+            let ended = match deadline {
+                Some(deadline) => tokio::time::timeout_at(deadline.into(), tx_ended)
+                    .await
+                    .is_ok(),
+                None => {
+                    tx_ended.await;
+                    true
+                }
+            };
+            if !ended {
+                begin_span.add_event("LockTimeout", vec![waited(started)]);
+                return Err(self.lock_timeout_error(started));
+            }
+        }
+    }
+
+    /// Executes a transaction from another client.
+    ///
+    /// It waits until any ongoing local transaction ends, so that the remote
+    /// transaction is never interleaved with local operations; the lock timeout of the
+    /// client does not apply, since the transaction would be lost otherwise.
+    /// Returns whether the transaction is newly executed.
+    #[instrument(skip_all, fields(tx = %tx))]
+    pub fn execute_remote_transaction(&self, tx: Arc<Transaction>) -> Result<bool, DatatypeError> {
+        let tx_ctx = TransactionContext::with_lock_timeout(Default::default(), Duration::MAX);
+        let executed = self.do_transaction(Arc::new(tx_ctx), || {
+            let mut mutable = self.mutable.write();
+            let executed = mutable.execute_remote_transaction(tx.clone())?;
+            if executed {
+                self.persist(&mutable, DatatypeRecord::Transaction(tx.clone()));
+            }
+            Ok(executed)
+        })?;
+        if executed {
+            self.events.dispatch(DatatypeEvent::RemoteOperations(
+                tx.iter().cloned().collect(),
+            ));
+            if tx.is_event() {
+                self.events.dispatch(DatatypeEvent::RemoteEvent(tx));
+            }
+        }
+        Ok(executed)
+    }
+
+    /// Creates a [`PushPullPack`] to push the pending transactions of this datatype.
+    pub fn create_push_pull_pack(&self) -> PushPullPack {
+        let mutable = self.mutable.read();
+        PushPullPack {
+            key: self.attr.key.clone(),
+            duid: self.attr.duid.clone(),
+            r#type: self.attr.r#type,
+            state: mutable.state,
+            checkpoint: mutable.checkpoint,
+            transactions: mutable.pending_transactions(),
+            error: None,
+        }
+    }
+
+    /// Applies a [`PushPullPack`] from the server.
+    ///
+    /// The transactions of other clients are executed, the checkpoint is advanced, the
+    /// transactions confirmed are dropped from the rollback history, and the datatype
+    /// becomes [`DatatypeState::Subscribed`] once the server accepts it. Likewise, it
+    /// becomes [`DatatypeState::Closed`] or [`DatatypeState::Deleted`] once the server
+    /// unsubscribes or deletes it.
+    #[instrument(skip_all, fields(pack = %pack))]
+    pub fn apply_push_pull_pack(&self, pack: PushPullPack) -> Result<(), DatatypeError> {
+        self.do_apply_push_pull_pack(pack)
+            .inspect_err(|e| self.events.dispatch(DatatypeEvent::Error(e.clone())))
+    }
+
+    fn do_apply_push_pull_pack(&self, pack: PushPullPack) -> Result<(), DatatypeError> {
+        if let Some(error) = pack.error {
+            return Err(err!(DatatypeError::FailedToSync, error));
+        }
+        for tx in pack.transactions {
+            self.execute_remote_transaction(tx)?;
+        }
+        let mut mutable = self.mutable.write();
+        let checkpoint = mutable.checkpoint;
+        mutable.checkpoint.sync(&pack.checkpoint);
+        if mutable.checkpoint != checkpoint {
+            self.persist(&mutable, DatatypeRecord::Checkpoint(mutable.checkpoint));
+        }
+        // the transactions confirmed by the server are no longer rolled back.
+        mutable.trim_rollback();
+        let old = mutable.state;
+        let new = match (old, pack.state) {
+            (
+                DatatypeState::DueToCreate
+                | DatatypeState::DueToSubscribe
+                | DatatypeState::DueToSubscribeOrCreate,
+                DatatypeState::Subscribed,
+            ) => DatatypeState::Subscribed,
+            (DatatypeState::DueToUnsubscribe, DatatypeState::Closed) => DatatypeState::Closed,
+            (_, DatatypeState::Deleted) => DatatypeState::Deleted,
+            _ => old,
+        };
+        self.change_state(&mut mutable, new)
+    }
+
+    /// Sets the state requested by the client, e.g., [`DatatypeState::DueToUnsubscribe`].
+    pub fn set_state(&self, state: DatatypeState) -> Result<(), DatatypeError> {
+        let mut mutable = self.mutable.write();
+        self.change_state(&mut mutable, state)
+    }
+
+    fn change_state(
+        &self,
+        mutable: &mut MutableDatatype,
+        new: DatatypeState,
+    ) -> Result<(), DatatypeError> {
+        let old = mutable.state;
+        if old == new {
+            return Ok(());
+        }
+        mutable.set_state(new)?;
+        self.persist(mutable, DatatypeRecord::State(new));
+        self.events.dispatch(DatatypeEvent::StateChanged(old, new));
+        if new == DatatypeState::Subscribed {
+            self.subscribed.notify_waiters();
+        }
+        Ok(())
+    }
+
+    /// Returns the instant until which a caller with `tx_ctx` started at `started` waits
+    /// for another transaction, or `None` to wait as long as it takes.
+    fn lock_deadline(&self, tx_ctx: &TransactionContext, started: Instant) -> Option<Instant> {
+        tx_ctx
+            .lock_timeout
+            .or(self.attr.client_info.lock_timeout)
+            .and_then(|timeout| started.checked_add(timeout))
+    }
+
+    /// Waits until `mutex` is unlocked, unless `deadline` passes first.
+    fn wait_for(
+        &self,
+        mutex: &NoGuardMutex,
+        deadline: Option<Instant>,
+        started: Instant,
+    ) -> Result<(), DatatypeError> {
+        // checked in advance, since the mutex can be unlocked while the transaction goes on.
+        if deadline.is_some_and(|deadline| deadline <= Instant::now())
+            || !mutex.lock_until(deadline)
+        {
+            return Err(self.lock_timeout_error(started));
+        }
+        mutex.unlock();
+        Ok(())
+    }
+
+    fn lock_timeout_error(&self, started: Instant) -> DatatypeError {
+        err!(
+            DatatypeError::LockTimeout,
+            format!(
+                "'{}' waited {:?} for another transaction",
+                self.attr.key,
+                started.elapsed()
+            )
+        )
+    }
+}
+
+/// Returns the attribute of a span event recording how long a caller has waited since
+/// `started`.
+fn waited(started: Instant) -> KeyValue {
+    KeyValue::new("waited_us", started.elapsed().as_micros() as i64)
 }
 
 #[cfg(test)]
 mod tests_transactional {
-    use std::sync::Arc;
+    use std::{
+        sync::Arc,
+        time::{Duration, Instant},
+    };
 
     use parking_lot::Mutex;
     use tracing::{Span, info, info_span, instrument};
     use tracing_opentelemetry::OpenTelemetrySpanExt;
 
     use crate::{
-        DataType,
-        datatypes::transactional::{TransactionContext, TransactionalDatatype},
-        operations::Operation,
+        DataType, DatatypeError, Primitive,
+        clients::client::ClientInfo,
+        datatypes::{
+            crdts::Crdt,
+            transactional::{TransactionContext, TransactionalDatatype},
+        },
+        operations::{Operation, transaction::Transaction},
+        types::{operation_id::OperationId, uid::Cuid},
     };
 
+    fn new_datatype_with_cuid(r#type: DataType) -> TransactionalDatatype {
+        let client_info = Arc::new(ClientInfo {
+            cuid: Cuid::new(),
+            ..Default::default()
+        });
+        TransactionalDatatype::new(module_path!(), r#type, Default::default(), client_info)
+    }
+
+    fn list_values(tx_dt: &TransactionalDatatype) -> Vec<Primitive> {
+        let mutable = tx_dt.mutable.read();
+        let Crdt::List(l) = &mutable.crdt else {
+            unreachable!()
+        };
+        l.iter().cloned().collect()
+    }
+
+    #[test]
+    #[instrument]
+    fn can_execute_remote_transactions() {
+        let tx_dt1 = new_datatype_with_cuid(DataType::Counter);
+        let tx_dt2 = new_datatype_with_cuid(DataType::Counter);
+        for delta in [1, 2, 3] {
+            tx_dt1
+                .execute_local_operation_as_tx(
+                    Default::default(),
+                    Operation::new_counter_increase(delta),
+                )
+                .unwrap();
+        }
+        let txs = tx_dt1.mutable.read().rollback.transactions().clone();
+        assert_eq!(3, txs.len());
+        for tx in txs.iter() {
+            assert!(tx_dt2.execute_remote_transaction(tx.clone()).unwrap());
+            // duplicated transactions are ignored
+            assert!(!tx_dt2.execute_remote_transaction(tx.clone()).unwrap());
+            // own transactions are ignored
+            assert!(!tx_dt1.execute_remote_transaction(tx.clone()).unwrap());
+        }
+        // a transaction following a missing one is not executed until the gap is filled.
+        let tx_dt3 = new_datatype_with_cuid(DataType::Counter);
+        assert_eq!(
+            tx_dt3
+                .execute_remote_transaction(txs[1].clone())
+                .unwrap_err(),
+            DatatypeError::FailedToSync("".into())
+        );
+        assert!(tx_dt3.execute_remote_transaction(txs[0].clone()).unwrap());
+        assert!(tx_dt3.execute_remote_transaction(txs[1].clone()).unwrap());
+        {
+            let mutable2 = tx_dt2.mutable.read();
+            let Crdt::Counter(c) = &mutable2.crdt else {
+                unreachable!()
+            };
+            assert_eq!(6, c.value());
+            assert_eq!(3, mutable2.op_id.lamport);
+            assert_eq!(0, mutable2.op_id.cseq);
+        }
+
+        tx_dt2
+            .execute_local_operation_as_tx(Default::default(), Operation::new_counter_increase(4))
+            .unwrap();
+        let mutable2 = tx_dt2.mutable.read();
+        assert_eq!(4, mutable2.op_id.lamport);
+        assert_eq!(1, mutable2.op_id.cseq);
+    }
+
+    #[test]
+    #[instrument]
+    fn can_rebase_pending_local_transactions() {
+        let tx_dt1 = new_datatype_with_cuid(DataType::List);
+        let tx_dt2 = new_datatype_with_cuid(DataType::List);
+        tx_dt1
+            .execute_local_operation_as_tx(
+                Default::default(),
+                Operation::new_list_insert(0, vec!["a".into(), "b".into()]),
+            )
+            .unwrap();
+        tx_dt2
+            .execute_local_operation_as_tx(
+                Default::default(),
+                Operation::new_list_insert(0, vec!["x".into()]),
+            )
+            .unwrap();
+        let remote_tx = tx_dt1.mutable.read().rollback.transactions()[0].clone();
+        let local_tx = tx_dt2.mutable.read().rollback.transactions()[0].clone();
+        tx_dt1.execute_remote_transaction(local_tx.clone()).unwrap();
+        tx_dt2
+            .execute_remote_transaction(remote_tx.clone())
+            .unwrap();
+        assert_eq!(list_values(&tx_dt1), list_values(&tx_dt2));
+        {
+            let mutable2 = tx_dt2.mutable.read();
+            let history = mutable2.rollback.transactions();
+            assert!(Arc::ptr_eq(&history[0], &remote_tx));
+            assert!(Arc::ptr_eq(&history[1], &local_tx));
+        }
+
+        // a failed operation rolls back and replays the rebased history
+        let expected = list_values(&tx_dt2);
+        let result = tx_dt2
+            .execute_local_operation_as_tx(Default::default(), Operation::new_list_delete(0, 10));
+        assert!(result.is_err());
+        assert_eq!(expected, list_values(&tx_dt2));
+        tx_dt2
+            .execute_local_operation_as_tx(
+                Default::default(),
+                Operation::new_list_insert(3, vec!["c".into()]),
+            )
+            .unwrap();
+        assert_eq!(4, list_values(&tx_dt2).len());
+    }
+
+    #[test]
+    #[instrument]
+    fn can_fail_remote_transaction_without_side_effects() {
+        let tx_dt1 = new_datatype_with_cuid(DataType::List);
+        let tx_dt2 = new_datatype_with_cuid(DataType::List);
+        for op in [
+            Operation::new_list_insert(0, vec![1.into()]),
+            Operation::new_list_insert(1, vec![2.into()]),
+        ] {
+            tx_dt1
+                .execute_local_operation_as_tx(Default::default(), op)
+                .unwrap();
+        }
+        let txs = tx_dt1.mutable.read().rollback.transactions().clone();
+        // the operations of the second transaction refer to the element inserted by the
+        // first one, and thus fail in a transaction of the first cseq.
+        let mut op_id = txs[0].get_op_id();
+        op_id.cseq -= 1;
+        let mut misordered = Transaction::new(&mut op_id);
+        txs[1]
+            .iter()
+            .for_each(|op| misordered.push_operation(op.clone()));
+        assert_eq!(
+            tx_dt2
+                .execute_remote_transaction(Arc::new(misordered))
+                .unwrap_err(),
+            DatatypeError::FailedToExecuteOperation("".into())
+        );
+        assert!(list_values(&tx_dt2).is_empty());
+        assert!(tx_dt2.execute_remote_transaction(txs[0].clone()).unwrap());
+        assert!(tx_dt2.execute_remote_transaction(txs[1].clone()).unwrap());
+        assert_eq!(list_values(&tx_dt1), list_values(&tx_dt2));
+    }
+
     #[test]
     #[instrument]
     fn can_fail_operation_execution() {
@@ -288,7 +1052,7 @@ mod tests_transactional {
             let mutable = tx_dt.mutable.write();
             assert_eq!(0, mutable.op_id.cseq);
             assert!(mutable.transaction.is_none());
-            assert_eq!(0, mutable.rollback.transactions.len());
+            assert_eq!(0, mutable.rollback.transactions().len());
         }
 
         let op1 = Operation::new_delay_for_test(10, true);
@@ -298,7 +1062,7 @@ mod tests_transactional {
             let mutable = tx_dt.mutable.write();
             assert_eq!(1, mutable.op_id.cseq);
             assert!(mutable.transaction.is_none());
-            assert_eq!(1, mutable.rollback.transactions.len());
+            assert_eq!(1, mutable.rollback.transactions().len());
         }
 
         let op2 = Operation::new_delay_for_test(10, false);
@@ -307,7 +1071,7 @@ mod tests_transactional {
         {
             let mutable = tx_dt.mutable.write();
             assert_eq!(1, mutable.op_id.cseq);
-            assert_eq!(1, mutable.rollback.transactions.len());
+            assert_eq!(1, mutable.rollback.transactions().len());
         }
     }
 
@@ -402,6 +1166,218 @@ mod tests_transactional {
         assert!(check_negatives_contiguous(&executions))
     }
 
+    #[tokio::test]
+    #[instrument]
+    async fn can_yield_while_waiting_for_other_transaction() {
+        let tx_dt = Arc::new(TransactionalDatatype::new(
+            module_path!(),
+            DataType::Counter,
+            Default::default(),
+            Default::default(),
+        ));
+        let executions = Arc::new(Mutex::new(vec![]));
+        let blocking = {
+            let tx_dt = tx_dt.clone();
+            let executions = executions.clone();
+            std::thread::spawn(move || {
+                let tx_ctx = Arc::new(TransactionContext::new("blocking"));
+                tx_dt.do_transaction(tx_ctx.clone(), || {
+                    tx_dt.execute_local_operation_as_tx(
+                        tx_ctx.clone(),
+                        Operation::new_delay_for_test(200, true),
+                    )?;
+                    executions.lock().push("blocking");
+                    Ok(())
+                })
+            })
+        };
+        while !tx_dt.tx_mutex.is_locked() {
+            tokio::task::yield_now().await;
+        }
+
+        // on this single-threaded runtime, the ticker runs only if the transaction yields.
+        let tx_ctx = Arc::new(TransactionContext::new("async"));
+        let async_tx = tx_dt.do_transaction_async(tx_ctx.clone(), || {
+            tx_dt.execute_local_operation_as_tx(
+                tx_ctx.clone(),
+                Operation::new_counter_increase(1),
+            )?;
+            executions.lock().push("async");
+            Ok(())
+        });
+        let ticker = async {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            executions.lock().push("ticker");
+        };
+        let (result, _) = tokio::join!(async_tx, ticker);
+        result.unwrap();
+        blocking.join().unwrap().unwrap();
+        assert_eq!(*executions.lock(), vec!["ticker", "blocking", "async"]);
+    }
+
+    #[tokio::test]
+    #[instrument]
+    async fn can_time_out_waiting_for_other_transaction() {
+        let client_info = Arc::new(ClientInfo {
+            lock_timeout: Some(Duration::from_millis(50)),
+            ..Default::default()
+        });
+        let tx_dt = Arc::new(TransactionalDatatype::new(
+            module_path!(),
+            DataType::Counter,
+            Default::default(),
+            client_info,
+        ));
+        let blocking = {
+            let tx_dt = tx_dt.clone();
+            std::thread::spawn(move || {
+                let tx_ctx = Arc::new(TransactionContext::new("blocking"));
+                tx_dt.do_transaction(tx_ctx.clone(), || {
+                    tx_dt.execute_local_operation_as_tx(
+                        tx_ctx.clone(),
+                        Operation::new_delay_for_test(500, true),
+                    )
+                })
+            })
+        };
+        while !tx_dt.op_mutex.is_locked() {
+            tokio::task::yield_now().await;
+        }
+
+        let started = Instant::now();
+        let result = tx_dt.do_transaction(Arc::new(TransactionContext::new("tx")), || Ok(()));
+        assert_eq!(result.unwrap_err(), DatatypeError::LockTimeout("".into()));
+        assert!(started.elapsed() >= Duration::from_millis(50));
+
+        let op = Operation::new_counter_increase(1);
+        let result = tx_dt.execute_local_operation_as_tx(Default::default(), op.clone());
+        assert!(matches!(result, Err(DatatypeError::LockTimeout(_))));
+
+        let tx_ctx = Arc::new(TransactionContext::new("async"));
+        let result = tx_dt.do_transaction_async(tx_ctx, || Ok(())).await;
+        assert_eq!(result.unwrap_err(), DatatypeError::LockTimeout("".into()));
+
+        // a remote transaction waits for the ongoing one regardless of the timeout.
+        let mut remote_op_id = OperationId::new_with_cuid(&Cuid::new());
+        let mut remote_tx = Transaction::new(&mut remote_op_id);
+        remote_tx.push_operation(Operation::new_counter_increase(10));
+        let remote = {
+            let tx_dt = tx_dt.clone();
+            std::thread::spawn(move || tx_dt.execute_remote_transaction(Arc::new(remote_tx)))
+        };
+
+        // the timeout of the client is overridden by that of the context.
+        let started = Instant::now();
+        let impatient = TransactionContext::with_lock_timeout(Default::default(), Duration::ZERO);
+        let result = tx_dt.execute_local_operation_as_tx(Arc::new(impatient), op.clone());
+        assert!(matches!(result, Err(DatatypeError::LockTimeout(_))));
+        assert!(started.elapsed() < Duration::from_millis(50));
+
+        let patient =
+            TransactionContext::with_lock_timeout(Default::default(), Duration::from_secs(10));
+        let patient = Arc::new(TransactionContext::new_within("patient", Arc::new(patient)));
+        let result = tx_dt.do_transaction(patient.clone(), || {
+            tx_dt.execute_local_operation_as_tx(patient.clone(), op)
+        });
+        assert!(result.is_ok());
+        blocking.join().unwrap().unwrap();
+        assert!(remote.join().unwrap().unwrap());
+        let Crdt::Counter(c) = &tx_dt.mutable.read().crdt else {
+            unreachable!()
+        };
+        assert_eq!(c.value(), 11);
+    }
+
+    #[test]
+    fn can_reject_locking_within_ongoing_transaction() {
+        let tx_dt = TransactionalDatatype::new(
+            module_path!(),
+            DataType::Counter,
+            Default::default(),
+            Default::default(),
+        );
+        let outer = Arc::new(TransactionContext::new("outer"));
+        let result = tx_dt.do_transaction(outer.clone(), || {
+            let inner = Arc::new(TransactionContext::new_within("inner", outer.clone()));
+            assert!(matches!(
+                tx_dt.lock_transaction(inner),
+                Err(DatatypeError::FailedTransaction(_))
+            ));
+            Ok(())
+        });
+        assert!(result.is_ok());
+        assert!(tx_dt.lock_transaction(outer).is_ok());
+    }
+
+    #[tokio::test]
+    #[instrument]
+    async fn can_roll_back_panicking_transaction() {
+        let tx_dt = TransactionalDatatype::new(
+            module_path!(),
+            DataType::Counter,
+            Default::default(),
+            Default::default(),
+        );
+        let value = |tx_dt: &TransactionalDatatype| {
+            let Crdt::Counter(c) = &tx_dt.mutable.read().crdt else {
+                unreachable!()
+            };
+            c.value()
+        };
+        let increase = |tx_ctx: &Arc<TransactionContext>, delta| {
+            tx_dt.execute_local_operation_as_tx(
+                tx_ctx.clone(),
+                Operation::new_counter_increase(delta),
+            )
+        };
+
+        let tx_ctx = Arc::new(TransactionContext::new("panic"));
+        let result: Result<(), _> = tx_dt.do_transaction(tx_ctx.clone(), || {
+            increase(&tx_ctx, 1)?;
+            panic!("in transaction");
+        });
+        assert!(
+            matches!(result, Err(DatatypeError::FailedTransaction(m)) if m.contains("in transaction"))
+        );
+        assert_eq!(value(&tx_dt), 0);
+        assert!(tx_dt.tx_ctx.read().is_none());
+        assert!(!tx_dt.tx_mutex.is_locked());
+
+        // only the nested transaction that panics is rolled back.
+        let outer = Arc::new(TransactionContext::new("outer"));
+        let result = tx_dt.do_transaction(outer.clone(), || {
+            increase(&outer, 1)?;
+            let inner = Arc::new(TransactionContext::new_within("inner", outer.clone()));
+            let result: Result<(), _> = tx_dt.do_transaction(inner.clone(), || {
+                increase(&inner, 10)?;
+                panic!("in nested transaction");
+            });
+            assert!(matches!(result, Err(DatatypeError::FailedTransaction(_))));
+            increase(&outer, 2)
+        });
+        assert!(result.is_ok());
+        assert_eq!(value(&tx_dt), 3);
+
+        let tx_ctx = Arc::new(TransactionContext::new("async-panic"));
+        let result: Result<(), _> = tx_dt
+            .do_transaction_async(tx_ctx.clone(), || {
+                increase(&tx_ctx, 100)?;
+                panic!("in async transaction");
+            })
+            .await;
+        assert!(matches!(result, Err(DatatypeError::FailedTransaction(_))));
+        assert_eq!(value(&tx_dt), 3);
+
+        tx_dt
+            .events
+            .add_before_commit_hook(Arc::new(|_| panic!("in before_commit hook")));
+        let result = increase(&Default::default(), 1000);
+        assert!(matches!(result, Err(DatatypeError::FailedTransaction(_))));
+        assert_eq!(value(&tx_dt), 3);
+        assert!(tx_dt.tx_ctx.read().is_none());
+        assert!(!tx_dt.tx_mutex.is_locked());
+    }
+
     fn check_negatives_contiguous(nums: &Vec<i32>) -> bool {
         let mut in_negative_block = false;
         let mut finished_negative_block = false;
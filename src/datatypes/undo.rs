@@ -0,0 +1,551 @@
+use std::collections::{HashMap, VecDeque};
+
+use crate::{
+    datatypes::common::ReturnType,
+    operations::{
+        Operation,
+        body::{
+            DocumentDeleteInArrayBody, DocumentInsertToArrayBody, DocumentPutToObjectBody,
+            DocumentRemoveInObjectBody, ListDeleteBody, ListInsertBody, ListUpdateBody,
+            OperationBody, SetRemoveBody, TextEditBody,
+        },
+    },
+    types::{document_value::DocumentValue, element_id::ElementId, operation_id::OperationId},
+};
+
+/// The default number of steps that can be undone.
+pub const DEFAULT_UNDO_DEPTH: usize = 100;
+
+/// The stack onto which the ongoing transaction is pushed when it is committed.
+#[derive(Debug, Default)]
+enum UndoMode {
+    #[default]
+    Do,
+    Undo(Option<String>),
+    Redo(Option<String>),
+}
+
+/// An operation reverting another one.
+#[derive(Debug)]
+pub struct Inverse {
+    pub op: Operation,
+    /// The deleted elements which `op` inserts again as new elements.
+    replaced: Vec<ElementId>,
+}
+
+impl Inverse {
+    fn new(body: OperationBody) -> Self {
+        Self::from(Operation::new(body))
+    }
+}
+
+impl From<Operation> for Inverse {
+    fn from(op: Operation) -> Self {
+        Self {
+            op,
+            replaced: vec![],
+        }
+    }
+}
+
+/// A step to undo or redo: the inverses of the operations of one or more local
+/// transactions.
+#[derive(Debug)]
+struct UndoEntry {
+    tag: Option<String>,
+    inverses: Vec<Inverse>,
+}
+
+/// Keeps the inverse operations of the local transactions to undo and redo them.
+///
+/// The inverse operations have their targets resolved to the elements of the original
+/// operations, so that they are executed like remote operations and still apply after
+/// the edits of other clients. Since deleted elements are inserted again as new ones,
+/// the inverse operations referring to them are redirected to the new ones.
+///
+/// The committed transactions with the same tag in a row are grouped into a single
+/// step, and at most `depth` steps are kept.
+#[derive(Debug)]
+pub struct UndoManager {
+    depth: usize,
+    undos: VecDeque<UndoEntry>,
+    redos: VecDeque<UndoEntry>,
+    ongoing: Vec<Inverse>,
+    mode: UndoMode,
+    replacements: HashMap<ElementId, ElementId>,
+}
+
+impl UndoManager {
+    pub fn new(depth: usize) -> Self {
+        Self {
+            depth,
+            undos: Default::default(),
+            redos: Default::default(),
+            ongoing: Default::default(),
+            mode: Default::default(),
+            replacements: Default::default(),
+        }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undos.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redos.is_empty()
+    }
+
+    /// Records the inverse of `op` executed by `op_id` in the ongoing transaction.
+    pub fn record(&mut self, op: &Operation, ret: &ReturnType, op_id: &OperationId) {
+        if self.depth > 0 {
+            self.ongoing.extend(inverse_of(op, ret, op_id));
+        }
+    }
+
+    /// Pops the last step to undo, or to redo if `redo` is true, and returns its
+    /// inverses in the order to execute them.
+    ///
+    /// The transaction executing them is pushed onto the opposite stack when committed.
+    pub fn begin(&mut self, redo: bool) -> Option<Vec<Inverse>> {
+        let entry = match redo {
+            false => self.undos.pop_back()?,
+            true => self.redos.pop_back()?,
+        };
+        self.mode = match redo {
+            false => UndoMode::Undo(entry.tag),
+            true => UndoMode::Redo(entry.tag),
+        };
+        let mut inverses = entry.inverses;
+        inverses.reverse();
+        inverses
+            .iter_mut()
+            .for_each(|inverse| self.redirect(&mut inverse.op));
+        Some(inverses)
+    }
+
+    /// Redirects the replaced elements of `inverse` to the new elements inserted by
+    /// its operation executed by `op_id`.
+    pub fn replace(&mut self, inverse: &Inverse, op_id: &OperationId) {
+        let inserted = inserted_elements(&inverse.op, op_id);
+        let inserted = match inverse.op.body {
+            // all the removed tags of a set element are replaced by the tag of the add.
+            OperationBody::SetAdd(_) => vec![inserted[0].clone(); inverse.replaced.len()],
+            _ => inserted,
+        };
+        if inserted.len() != inverse.replaced.len() {
+            return;
+        }
+        for (replaced, new) in inverse.replaced.iter().zip(inserted) {
+            self.replacements.insert(replaced.clone(), new);
+        }
+    }
+
+    fn latest(&self, id: &ElementId) -> ElementId {
+        let mut id = id;
+        while let Some(new) = self.replacements.get(id) {
+            id = new;
+        }
+        id.clone()
+    }
+
+    fn redirect(&self, op: &mut Operation) {
+        let redirect = |id: &mut ElementId| *id = self.latest(id);
+        match &mut op.body {
+            OperationBody::ListInsert(body) => body.target.iter_mut().for_each(redirect),
+            OperationBody::ListDelete(body) => body.targets.iter_mut().for_each(redirect),
+            OperationBody::ListUpdate(body) => body.target.iter_mut().for_each(redirect),
+            OperationBody::DocumentPutToObject(body) => body.target.iter_mut().for_each(redirect),
+            OperationBody::DocumentRemoveInObject(body) => {
+                body.target.iter_mut().for_each(redirect)
+            }
+            OperationBody::DocumentInsertToArray(body) => {
+                body.target.iter_mut().for_each(redirect);
+                body.prev.iter_mut().for_each(redirect);
+            }
+            OperationBody::DocumentDeleteInArray(body) => {
+                body.target.iter_mut().for_each(redirect);
+                body.targets.iter_mut().for_each(redirect);
+            }
+            OperationBody::TextEdit(body) => {
+                body.target.iter_mut().for_each(redirect);
+                let mut chars = expand_spans(&body.targets);
+                chars.iter_mut().for_each(redirect);
+                body.targets = join_spans(chars);
+            }
+            OperationBody::SetRemove(body) => body.tags.iter_mut().for_each(redirect),
+            _ => {}
+        }
+    }
+
+    /// Pushes the ongoing transaction with `tag` as a step.
+    pub fn commit(&mut self, tag: Option<String>) {
+        let inverses = std::mem::take(&mut self.ongoing);
+        let mode = std::mem::take(&mut self.mode);
+        if inverses.is_empty() {
+            return;
+        }
+        match mode {
+            UndoMode::Do => {
+                self.redos.clear();
+                match self.undos.back_mut() {
+                    Some(last) if tag.is_some() && last.tag == tag => {
+                        last.inverses.extend(inverses)
+                    }
+                    _ => self.undos.push_back(UndoEntry { tag, inverses }),
+                }
+            }
+            UndoMode::Undo(tag) => self.redos.push_back(UndoEntry { tag, inverses }),
+            UndoMode::Redo(tag) => self.undos.push_back(UndoEntry { tag, inverses }),
+        }
+        for stack in [&mut self.undos, &mut self.redos] {
+            while stack.len() > self.depth {
+                stack.pop_front();
+            }
+        }
+        if self.undos.is_empty() && self.redos.is_empty() {
+            self.replacements.clear();
+        }
+    }
+
+    /// Returns the number of inverses recorded in the ongoing transaction, to which it
+    /// can be rolled back by [`Self::rollback_to`].
+    pub fn savepoint(&self) -> usize {
+        self.ongoing.len()
+    }
+
+    /// Discards the inverses recorded in the ongoing transaction after `savepoint`.
+    pub fn rollback_to(&mut self, savepoint: usize) {
+        self.ongoing.truncate(savepoint);
+    }
+
+    /// Discards the ongoing transaction rolled back.
+    pub fn discard(&mut self) {
+        self.ongoing.clear();
+        self.mode = UndoMode::Do;
+    }
+}
+
+/// Returns the number of elements of `value`, to which its nested values are added with
+/// consecutive offsets.
+fn count_elements(value: &DocumentValue) -> u32 {
+    1 + match value {
+        DocumentValue::Primitive(_) => 0,
+        DocumentValue::Object(o) => o.values().map(count_elements).sum(),
+        DocumentValue::Array(a) => a.iter().map(count_elements).sum(),
+    }
+}
+
+/// Returns the elements inserted by `op` executed by `op_id`, except the nested ones of
+/// document values.
+fn inserted_elements(op: &Operation, op_id: &OperationId) -> Vec<ElementId> {
+    let consecutive = |len: usize| {
+        (0..len as u32)
+            .map(|offset| ElementId::new(op_id, offset))
+            .collect()
+    };
+    match &op.body {
+        OperationBody::ListInsert(body) => consecutive(body.values.len()),
+        OperationBody::TextEdit(body) => consecutive(body.text.chars().count()),
+        OperationBody::SetAdd(_) => consecutive(1),
+        OperationBody::DocumentInsertToArray(body) => {
+            let mut offset = 0;
+            body.values
+                .iter()
+                .map(|value| {
+                    let id = ElementId::new(op_id, offset);
+                    offset += count_elements(value);
+                    id
+                })
+                .collect()
+        }
+        _ => vec![],
+    }
+}
+
+fn expand_spans(spans: &[(ElementId, u32)]) -> Vec<ElementId> {
+    spans
+        .iter()
+        .flat_map(|(id, len)| {
+            (0..*len).map(|k| ElementId {
+                offset: id.offset + k,
+                ..id.clone()
+            })
+        })
+        .collect()
+}
+
+fn join_spans(chars: Vec<ElementId>) -> Vec<(ElementId, u32)> {
+    let mut spans: Vec<(ElementId, u32)> = vec![];
+    for id in chars {
+        match spans.last_mut() {
+            Some((first, len))
+                if first.lamport == id.lamport
+                    && first.cuid == id.cuid
+                    && first.offset + *len == id.offset =>
+            {
+                *len += 1
+            }
+            _ => spans.push((id, 1)),
+        }
+    }
+    spans
+}
+
+/// Returns the inverses reverting `op`, which has been executed by `op_id` and has
+/// returned `ret`.
+///
+/// The keys of a document object removed by replacing it are not restored.
+fn inverse_of(op: &Operation, ret: &ReturnType, op_id: &OperationId) -> Vec<Inverse> {
+    let inserted = inserted_elements(op, op_id);
+    match (&op.body, ret) {
+        (OperationBody::CounterIncrease(body), _) => {
+            vec![Operation::new_counter_increase(body.delta.wrapping_neg()).into()]
+        }
+        (OperationBody::VariableSet(_), ReturnType::Variable(old)) => {
+            vec![Operation::new_variable_set(old.clone()).into()]
+        }
+        (OperationBody::ListInsert(body), _) if !inserted.is_empty() => {
+            let mut delete = ListDeleteBody::new(body.pos, inserted.len());
+            delete.targets = inserted;
+            vec![Inverse::new(OperationBody::ListDelete(delete))]
+        }
+        (OperationBody::ListDelete(body), ReturnType::List(deleted)) if !deleted.is_empty() => {
+            // inserted right after the first deleted element, i.e., where they were.
+            let mut insert = ListInsertBody::new(body.pos, deleted.clone());
+            insert.target = body.targets.first().cloned();
+            vec![Inverse {
+                op: Operation::new(OperationBody::ListInsert(insert)),
+                replaced: body.targets.clone(),
+            }]
+        }
+        (OperationBody::ListUpdate(body), ReturnType::List(olds)) => {
+            let Some(old) = olds.first() else {
+                return vec![];
+            };
+            let mut update = ListUpdateBody::new(body.pos, old.clone());
+            update.target = body.target.clone();
+            vec![Inverse::new(OperationBody::ListUpdate(update))]
+        }
+        (OperationBody::MapPut(body), ReturnType::Map(olds)) => body
+            .entries
+            .iter()
+            .zip(olds.iter())
+            .rev()
+            .map(|((key, _), old)| match old {
+                Some(old) => Operation::new_map_put(vec![(key.clone(), old.clone())]).into(),
+                None => Operation::new_map_remove(key.clone()).into(),
+            })
+            .collect(),
+        (OperationBody::MapRemove(body), ReturnType::Map(olds)) => match olds.first() {
+            Some(Some(old)) => {
+                vec![Operation::new_map_put(vec![(body.key.clone(), old.clone())]).into()]
+            }
+            _ => vec![],
+        },
+        (OperationBody::DocumentPutToObject(body), ReturnType::Document(olds)) => body
+            .entries
+            .iter()
+            .zip(olds.iter())
+            .rev()
+            .map(|((key, _), old)| match old {
+                Some(old) => {
+                    let entries = vec![(key.clone(), old.clone())];
+                    let mut put = DocumentPutToObjectBody::new(body.path.clone(), entries, false);
+                    put.target = body.target.clone();
+                    Inverse::new(OperationBody::DocumentPutToObject(put))
+                }
+                None => {
+                    let mut remove =
+                        DocumentRemoveInObjectBody::new(body.path.clone(), key.clone());
+                    remove.target = body.target.clone();
+                    Inverse::new(OperationBody::DocumentRemoveInObject(remove))
+                }
+            })
+            .collect(),
+        (OperationBody::DocumentRemoveInObject(body), ReturnType::Document(olds)) => {
+            let Some(Some(old)) = olds.first() else {
+                return vec![];
+            };
+            let entries = vec![(body.key.clone(), old.clone())];
+            let mut put = DocumentPutToObjectBody::new(body.path.clone(), entries, false);
+            put.target = body.target.clone();
+            vec![Inverse::new(OperationBody::DocumentPutToObject(put))]
+        }
+        (OperationBody::DocumentInsertToArray(body), _) if !inserted.is_empty() => {
+            let mut delete =
+                DocumentDeleteInArrayBody::new(body.path.clone(), body.pos, inserted.len());
+            delete.target = body.target.clone();
+            delete.targets = inserted;
+            vec![Inverse::new(OperationBody::DocumentDeleteInArray(delete))]
+        }
+        (OperationBody::DocumentDeleteInArray(body), ReturnType::Document(deleted)) => {
+            let values: Vec<DocumentValue> = deleted.iter().flatten().cloned().collect();
+            if values.is_empty() {
+                return vec![];
+            }
+            let mut insert = DocumentInsertToArrayBody::new(body.path.clone(), body.pos, values);
+            insert.target = body.target.clone();
+            insert.prev = body.targets.first().cloned();
+            vec![Inverse {
+                op: Operation::new(OperationBody::DocumentInsertToArray(insert)),
+                replaced: body.targets.clone(),
+            }]
+        }
+        (OperationBody::TextEdit(body), ReturnType::Text(deleted)) => {
+            if inserted.is_empty() && deleted.is_empty() {
+                return vec![];
+            }
+            let mut edit = TextEditBody::new(body.pos, inserted.len(), deleted.clone());
+            edit.targets = join_spans(inserted);
+            let mut replaced = vec![];
+            if !deleted.is_empty() {
+                edit.target = body.targets.first().map(|(id, _)| id.clone());
+                replaced = expand_spans(&body.targets);
+            }
+            vec![Inverse {
+                op: Operation::new(OperationBody::TextEdit(edit)),
+                replaced,
+            }]
+        }
+        (OperationBody::SetAdd(body), ReturnType::Set(true)) => {
+            let mut remove = SetRemoveBody::new(body.element.clone());
+            remove.tags = inserted;
+            vec![Inverse::new(OperationBody::SetRemove(remove))]
+        }
+        (OperationBody::SetRemove(body), ReturnType::Set(true)) => vec![Inverse {
+            op: Operation::new_set_add(body.element.clone()),
+            replaced: body.tags.clone(),
+        }],
+        _ => vec![],
+    }
+}
+
+#[cfg(test)]
+mod tests_undo {
+    use tracing::instrument;
+
+    use crate::{
+        Client, Datatype, DocumentValue, Primitive,
+        datatypes::{
+            common::ReturnType,
+            undo::{UndoManager, count_elements},
+        },
+        operations::Operation,
+    };
+
+    #[test]
+    fn can_count_elements_of_document_values() {
+        assert_eq!(count_elements(&1.into()), 1);
+        let value = DocumentValue::Array(vec![1.into(), DocumentValue::Array(vec![2.into()])]);
+        assert_eq!(count_elements(&value), 4);
+    }
+
+    #[test]
+    fn can_group_steps_by_tag_and_bound_depth() {
+        let mut undo = UndoManager::new(2);
+        let commit = |undo: &mut UndoManager, tag: Option<&str>| {
+            undo.ongoing.push(Operation::new_counter_increase(1).into());
+            undo.commit(tag.map(str::to_owned));
+        };
+        commit(&mut undo, Some("typing"));
+        commit(&mut undo, Some("typing"));
+        assert_eq!(undo.undos.len(), 1);
+        assert_eq!(undo.undos[0].inverses.len(), 2);
+        commit(&mut undo, None);
+        commit(&mut undo, None);
+        assert_eq!(undo.undos.len(), 2);
+        assert_eq!(undo.undos[0].tag, None);
+
+        assert_eq!(undo.begin(false).unwrap().len(), 1);
+        commit(&mut undo, None);
+        assert!(undo.can_redo());
+        // a new step clears the steps to redo
+        commit(&mut undo, Some("typing"));
+        assert!(!undo.can_redo());
+        assert!(undo.begin(true).is_none());
+
+        let mut disabled = UndoManager::new(0);
+        disabled.record(
+            &Operation::new_counter_increase(1),
+            &ReturnType::Counter(1),
+            &Default::default(),
+        );
+        disabled.commit(None);
+        assert!(!disabled.can_undo());
+    }
+
+    #[test]
+    #[instrument]
+    fn can_undo_and_redo_every_datatype() {
+        let client = Client::builder(module_path!(), module_path!())
+            .build()
+            .unwrap();
+
+        let counter = client.create_counter("counter").unwrap();
+        counter.increase_by(3);
+        counter.increase_by(4);
+        assert!(counter.undo());
+        assert_eq!(counter.get_value(), 3);
+        assert!(counter.redo());
+        assert_eq!(counter.get_value(), 7);
+        assert!(!counter.redo());
+
+        let variable = client.create_variable("variable").unwrap();
+        variable.set(1);
+        variable.set("yam");
+        assert!(variable.undo());
+        assert_eq!(variable.get(), Primitive::Int(1));
+        assert!(variable.undo());
+        assert_eq!(variable.get(), Primitive::Null);
+        assert!(!variable.undo());
+
+        let list = client.create_list("list").unwrap();
+        list.insert(0, [1, 2, 3]).unwrap();
+        list.delete(1, 1).unwrap();
+        list.update(0, 0).unwrap();
+        assert!(list.undo() && list.undo());
+        assert_eq!(list.to_vec(), vec![1.into(), 2.into(), 3.into()]);
+        assert!(list.redo());
+        assert_eq!(list.to_vec(), vec![1.into(), 3.into()]);
+        // the elements inserted again are deleted by undoing their insertion
+        assert!(list.undo() && list.undo());
+        assert!(list.is_empty());
+
+        let map = client.create_map("map").unwrap();
+        map.put("a", 1);
+        map.transaction("put", |m| {
+            m.put("a", 2);
+            m.put("b", 3);
+            m.remove("a");
+            Ok(())
+        })
+        .unwrap();
+        assert!(map.undo());
+        assert_eq!(map.get("a"), Some(1.into()));
+        assert_eq!(map.get("b"), None);
+
+        let document = client.create_document("document").unwrap();
+        document
+            .put_to_object("", "a", DocumentValue::new_array())
+            .unwrap();
+        let values = vec![1.into(), DocumentValue::Array(vec![2.into()]), 3.into()];
+        document.insert_to_array("/a", 0, values).unwrap();
+        document.delete_in_array("/a", 0, 2).unwrap();
+        assert!(document.undo());
+        assert_eq!(document.get_by_path("/a/1/0"), Some(2.into()));
+        assert!(document.undo());
+        assert_eq!(document.get_by_path("/a"), Some(DocumentValue::new_array()));
+        assert!(document.undo());
+        assert_eq!(document.get_by_path("/a"), None);
+
+        let set = client.create_set("set").unwrap();
+        set.add("a");
+        set.remove("a");
+        assert!(set.undo());
+        assert!(set.contains("a"));
+        assert!(set.undo());
+        assert!(set.is_empty());
+        assert!(set.redo());
+        assert!(set.contains("a"));
+    }
+}
@@ -0,0 +1,271 @@
+use std::{error::Error, sync::Arc, time::Duration};
+
+use crate::{
+    DataType, DatatypeError, DatatypeState, IntoString,
+    clients::client::ClientInfo,
+    datatypes::{
+        common::ReturnType,
+        crdts::Crdt,
+        datatype::DatatypeBlanket,
+        datatype_instrument,
+        transactional::{TransactionContext, TransactionalDatatype},
+    },
+    operations::Operation,
+    types::primitive::Primitive,
+};
+
+/// A variable is a conflict-free register that holds a single [`Primitive`] value.
+///
+/// Concurrent sets are resolved by the last-writer-wins rule, where writers are
+/// ordered by their lamport clocks and then by their client ids.
+#[derive(Clone)]
+pub struct Variable {
+    datatype: Arc<TransactionalDatatype>,
+    tx_ctx: Arc<TransactionContext>,
+}
+
+impl Variable {
+    pub(crate) fn new(key: String, state: DatatypeState, client_info: Arc<ClientInfo>) -> Self {
+        Variable {
+            datatype: Arc::new(TransactionalDatatype::new(
+                &key,
+                DataType::Variable,
+                state,
+                client_info,
+            )),
+            tx_ctx: Default::default(),
+        }
+    }
+
+    pub(crate) fn from_core(datatype: Arc<TransactionalDatatype>) -> Self {
+        Variable {
+            datatype,
+            tx_ctx: Default::default(),
+        }
+    }
+
+    /// Returns a handle of this variable operating in the transaction of `tx_ctx`.
+    pub(crate) fn with_tx_ctx(&self, tx_ctx: Arc<TransactionContext>) -> Self {
+        Variable {
+            datatype: self.datatype.clone(),
+            tx_ctx,
+        }
+    }
+
+    datatype_instrument! {
+    /// Sets the value of the variable.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The new value, which can be anything convertible into a [`Primitive`]
+    ///
+    /// # Returns
+    ///
+    /// The previous value of the variable
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use syncyam::{Client, Primitive};
+    /// let client = Client::builder("test-collection", "test-client").build().unwrap();
+    /// let variable = client.create_variable("test-variable").unwrap();
+    /// assert_eq!(variable.set("yam"), Primitive::Null);
+    /// assert_eq!(variable.set(42), Primitive::from("yam"));
+    /// ```
+    pub fn set(&self, value: impl Into<Primitive>) -> Primitive {
+        let op = Operation::new_variable_set(value.into());
+        match self
+            .datatype
+            .execute_local_operation_as_tx(self.tx_ctx.clone(), op)
+        {
+            Ok(ReturnType::Variable(old)) => old,
+            _ => self.get(),
+        }
+    }}
+
+    /// Gets the current value of the variable.
+    ///
+    /// A newly created variable holds [`Primitive::Null`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use syncyam::{Client, Primitive};
+    /// let client = Client::builder("test-collection", "test-client").build().unwrap();
+    /// let variable = client.create_variable("test-variable").unwrap();
+    /// assert!(variable.get().is_null());
+    /// variable.set(2.5);
+    /// assert_eq!(variable.get(), Primitive::Float(2.5));
+    /// ```
+    pub fn get(&self) -> Primitive {
+        let mutable = self.datatype.mutable.read();
+        let Crdt::Variable(v) = &mutable.crdt else {
+            unreachable!()
+        };
+        v.value().clone()
+    }
+
+    /// Returns a handle of this variable whose operations and transactions wait at most
+    /// `timeout` for another transaction of the variable, overriding the lock timeout of
+    /// the client.
+    ///
+    /// Once the timeout elapses, they fail with [`DatatypeError::LockTimeout`].
+    pub fn with_lock_timeout(&self, timeout: Duration) -> Self {
+        let tx_ctx = TransactionContext::with_lock_timeout(self.tx_ctx.clone(), timeout);
+        self.with_tx_ctx(Arc::new(tx_ctx))
+    }
+
+    /// Returns a handle of this variable whose transactions are marked as events, which
+    /// other clients receive by [`Datatype::on_remote_event`](crate::Datatype::on_remote_event).
+    pub fn as_event(&self) -> Self {
+        self.with_tx_ctx(Arc::new(TransactionContext::for_event(self.tx_ctx.clone())))
+    }
+
+    datatype_instrument! {
+    /// Executes multiple operations atomically within a transaction.
+    ///
+    /// If the transaction function returns an error or panics, all operations within
+    /// the transaction are rolled back, leaving the variable unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use syncyam::{Client, Primitive};
+    /// let client = Client::builder("test-collection", "test-client").build().unwrap();
+    /// let variable = client.create_variable("test-variable").unwrap();
+    /// let result: Result<(), _> = variable.transaction("failing-update", |v| {
+    ///     v.set("yam");
+    ///     Err("something went wrong".into())
+    /// });
+    /// assert!(result.is_err());
+    /// assert!(variable.get().is_null()); // unchanged
+    /// ```
+    pub fn transaction<T, R>(
+        &self,
+        tag: impl IntoString,
+        tx_func: T,
+    ) -> Result<R, DatatypeError>
+    where
+        T: FnOnce(Self) -> Result<R, Box<dyn Error + Send + Sync>> + Send + Sync + 'static,
+    {
+        let this_tx_ctx = Arc::new(TransactionContext::new_within(tag, self.tx_ctx.clone()));
+        let this_tx_ctx_clone = this_tx_ctx.clone();
+        let do_tx_func = move || {
+            let mut variable_clone = self.clone();
+            variable_clone.tx_ctx = this_tx_ctx_clone.clone();
+            match tx_func(variable_clone) {
+                Ok(r) => Ok(r),
+                Err(e) => Err(DatatypeError::FailedTransaction(e.to_string())),
+            }
+        };
+        self.datatype
+            .do_transaction(this_tx_ctx, do_tx_func)
+    }}
+
+    datatype_instrument! {
+    /// Executes multiple operations atomically within a transaction like
+    /// [`Variable::transaction`], but yields to the executor instead of blocking the thread
+    /// while another transaction of this variable is running.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use syncyam::Client;
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let client = Client::builder("test-collection", "test-client").build().unwrap();
+    /// let variable = client.create_variable_async("test-variable").await.unwrap();
+    /// let result = variable
+    ///     .transaction_async("update", |v| {
+    ///         v.set("yam");
+    ///         Ok(())
+    ///     })
+    ///     .await;
+    /// assert!(result.is_ok());
+    /// assert_eq!(variable.get(), "yam".into());
+    /// # }
+    /// ```
+    pub async fn transaction_async<T, R>(
+        &self,
+        tag: impl IntoString,
+        tx_func: T,
+    ) -> Result<R, DatatypeError>
+    where
+        T: FnOnce(Self) -> Result<R, Box<dyn Error + Send + Sync>> + Send + Sync + 'static,
+    {
+        let this_tx_ctx = Arc::new(TransactionContext::new_within(tag, self.tx_ctx.clone()));
+        let this_tx_ctx_clone = this_tx_ctx.clone();
+        let do_tx_func = move || {
+            let mut variable_clone = self.clone();
+            variable_clone.tx_ctx = this_tx_ctx_clone.clone();
+            match tx_func(variable_clone) {
+                Ok(r) => Ok(r),
+                Err(e) => Err(DatatypeError::FailedTransaction(e.to_string())),
+            }
+        };
+        self.datatype
+            .do_transaction_async(this_tx_ctx, do_tx_func)
+            .await
+    }}
+}
+
+impl DatatypeBlanket for Variable {
+    fn get_core(&self) -> &TransactionalDatatype {
+        self.datatype.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests_variable {
+    use tracing::instrument;
+
+    use crate::{
+        DataType, Primitive,
+        datatypes::{datatype::Datatype, variable::Variable},
+    };
+
+    #[test]
+    fn can_assert_send_and_sync_traits() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Variable>();
+    }
+
+    #[test]
+    #[instrument]
+    fn can_use_variable_operations() {
+        let variable = Variable::new(
+            module_path!().to_owned(),
+            Default::default(),
+            Default::default(),
+        );
+        assert_eq!(variable.get_type(), DataType::Variable);
+        assert!(variable.get().is_null());
+        assert_eq!(Primitive::Null, variable.set(1));
+        assert_eq!(Primitive::Int(1), variable.set("two"));
+        assert_eq!(Primitive::from("two"), variable.get());
+    }
+
+    #[test]
+    #[instrument]
+    fn can_use_transaction() {
+        let variable = Variable::new(
+            module_path!().to_owned(),
+            Default::default(),
+            Default::default(),
+        );
+        let result1 = variable.transaction("success", |v| {
+            v.set(1);
+            v.set(vec![2u8]);
+            Ok(())
+        });
+        assert!(result1.is_ok());
+        assert_eq!(Primitive::Bytes(vec![2]), variable.get());
+
+        let result2: Result<(), _> = variable.transaction("failure", |v| {
+            v.set(true);
+            Err("failed".into())
+        });
+        assert!(result2.is_err());
+        assert_eq!(Primitive::Bytes(vec![2]), variable.get());
+    }
+}
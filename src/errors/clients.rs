@@ -15,6 +15,43 @@ pub enum ClientError {
     /// example, mismatched type or datatype state).
     #[error("Cannot subscribe or create datatype: {0}")]
     FailedToSubscribeOrCreateDatatype(String),
+    /// Synchronization with the server failed.
+    ///
+    /// Returned when no transport is configured or the transport fails
+    /// to deliver a request to the server.
+    #[error("Cannot sync with server: {0}")]
+    FailedToSync(String),
+    /// Accessing the local storage failed.
+    ///
+    /// Returned when the records of datatypes cannot be read from or
+    /// written to the storage, or the stored records are corrupted.
+    #[error("Cannot access storage: {0}")]
+    FailedToAccessStorage(String),
+    /// Finding a datatype failed.
+    ///
+    /// Returned when the client has no datatype for the key of a
+    /// request, e.g., to unsubscribe, close, or delete it.
+    #[error("Cannot find datatype: {0}")]
+    FailedToFindDatatype(String),
+    /// Changing the state of a datatype failed.
+    ///
+    /// Returned when a request, e.g., to unsubscribe or delete a
+    /// datatype, is not allowed in its current state.
+    #[error("Cannot change datatype state: {0}")]
+    FailedToChangeDatatypeState(String),
+    /// Importing a datatype failed.
+    ///
+    /// Returned when a snapshot to import is malformed, or the client
+    /// already has a datatype for the key to import it as.
+    #[error("Cannot import datatype: {0}")]
+    FailedToImportDatatype(String),
+    /// A transaction over several datatypes failed.
+    ///
+    /// Returned when the function of [`Client::transaction`](crate::Client::transaction)
+    /// returns an error, after every change it made is rolled back, or any of the
+    /// datatypes cannot be locked in time.
+    #[error("Failed transaction: {0}")]
+    FailedTransaction(String),
 }
 
 impl PartialEq for ClientError {
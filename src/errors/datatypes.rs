@@ -10,11 +10,11 @@ use thiserror::Error;
 /// Two `DatatypeError` values are considered equal if they are the **same variant**,
 /// regardless of their message payload. See the custom `PartialEq` implementation.
 ///
-#[derive(Debug, Error)]
+#[derive(Debug, Clone, Error)]
 pub enum DatatypeError {
     /// Transaction execution failed.
     ///
-    /// Returned when a closure passed to `transaction` returns an error or when the
+    /// Returned when a closure passed to `transaction` returns an error or panics, or when the
     /// transactional context cannot be committed. The datatype state is left unchanged
     /// if a rollback succeeds.
     #[error("failed to do transaction: {0}")]
@@ -33,6 +33,31 @@ pub enum DatatypeError {
     /// not satisfied).
     #[error("failed to execute operation: {0}")]
     FailedToExecuteOperation(String),
+    /// Synchronization with the server failed.
+    ///
+    /// Returned when the server rejects the request for a datatype (e.g., subscribing
+    /// a datatype that does not exist) or its response cannot be applied.
+    #[error("failed to sync: {0}")]
+    FailedToSync(String),
+    /// The datatype is no longer writable.
+    ///
+    /// Returned when a local operation is executed on a datatype that is unsubscribed,
+    /// closed, or deleted, or is going to be.
+    #[error("datatype is closed: {0}")]
+    ClosedDatatype(String),
+    /// The state of the datatype cannot change as requested.
+    ///
+    /// Returned when a state change does not follow the lifecycle of datatypes, e.g.,
+    /// unsubscribing a datatype that is going to be deleted.
+    #[error("illegal state transition: {0}")]
+    IllegalStateTransition(String),
+    /// Waiting for another transaction timed out.
+    ///
+    /// Returned when an operation or a transaction cannot begin within the lock timeout
+    /// of the client or the handle, because another transaction of the datatype is
+    /// still running.
+    #[error("lock timed out: {0}")]
+    LockTimeout(String),
 }
 
 impl PartialEq for DatatypeError {
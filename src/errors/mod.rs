@@ -19,7 +19,7 @@ pub(crate) use err;
 
 #[cfg(test)]
 mod tests_datatype_errors {
-    use crate::{ClientError, DatatypeError, errors::err};
+    use crate::{ClientError, DatatypeError};
 
     #[test]
     fn can_compare_errors() {
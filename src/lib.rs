@@ -1,18 +1,44 @@
 use std::fmt::Debug;
 
 pub use crate::{
-    clients::client::Client,
-    datatypes::{DatatypeSet, counter::Counter, datatype::Datatype},
+    clients::{
+        client::{Client, ClientBuilder, ClientTransaction},
+        storage::{FileStorage, Storage},
+        transport::{Notifier, Transport},
+    },
+    datatypes::{
+        DatatypeSet,
+        counter::Counter,
+        datatype::Datatype,
+        document::Document,
+        list::List,
+        map::Map,
+        set::Set,
+        text::{Text, TextCursor},
+        variable::Variable,
+    },
     errors::{clients::ClientError, datatypes::DatatypeError},
-    types::datatype::{DataType, DatatypeState},
+    operations::{Operation, body::OperationBody, transaction::Transaction},
+    protocol::{Checkpoint, PushPullPack, PushPullRequest, PushPullResponse},
+    types::{
+        datatype::{DataType, DatatypeState},
+        document_value::DocumentValue,
+        operation_id::OperationId,
+        primitive::Primitive,
+        set_element::SetElement,
+        uid::{Cuid, Duid},
+    },
 };
 
 pub(crate) mod clients;
+#[cfg(feature = "tracing")]
 mod constants;
 pub(crate) mod datatypes;
 pub(crate) mod errors;
 pub(crate) mod observability;
 pub(crate) mod operations;
+pub(crate) mod protocol;
+pub mod server;
 pub(crate) mod types;
 pub(crate) mod utils;
 
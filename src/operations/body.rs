@@ -2,15 +2,67 @@ use std::fmt::{Debug, Formatter};
 
 use derive_more::Display;
 
-use crate::operations::MemoryMeasurable;
+use crate::{
+    DatatypeError,
+    operations::MemoryMeasurable,
+    types::{
+        document_value::DocumentValue, element_id::ElementId, primitive::Primitive,
+        set_element::SetElement,
+    },
+    utils::bytes::{BytesReader, BytesWriter},
+};
 
-#[derive(Clone, Display)]
+#[cfg(test)]
+const TAG_DELAY_4_TEST: u8 = 0;
+const TAG_COUNTER_INCREASE: u8 = 1;
+const TAG_VARIABLE_SET: u8 = 2;
+const TAG_LIST_INSERT: u8 = 3;
+const TAG_LIST_DELETE: u8 = 4;
+const TAG_LIST_UPDATE: u8 = 5;
+const TAG_MAP_PUT: u8 = 6;
+const TAG_MAP_REMOVE: u8 = 7;
+const TAG_DOCUMENT_PUT_TO_OBJECT: u8 = 8;
+const TAG_DOCUMENT_REMOVE_IN_OBJECT: u8 = 9;
+const TAG_DOCUMENT_INSERT_TO_ARRAY: u8 = 10;
+const TAG_DOCUMENT_DELETE_IN_ARRAY: u8 = 11;
+const TAG_TEXT_EDIT: u8 = 12;
+const TAG_SET_ADD: u8 = 13;
+const TAG_SET_REMOVE: u8 = 14;
+
+#[derive(Clone, PartialEq, Display)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum OperationBody {
     #[cfg(test)]
     #[display("Delay4Test")]
     Delay4Test(Delay4TestBody),
     #[display("CounterIncrease{_0}")]
     CounterIncrease(CounterIncreaseBody),
+    #[display("VariableSet{_0}")]
+    VariableSet(VariableSetBody),
+    #[display("ListInsert{_0}")]
+    ListInsert(ListInsertBody),
+    #[display("ListDelete{_0}")]
+    ListDelete(ListDeleteBody),
+    #[display("ListUpdate{_0}")]
+    ListUpdate(ListUpdateBody),
+    #[display("MapPut{_0}")]
+    MapPut(MapPutBody),
+    #[display("MapRemove{_0}")]
+    MapRemove(MapRemoveBody),
+    #[display("DocumentPutToObject{_0}")]
+    DocumentPutToObject(DocumentPutToObjectBody),
+    #[display("DocumentRemoveInObject{_0}")]
+    DocumentRemoveInObject(DocumentRemoveInObjectBody),
+    #[display("DocumentInsertToArray{_0}")]
+    DocumentInsertToArray(DocumentInsertToArrayBody),
+    #[display("DocumentDeleteInArray{_0}")]
+    DocumentDeleteInArray(DocumentDeleteInArrayBody),
+    #[display("TextEdit{_0}")]
+    TextEdit(TextEditBody),
+    #[display("SetAdd{_0}")]
+    SetAdd(SetAddBody),
+    #[display("SetRemove{_0}")]
+    SetRemove(SetRemoveBody),
 }
 
 impl Debug for OperationBody {
@@ -19,18 +71,306 @@ impl Debug for OperationBody {
     }
 }
 
+impl OperationBody {
+    pub(crate) fn write_to(&self, writer: &mut BytesWriter) {
+        match self {
+            #[cfg(test)]
+            OperationBody::Delay4Test(body) => {
+                writer.write_u8(TAG_DELAY_4_TEST);
+                writer.write_u64(body.duration_ms);
+                writer.write_bool(body.success);
+            }
+            OperationBody::CounterIncrease(body) => {
+                writer.write_u8(TAG_COUNTER_INCREASE);
+                writer.write_i64(body.delta);
+            }
+            OperationBody::VariableSet(body) => {
+                writer.write_u8(TAG_VARIABLE_SET);
+                body.value.write_to(writer);
+            }
+            OperationBody::ListInsert(body) => {
+                writer.write_u8(TAG_LIST_INSERT);
+                writer.write_u64(body.pos as u64);
+                write_optional_element_id(writer, &body.target);
+                writer.write_u32(body.values.len() as u32);
+                body.values.iter().for_each(|v| v.write_to(writer));
+            }
+            OperationBody::ListDelete(body) => {
+                writer.write_u8(TAG_LIST_DELETE);
+                writer.write_u64(body.pos as u64);
+                writer.write_u64(body.len as u64);
+                writer.write_u32(body.targets.len() as u32);
+                body.targets.iter().for_each(|t| t.write_to(writer));
+            }
+            OperationBody::ListUpdate(body) => {
+                writer.write_u8(TAG_LIST_UPDATE);
+                writer.write_u64(body.pos as u64);
+                write_optional_element_id(writer, &body.target);
+                body.value.write_to(writer);
+            }
+            OperationBody::MapPut(body) => {
+                writer.write_u8(TAG_MAP_PUT);
+                writer.write_u32(body.entries.len() as u32);
+                for (key, value) in body.entries.iter() {
+                    writer.write_str(key);
+                    value.write_to(writer);
+                }
+            }
+            OperationBody::MapRemove(body) => {
+                writer.write_u8(TAG_MAP_REMOVE);
+                writer.write_str(&body.key);
+            }
+            OperationBody::DocumentPutToObject(body) => {
+                writer.write_u8(TAG_DOCUMENT_PUT_TO_OBJECT);
+                writer.write_str(&body.path);
+                write_optional_element_id(writer, &body.target);
+                writer.write_u32(body.entries.len() as u32);
+                for (key, value) in body.entries.iter() {
+                    writer.write_str(key);
+                    value.write_to(writer);
+                }
+                writer.write_bool(body.replace);
+            }
+            OperationBody::DocumentRemoveInObject(body) => {
+                writer.write_u8(TAG_DOCUMENT_REMOVE_IN_OBJECT);
+                writer.write_str(&body.path);
+                write_optional_element_id(writer, &body.target);
+                writer.write_str(&body.key);
+            }
+            OperationBody::DocumentInsertToArray(body) => {
+                writer.write_u8(TAG_DOCUMENT_INSERT_TO_ARRAY);
+                writer.write_str(&body.path);
+                writer.write_u64(body.pos as u64);
+                write_optional_element_id(writer, &body.target);
+                write_optional_element_id(writer, &body.prev);
+                writer.write_u32(body.values.len() as u32);
+                body.values.iter().for_each(|v| v.write_to(writer));
+            }
+            OperationBody::DocumentDeleteInArray(body) => {
+                writer.write_u8(TAG_DOCUMENT_DELETE_IN_ARRAY);
+                writer.write_str(&body.path);
+                writer.write_u64(body.pos as u64);
+                writer.write_u64(body.len as u64);
+                write_optional_element_id(writer, &body.target);
+                writer.write_u32(body.targets.len() as u32);
+                body.targets.iter().for_each(|t| t.write_to(writer));
+            }
+            OperationBody::TextEdit(body) => {
+                writer.write_u8(TAG_TEXT_EDIT);
+                writer.write_u64(body.pos as u64);
+                writer.write_u64(body.len as u64);
+                writer.write_str(&body.text);
+                write_optional_element_id(writer, &body.target);
+                writer.write_u32(body.targets.len() as u32);
+                for (id, len) in body.targets.iter() {
+                    id.write_to(writer);
+                    writer.write_u32(*len);
+                }
+            }
+            OperationBody::SetAdd(body) => {
+                writer.write_u8(TAG_SET_ADD);
+                body.element.write_to(writer);
+            }
+            OperationBody::SetRemove(body) => {
+                writer.write_u8(TAG_SET_REMOVE);
+                body.element.write_to(writer);
+                writer.write_u32(body.tags.len() as u32);
+                body.tags.iter().for_each(|t| t.write_to(writer));
+            }
+        }
+    }
+
+    pub(crate) fn read_from(reader: &mut BytesReader) -> Result<Self, DatatypeError> {
+        match reader.read_u8()? {
+            #[cfg(test)]
+            TAG_DELAY_4_TEST => Ok(OperationBody::Delay4Test(Delay4TestBody::new(
+                reader.read_u64()?,
+                reader.read_bool()?,
+            ))),
+            TAG_COUNTER_INCREASE => Ok(OperationBody::CounterIncrease(CounterIncreaseBody::new(
+                reader.read_i64()?,
+            ))),
+            TAG_VARIABLE_SET => Ok(OperationBody::VariableSet(VariableSetBody::new(
+                Primitive::read_from(reader)?,
+            ))),
+            TAG_LIST_INSERT => {
+                let pos = reader.read_u64()? as usize;
+                let target = read_optional_element_id(reader)?;
+                let len = reader.read_u32()?;
+                let values = (0..len)
+                    .map(|_| Primitive::read_from(reader))
+                    .collect::<Result<_, _>>()?;
+                Ok(OperationBody::ListInsert(ListInsertBody {
+                    pos,
+                    target,
+                    values,
+                }))
+            }
+            TAG_LIST_DELETE => {
+                let pos = reader.read_u64()? as usize;
+                let len = reader.read_u64()? as usize;
+                let count = reader.read_u32()?;
+                let targets = (0..count)
+                    .map(|_| ElementId::read_from(reader))
+                    .collect::<Result<_, _>>()?;
+                Ok(OperationBody::ListDelete(ListDeleteBody {
+                    pos,
+                    len,
+                    targets,
+                }))
+            }
+            TAG_LIST_UPDATE => Ok(OperationBody::ListUpdate(ListUpdateBody {
+                pos: reader.read_u64()? as usize,
+                target: read_optional_element_id(reader)?,
+                value: Primitive::read_from(reader)?,
+            })),
+            TAG_MAP_PUT => {
+                let len = reader.read_u32()?;
+                let entries = (0..len)
+                    .map(|_| Ok((reader.read_string()?, Primitive::read_from(reader)?)))
+                    .collect::<Result<_, DatatypeError>>()?;
+                Ok(OperationBody::MapPut(MapPutBody { entries }))
+            }
+            TAG_MAP_REMOVE => Ok(OperationBody::MapRemove(MapRemoveBody::new(
+                reader.read_string()?,
+            ))),
+            TAG_DOCUMENT_PUT_TO_OBJECT => {
+                let path = reader.read_string()?;
+                let target = read_optional_element_id(reader)?;
+                let len = reader.read_u32()?;
+                let entries = (0..len)
+                    .map(|_| Ok((reader.read_string()?, DocumentValue::read_from(reader)?)))
+                    .collect::<Result<_, DatatypeError>>()?;
+                Ok(OperationBody::DocumentPutToObject(
+                    DocumentPutToObjectBody {
+                        path,
+                        target,
+                        entries,
+                        replace: reader.read_bool()?,
+                    },
+                ))
+            }
+            TAG_DOCUMENT_REMOVE_IN_OBJECT => Ok(OperationBody::DocumentRemoveInObject(
+                DocumentRemoveInObjectBody {
+                    path: reader.read_string()?,
+                    target: read_optional_element_id(reader)?,
+                    key: reader.read_string()?,
+                },
+            )),
+            TAG_DOCUMENT_INSERT_TO_ARRAY => {
+                let path = reader.read_string()?;
+                let pos = reader.read_u64()? as usize;
+                let target = read_optional_element_id(reader)?;
+                let prev = read_optional_element_id(reader)?;
+                let len = reader.read_u32()?;
+                let values = (0..len)
+                    .map(|_| DocumentValue::read_from(reader))
+                    .collect::<Result<_, _>>()?;
+                Ok(OperationBody::DocumentInsertToArray(
+                    DocumentInsertToArrayBody {
+                        path,
+                        pos,
+                        target,
+                        prev,
+                        values,
+                    },
+                ))
+            }
+            TAG_DOCUMENT_DELETE_IN_ARRAY => {
+                let path = reader.read_string()?;
+                let pos = reader.read_u64()? as usize;
+                let len = reader.read_u64()? as usize;
+                let target = read_optional_element_id(reader)?;
+                let count = reader.read_u32()?;
+                let targets = (0..count)
+                    .map(|_| ElementId::read_from(reader))
+                    .collect::<Result<_, _>>()?;
+                Ok(OperationBody::DocumentDeleteInArray(
+                    DocumentDeleteInArrayBody {
+                        path,
+                        pos,
+                        len,
+                        target,
+                        targets,
+                    },
+                ))
+            }
+            TAG_TEXT_EDIT => {
+                let pos = reader.read_u64()? as usize;
+                let len = reader.read_u64()? as usize;
+                let text = reader.read_string()?;
+                let target = read_optional_element_id(reader)?;
+                let count = reader.read_u32()?;
+                let targets = (0..count)
+                    .map(|_| Ok((ElementId::read_from(reader)?, reader.read_u32()?)))
+                    .collect::<Result<_, DatatypeError>>()?;
+                Ok(OperationBody::TextEdit(TextEditBody {
+                    pos,
+                    len,
+                    text,
+                    target,
+                    targets,
+                }))
+            }
+            TAG_SET_ADD => Ok(OperationBody::SetAdd(SetAddBody::new(
+                SetElement::read_from(reader)?,
+            ))),
+            TAG_SET_REMOVE => {
+                let element = SetElement::read_from(reader)?;
+                let count = reader.read_u32()?;
+                let tags = (0..count)
+                    .map(|_| ElementId::read_from(reader))
+                    .collect::<Result<_, _>>()?;
+                Ok(OperationBody::SetRemove(SetRemoveBody { element, tags }))
+            }
+            tag => Err(DatatypeError::FailedToDeserialize(format!(
+                "unknown operation body tag: {tag}"
+            ))),
+        }
+    }
+}
+
+fn write_optional_element_id(writer: &mut BytesWriter, id: &Option<ElementId>) {
+    writer.write_bool(id.is_some());
+    if let Some(id) = id {
+        id.write_to(writer);
+    }
+}
+
+fn read_optional_element_id(reader: &mut BytesReader) -> Result<Option<ElementId>, DatatypeError> {
+    if reader.read_bool()? {
+        Ok(Some(ElementId::read_from(reader)?))
+    } else {
+        Ok(None)
+    }
+}
+
 impl MemoryMeasurable for OperationBody {
     fn size(&self) -> usize {
         match self {
             #[cfg(test)]
             OperationBody::Delay4Test(body) => body.size(),
             OperationBody::CounterIncrease(body) => body.size(),
+            OperationBody::VariableSet(body) => body.size(),
+            OperationBody::ListInsert(body) => body.size(),
+            OperationBody::ListDelete(body) => body.size(),
+            OperationBody::ListUpdate(body) => body.size(),
+            OperationBody::MapPut(body) => body.size(),
+            OperationBody::MapRemove(body) => body.size(),
+            OperationBody::DocumentPutToObject(body) => body.size(),
+            OperationBody::DocumentRemoveInObject(body) => body.size(),
+            OperationBody::DocumentInsertToArray(body) => body.size(),
+            OperationBody::DocumentDeleteInArray(body) => body.size(),
+            OperationBody::TextEdit(body) => body.size(),
+            OperationBody::SetAdd(body) => body.size(),
+            OperationBody::SetRemove(body) => body.size(),
         }
     }
 }
 
 #[cfg(test)]
-#[derive(Debug, Clone, Display)]
+#[derive(Debug, Clone, PartialEq, Display)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[display("")]
 pub struct Delay4TestBody {
     duration_ms: u64,
@@ -60,7 +400,8 @@ impl MemoryMeasurable for Delay4TestBody {
     }
 }
 
-#[derive(Debug, Clone, Display)]
+#[derive(Debug, Clone, PartialEq, Display)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[display("(delta={delta})")]
 pub struct CounterIncreaseBody {
     pub delta: i64,
@@ -78,13 +419,381 @@ impl MemoryMeasurable for CounterIncreaseBody {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Display)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[display("({value})")]
+pub struct VariableSetBody {
+    pub value: Primitive,
+}
+
+impl VariableSetBody {
+    pub fn new(value: Primitive) -> Self {
+        Self { value }
+    }
+}
+
+impl MemoryMeasurable for VariableSetBody {
+    fn size(&self) -> usize {
+        self.value.size()
+    }
+}
+
+const ELEMENT_ID_SIZE: usize = size_of::<ElementId>();
+
+/// Inserts `values` at `pos`.
+///
+/// `target` is the element after which the values are inserted (`None` for the head);
+/// it is resolved from `pos` when the operation is executed locally.
+#[derive(Debug, Clone, PartialEq, Display)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[display("(pos={pos}, target={target:?}, values={values:?})")]
+pub struct ListInsertBody {
+    pub pos: usize,
+    pub target: Option<ElementId>,
+    pub values: Vec<Primitive>,
+}
+
+impl ListInsertBody {
+    pub fn new(pos: usize, values: Vec<Primitive>) -> Self {
+        Self {
+            pos,
+            target: None,
+            values,
+        }
+    }
+}
+
+impl MemoryMeasurable for ListInsertBody {
+    fn size(&self) -> usize {
+        size_of::<usize>()
+            + size_of::<Option<ElementId>>()
+            + self.values.iter().map(|v| v.size()).sum::<usize>()
+    }
+}
+
+/// Deletes `len` elements from `pos`.
+///
+/// `targets` are the deleted elements resolved when the operation is executed locally.
+#[derive(Debug, Clone, PartialEq, Display)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[display("(pos={pos}, len={len}, targets={targets:?})")]
+pub struct ListDeleteBody {
+    pub pos: usize,
+    pub len: usize,
+    pub targets: Vec<ElementId>,
+}
+
+impl ListDeleteBody {
+    pub fn new(pos: usize, len: usize) -> Self {
+        Self {
+            pos,
+            len,
+            targets: vec![],
+        }
+    }
+}
+
+impl MemoryMeasurable for ListDeleteBody {
+    fn size(&self) -> usize {
+        size_of::<usize>() * 2 + ELEMENT_ID_SIZE * self.targets.len()
+    }
+}
+
+/// Updates the element at `pos` with `value`.
+///
+/// `target` is the updated element resolved when the operation is executed locally.
+#[derive(Debug, Clone, PartialEq, Display)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[display("(pos={pos}, target={target:?}, value={value})")]
+pub struct ListUpdateBody {
+    pub pos: usize,
+    pub target: Option<ElementId>,
+    pub value: Primitive,
+}
+
+impl ListUpdateBody {
+    pub fn new(pos: usize, value: Primitive) -> Self {
+        Self {
+            pos,
+            target: None,
+            value,
+        }
+    }
+}
+
+impl MemoryMeasurable for ListUpdateBody {
+    fn size(&self) -> usize {
+        size_of::<usize>() + size_of::<Option<ElementId>>() + self.value.size()
+    }
+}
+
+/// Puts the `entries` into a map; the last one wins among the same keys.
+#[derive(Debug, Clone, PartialEq, Display)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[display("(entries={entries:?})")]
+pub struct MapPutBody {
+    pub entries: Vec<(String, Primitive)>,
+}
+
+impl MapPutBody {
+    pub fn new(entries: Vec<(String, Primitive)>) -> Self {
+        Self { entries }
+    }
+}
+
+impl MemoryMeasurable for MapPutBody {
+    fn size(&self) -> usize {
+        self.entries
+            .iter()
+            .map(|(k, v)| size_of::<String>() + k.len() + v.size())
+            .sum()
+    }
+}
+
+/// Removes `key` from a map.
+#[derive(Debug, Clone, PartialEq, Display)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[display("(key={key})")]
+pub struct MapRemoveBody {
+    pub key: String,
+}
+
+impl MapRemoveBody {
+    pub fn new(key: String) -> Self {
+        Self { key }
+    }
+}
+
+impl MemoryMeasurable for MapRemoveBody {
+    fn size(&self) -> usize {
+        size_of::<String>() + self.key.len()
+    }
+}
+
+/// Puts the `entries` into the object at `path`; the last one wins among the same keys.
+///
+/// If `replace` is true, the other keys of the object are removed. `target` is the object
+/// resolved from `path` when the operation is executed locally.
+#[derive(Debug, Clone, PartialEq, Display)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[display("(path={path:?}, target={target:?}, entries={entries:?}, replace={replace})")]
+pub struct DocumentPutToObjectBody {
+    pub path: String,
+    pub target: Option<ElementId>,
+    pub entries: Vec<(String, DocumentValue)>,
+    pub replace: bool,
+}
+
+impl DocumentPutToObjectBody {
+    pub fn new(path: String, entries: Vec<(String, DocumentValue)>, replace: bool) -> Self {
+        Self {
+            path,
+            target: None,
+            entries,
+            replace,
+        }
+    }
+}
+
+impl MemoryMeasurable for DocumentPutToObjectBody {
+    fn size(&self) -> usize {
+        size_of::<Self>()
+            + self.path.len()
+            + self
+                .entries
+                .iter()
+                .map(|(k, v)| size_of::<String>() + k.len() + v.size())
+                .sum::<usize>()
+    }
+}
+
+/// Removes `key` from the object at `path`.
+///
+/// `target` is the object resolved from `path` when the operation is executed locally.
+#[derive(Debug, Clone, PartialEq, Display)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[display("(path={path:?}, target={target:?}, key={key})")]
+pub struct DocumentRemoveInObjectBody {
+    pub path: String,
+    pub target: Option<ElementId>,
+    pub key: String,
+}
+
+impl DocumentRemoveInObjectBody {
+    pub fn new(path: String, key: String) -> Self {
+        Self {
+            path,
+            target: None,
+            key,
+        }
+    }
+}
+
+impl MemoryMeasurable for DocumentRemoveInObjectBody {
+    fn size(&self) -> usize {
+        size_of::<Self>() + self.path.len() + self.key.len()
+    }
+}
+
+/// Inserts `values` at `pos` of the array at `path`.
+///
+/// `target` is the array and `prev` is the element after which the values are inserted
+/// (`None` for the head); both are resolved when the operation is executed locally.
+#[derive(Debug, Clone, PartialEq, Display)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[display("(path={path:?}, pos={pos}, target={target:?}, prev={prev:?}, values={values:?})")]
+pub struct DocumentInsertToArrayBody {
+    pub path: String,
+    pub pos: usize,
+    pub target: Option<ElementId>,
+    pub prev: Option<ElementId>,
+    pub values: Vec<DocumentValue>,
+}
+
+impl DocumentInsertToArrayBody {
+    pub fn new(path: String, pos: usize, values: Vec<DocumentValue>) -> Self {
+        Self {
+            path,
+            pos,
+            target: None,
+            prev: None,
+            values,
+        }
+    }
+}
+
+impl MemoryMeasurable for DocumentInsertToArrayBody {
+    fn size(&self) -> usize {
+        size_of::<Self>() + self.path.len() + self.values.iter().map(|v| v.size()).sum::<usize>()
+    }
+}
+
+/// Deletes `len` elements from `pos` of the array at `path`.
+///
+/// `target` is the array and `targets` are the deleted elements; both are resolved when
+/// the operation is executed locally.
+#[derive(Debug, Clone, PartialEq, Display)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[display("(path={path:?}, pos={pos}, len={len}, target={target:?}, targets={targets:?})")]
+pub struct DocumentDeleteInArrayBody {
+    pub path: String,
+    pub pos: usize,
+    pub len: usize,
+    pub target: Option<ElementId>,
+    pub targets: Vec<ElementId>,
+}
+
+impl DocumentDeleteInArrayBody {
+    pub fn new(path: String, pos: usize, len: usize) -> Self {
+        Self {
+            path,
+            pos,
+            len,
+            target: None,
+            targets: vec![],
+        }
+    }
+}
+
+impl MemoryMeasurable for DocumentDeleteInArrayBody {
+    fn size(&self) -> usize {
+        size_of::<Self>() + self.path.len() + ELEMENT_ID_SIZE * self.targets.len()
+    }
+}
+
+/// Deletes `len` characters from `pos` of a text, and then inserts `text` at `pos`.
+///
+/// `target` is the character after which `text` is inserted (`None` for the head), and
+/// `targets` are the deleted characters as spans of a start and a length; both are
+/// resolved when the operation is executed locally.
+#[derive(Debug, Clone, PartialEq, Display)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[display("(pos={pos}, len={len}, text={text:?}, target={target:?}, targets={targets:?})")]
+pub struct TextEditBody {
+    pub pos: usize,
+    pub len: usize,
+    pub text: String,
+    pub target: Option<ElementId>,
+    pub targets: Vec<(ElementId, u32)>,
+}
+
+impl TextEditBody {
+    pub fn new(pos: usize, len: usize, text: String) -> Self {
+        Self {
+            pos,
+            len,
+            text,
+            target: None,
+            targets: vec![],
+        }
+    }
+}
+
+impl MemoryMeasurable for TextEditBody {
+    fn size(&self) -> usize {
+        size_of::<Self>()
+            + self.text.len()
+            + (ELEMENT_ID_SIZE + size_of::<u32>()) * self.targets.len()
+    }
+}
+
+/// Adds `element` to a set, tagged with the id of this operation.
+#[derive(Debug, Clone, PartialEq, Display)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[display("(element={element})")]
+pub struct SetAddBody {
+    pub element: SetElement,
+}
+
+impl SetAddBody {
+    pub fn new(element: SetElement) -> Self {
+        Self { element }
+    }
+}
+
+impl MemoryMeasurable for SetAddBody {
+    fn size(&self) -> usize {
+        self.element.size()
+    }
+}
+
+/// Removes `element` from a set.
+///
+/// `tags` are the adds of the element observed when the operation is executed locally;
+/// the concurrent adds not among them survive the remove.
+#[derive(Debug, Clone, PartialEq, Display)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[display("(element={element}, tags={tags:?})")]
+pub struct SetRemoveBody {
+    pub element: SetElement,
+    pub tags: Vec<ElementId>,
+}
+
+impl SetRemoveBody {
+    pub fn new(element: SetElement) -> Self {
+        Self {
+            element,
+            tags: vec![],
+        }
+    }
+}
+
+impl MemoryMeasurable for SetRemoveBody {
+    fn size(&self) -> usize {
+        self.element.size() + ELEMENT_ID_SIZE * self.tags.len()
+    }
+}
+
 #[cfg(test)]
 mod tests_operation_body {
     use tracing::info;
 
-    use crate::operations::{
-        MemoryMeasurable,
-        body::{CounterIncreaseBody, Delay4TestBody, OperationBody},
+    use crate::{
+        Primitive,
+        operations::{
+            MemoryMeasurable,
+            body::{CounterIncreaseBody, Delay4TestBody, OperationBody, VariableSetBody},
+        },
     };
 
     #[test]
@@ -101,5 +810,7 @@ mod tests_operation_body {
         assert_eq!(body.size(), size_of::<i64>());
         let body = OperationBody::Delay4Test(Delay4TestBody::new(123, true));
         assert_eq!(body.size(), size_of::<u64>() + size_of::<bool>());
+        let body = OperationBody::VariableSet(VariableSetBody::new("abc".into()));
+        assert_eq!(body.size(), size_of::<Primitive>() + 3);
     }
 }
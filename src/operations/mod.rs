@@ -1,18 +1,32 @@
 use std::{
     fmt::{Debug, Display, Formatter},
-    time::SystemTime,
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
 use chrono::Local;
 
 #[cfg(test)]
 use crate::operations::body::Delay4TestBody;
-use crate::operations::body::{CounterIncreaseBody, OperationBody};
+use crate::{
+    DatatypeError,
+    operations::body::{
+        CounterIncreaseBody, DocumentDeleteInArrayBody, DocumentInsertToArrayBody,
+        DocumentPutToObjectBody, DocumentRemoveInObjectBody, ListDeleteBody, ListInsertBody,
+        ListUpdateBody, MapPutBody, MapRemoveBody, OperationBody, SetAddBody, SetRemoveBody,
+        TextEditBody, VariableSetBody,
+    },
+    types::{document_value::DocumentValue, primitive::Primitive, set_element::SetElement},
+    utils::{
+        bytes::{BytesReader, BytesWriter},
+        codec,
+    },
+};
 
 pub mod body;
 pub mod transaction;
 
-#[derive(Clone)]
+#[derive(Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Operation {
     pub lamport: u64,
     pub body: OperationBody,
@@ -38,6 +52,74 @@ impl Operation {
         )))
     }
 
+    pub fn new_variable_set(value: Primitive) -> Self {
+        Self::new(OperationBody::VariableSet(VariableSetBody::new(value)))
+    }
+
+    pub fn new_list_insert(pos: usize, values: Vec<Primitive>) -> Self {
+        Self::new(OperationBody::ListInsert(ListInsertBody::new(pos, values)))
+    }
+
+    pub fn new_list_delete(pos: usize, len: usize) -> Self {
+        Self::new(OperationBody::ListDelete(ListDeleteBody::new(pos, len)))
+    }
+
+    pub fn new_list_update(pos: usize, value: Primitive) -> Self {
+        Self::new(OperationBody::ListUpdate(ListUpdateBody::new(pos, value)))
+    }
+
+    pub fn new_map_put(entries: Vec<(String, Primitive)>) -> Self {
+        Self::new(OperationBody::MapPut(MapPutBody::new(entries)))
+    }
+
+    pub fn new_map_remove(key: String) -> Self {
+        Self::new(OperationBody::MapRemove(MapRemoveBody::new(key)))
+    }
+
+    pub fn new_document_put_to_object(
+        path: String,
+        entries: Vec<(String, DocumentValue)>,
+        replace: bool,
+    ) -> Self {
+        Self::new(OperationBody::DocumentPutToObject(
+            DocumentPutToObjectBody::new(path, entries, replace),
+        ))
+    }
+
+    pub fn new_document_remove_in_object(path: String, key: String) -> Self {
+        Self::new(OperationBody::DocumentRemoveInObject(
+            DocumentRemoveInObjectBody::new(path, key),
+        ))
+    }
+
+    pub fn new_document_insert_to_array(
+        path: String,
+        pos: usize,
+        values: Vec<DocumentValue>,
+    ) -> Self {
+        Self::new(OperationBody::DocumentInsertToArray(
+            DocumentInsertToArrayBody::new(path, pos, values),
+        ))
+    }
+
+    pub fn new_document_delete_in_array(path: String, pos: usize, len: usize) -> Self {
+        Self::new(OperationBody::DocumentDeleteInArray(
+            DocumentDeleteInArrayBody::new(path, pos, len),
+        ))
+    }
+
+    pub fn new_text_edit(pos: usize, len: usize, text: String) -> Self {
+        Self::new(OperationBody::TextEdit(TextEditBody::new(pos, len, text)))
+    }
+
+    pub fn new_set_add(element: SetElement) -> Self {
+        Self::new(OperationBody::SetAdd(SetAddBody::new(element)))
+    }
+
+    pub fn new_set_remove(element: SetElement) -> Self {
+        Self::new(OperationBody::SetRemove(SetRemoveBody::new(element)))
+    }
+
     #[cfg(test)]
     pub fn new_delay_for_test(duration_ms: u64, success: bool) -> Self {
         Self::new(OperationBody::Delay4Test(Delay4TestBody::new(
@@ -49,6 +131,47 @@ impl Operation {
     pub fn set_lamport(&mut self, lamport: u64) {
         self.lamport = lamport;
     }
+
+    /// Encodes the operation into the binary wire format.
+    pub fn encode(&self) -> Vec<u8> {
+        codec::encode(codec::KIND_OPERATION, |w| self.write_to(w))
+    }
+
+    /// Decodes an operation from the binary wire format.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DatatypeError::FailedToDeserialize`] if `bytes` is not a valid encoded
+    /// operation.
+    pub fn decode(bytes: &[u8]) -> Result<Self, DatatypeError> {
+        codec::decode(bytes, codec::KIND_OPERATION, Self::read_from)
+    }
+
+    pub(crate) fn write_to(&self, writer: &mut BytesWriter) {
+        let at = self.at.duration_since(UNIX_EPOCH).unwrap_or_default();
+        writer.write_u64(self.lamport);
+        writer.write_u64(at.as_secs());
+        writer.write_u32(at.subsec_nanos());
+        self.body.write_to(writer);
+    }
+
+    pub(crate) fn read_from(reader: &mut BytesReader) -> Result<Self, DatatypeError> {
+        let lamport = reader.read_u64()?;
+        let secs = reader.read_u64()?;
+        let nanos = reader.read_u32()?;
+        // checked, since Duration::new panics if the nanoseconds carry over u64::MAX secs.
+        let at = Some(nanos)
+            .filter(|nanos| *nanos < 1_000_000_000)
+            .and_then(|nanos| UNIX_EPOCH.checked_add(Duration::new(secs, nanos)))
+            .ok_or_else(|| {
+                DatatypeError::FailedToDeserialize(format!("invalid time: {secs}.{nanos}"))
+            })?;
+        Ok(Self {
+            lamport,
+            body: OperationBody::read_from(reader)?,
+            at,
+        })
+    }
 }
 
 impl Debug for Operation {
@@ -78,9 +201,14 @@ impl MemoryMeasurable for Operation {
 mod tests_operations {
     use std::time::SystemTime;
 
+    use rstest::rstest;
     use tracing::info;
 
-    use crate::operations::{MemoryMeasurable, Operation};
+    use crate::{
+        DatatypeError, Primitive,
+        operations::{MemoryMeasurable, Operation, body::OperationBody},
+        types::{element_id::ElementId, operation_id::OperationId, uid::Cuid},
+    };
 
     #[test]
     fn can_new_and_print_operations() {
@@ -98,4 +226,78 @@ mod tests_operations {
         let op = Operation::new_delay_for_test(1, true);
         assert_eq!(op.size(), constant_size + op.body.size());
     }
+
+    fn new_list_operations() -> Vec<Operation> {
+        let op_id = OperationId::new_with_cuid(&Cuid::new());
+        let mut insert = Operation::new_list_insert(1, vec![1.into(), "a".into()]);
+        let mut delete = Operation::new_list_delete(0, 2);
+        let mut update = Operation::new_list_update(3, vec![0u8, 1].into());
+        match (&mut insert.body, &mut delete.body, &mut update.body) {
+            (
+                OperationBody::ListInsert(i),
+                OperationBody::ListDelete(d),
+                OperationBody::ListUpdate(u),
+            ) => {
+                i.target = Some(ElementId::new(&op_id, 0));
+                d.targets = vec![ElementId::new(&op_id, 1), ElementId::new(&op_id, 2)];
+                u.target = Some(ElementId::new(&op_id, 3));
+            }
+            _ => unreachable!(),
+        }
+        vec![
+            insert,
+            delete,
+            update,
+            Operation::new_list_insert(0, vec![]),
+        ]
+    }
+
+    #[test]
+    fn can_encode_and_decode_operations() {
+        let mut ops = vec![
+            Operation::new_counter_increase(-3),
+            Operation::new_variable_set(2.5.into()),
+            Operation::new_variable_set(Primitive::Null),
+            Operation::new_delay_for_test(10, false),
+        ];
+        ops.extend(new_list_operations());
+        let mut edit = Operation::new_text_edit(2, 3, "yam🍠".into());
+        if let OperationBody::TextEdit(e) = &mut edit.body {
+            let op_id = OperationId::new_with_cuid(&Cuid::new());
+            e.target = Some(ElementId::new(&op_id, 1));
+            e.targets = vec![(ElementId::new(&op_id, 2), 3)];
+        }
+        ops.push(edit);
+        ops.push(Operation::new_set_add(vec![0u8, 1].into()));
+        let mut remove = Operation::new_set_remove("yam".into());
+        if let OperationBody::SetRemove(r) = &mut remove.body {
+            let op_id = OperationId::new_with_cuid(&Cuid::new());
+            r.tags = vec![ElementId::new(&op_id, 0)];
+        }
+        ops.push(remove);
+        for (i, mut op) in ops.into_iter().enumerate() {
+            op.set_lamport(i as u64 + 1);
+            let decoded = Operation::decode(&op.encode()).unwrap();
+            info!("{decoded}");
+            assert_eq!(decoded, op);
+        }
+    }
+
+    #[rstest]
+    #[case::unknown_body_tag(|b: &mut Vec<u8>| b[24] = 99)]
+    #[case::truncated(|b: &mut Vec<u8>| b.truncate(b.len() - 1))]
+    #[case::trailing(|b: &mut Vec<u8>| b.push(0))]
+    #[case::invalid_bool(|b: &mut Vec<u8>| b[33] = 2)]
+    #[case::invalid_nanos(|b: &mut Vec<u8>| b[20..24].copy_from_slice(&1_000_000_000u32.to_le_bytes()))]
+    #[case::overflowing_time(|b: &mut Vec<u8>| b[12..24].fill(0xff))]
+    fn can_reject_malformed_operations(#[case] corrupt: fn(&mut Vec<u8>)) {
+        let mut op = Operation::new_list_update(3, 1.into());
+        op.set_lamport(1);
+        let mut bytes = op.encode();
+        corrupt(&mut bytes);
+        assert_eq!(
+            Operation::decode(&bytes).unwrap_err(),
+            DatatypeError::FailedToDeserialize("".into())
+        );
+    }
 }
@@ -1,23 +1,39 @@
 use std::fmt::{Debug, Display, Formatter};
 
 use crate::{
+    DatatypeError,
     operations::{MemoryMeasurable, Operation},
     types,
-    types::{operation_id::OperationId, uid::Cuid},
+    types::{
+        operation_id::OperationId,
+        uid::{Cuid, Uid},
+    },
+    utils::{
+        bytes::{BytesReader, BytesWriter},
+        codec,
+    },
 };
 
 const TRANSACTION_CONSTANT_SIZE: usize = size_of::<Vec<Operation>>() // operations
     + types::uid::UID_LEN // cuid
     + size_of::<Option<String>>() // tag
+    + size_of::<Option<Uid>>() // group
     + size_of::<u64>() // cseq
     + size_of::<u64>() // sseq
     + size_of::<bool>(); // event
 
+#[derive(Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Transaction {
     cuid: Cuid,
     cseq: u64,
+    #[cfg_attr(feature = "serde", serde(default))]
     sseq: u64,
+    #[cfg_attr(feature = "serde", serde(default))]
     tag: Option<String>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    group: Option<Uid>,
+    #[cfg_attr(feature = "serde", serde(default))]
     event: bool,
     operations: Vec<Operation>,
 }
@@ -27,12 +43,25 @@ impl Transaction {
         &self.cuid
     }
 
+    pub fn cseq(&self) -> u64 {
+        self.cseq
+    }
+
+    pub fn sseq(&self) -> u64 {
+        self.sseq
+    }
+
+    pub fn set_sseq(&mut self, sseq: u64) {
+        self.sseq = sseq;
+    }
+
     pub fn new(op_id: &mut OperationId) -> Self {
         Self {
             cuid: op_id.cuid.clone(),
             cseq: op_id.next_cseq(),
             sseq: 0,
             tag: None,
+            group: None,
             event: false,
             operations: vec![],
         }
@@ -44,10 +73,30 @@ impl Transaction {
         op_id
     }
 
+    pub fn tag(&self) -> Option<&str> {
+        self.tag.as_deref()
+    }
+
     pub fn set_tag(&mut self, tag: Option<String>) {
         self.tag = tag;
     }
 
+    /// Returns the identifier shared by the transactions of several datatypes committed
+    /// together by [`Client::transaction`](crate::Client::transaction).
+    pub fn group(&self) -> Option<&Uid> {
+        self.group.as_ref()
+    }
+
+    pub fn set_group(&mut self, group: Option<Uid>) {
+        self.group = group;
+    }
+
+    /// Returns whether the transaction is marked as an event, which other clients receive
+    /// by [`Datatype::on_remote_event`](crate::Datatype::on_remote_event).
+    pub fn is_event(&self) -> bool {
+        self.event
+    }
+
     pub fn set_event(&mut self, event: bool) {
         self.event = event;
     }
@@ -56,9 +105,74 @@ impl Transaction {
         self.operations.push(op);
     }
 
-    pub fn iter(&self) -> std::slice::Iter<Operation> {
+    pub fn iter(&self) -> std::slice::Iter<'_, Operation> {
         self.operations.iter()
     }
+
+    /// Keeps the first `len` operations, dropping the rest.
+    pub fn truncate(&mut self, len: usize) {
+        self.operations.truncate(len);
+    }
+
+    /// Encodes the transaction into the binary wire format.
+    pub fn encode(&self) -> Vec<u8> {
+        codec::encode(codec::KIND_TRANSACTION, |w| self.write_to(w))
+    }
+
+    /// Decodes a transaction from the binary wire format.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DatatypeError::FailedToDeserialize`] if `bytes` is not a valid encoded
+    /// transaction.
+    pub fn decode(bytes: &[u8]) -> Result<Self, DatatypeError> {
+        codec::decode(bytes, codec::KIND_TRANSACTION, Self::read_from)
+    }
+
+    pub(crate) fn write_to(&self, writer: &mut BytesWriter) {
+        self.cuid.write_to(writer);
+        writer.write_u64(self.cseq);
+        writer.write_u64(self.sseq);
+        writer.write_bool(self.tag.is_some());
+        if let Some(tag) = &self.tag {
+            writer.write_str(tag);
+        }
+        writer.write_bool(self.group.is_some());
+        if let Some(group) = &self.group {
+            group.write_to(writer);
+        }
+        writer.write_bool(self.event);
+        writer.write_u32(self.operations.len() as u32);
+        self.operations.iter().for_each(|op| op.write_to(writer));
+    }
+
+    pub(crate) fn read_from(reader: &mut BytesReader) -> Result<Self, DatatypeError> {
+        let cuid = Cuid::read_from(reader)?;
+        let cseq = reader.read_u64()?;
+        let sseq = reader.read_u64()?;
+        let tag = match reader.read_bool()? {
+            true => Some(reader.read_string()?),
+            false => None,
+        };
+        let group = match reader.version() >= codec::WIRE_VERSION_TX_GROUP && reader.read_bool()? {
+            true => Some(Uid::read_from(reader)?),
+            false => None,
+        };
+        let event = reader.read_bool()?;
+        let len = reader.read_u32()?;
+        let operations = (0..len)
+            .map(|_| Operation::read_from(reader))
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            cuid,
+            cseq,
+            sseq,
+            tag,
+            group,
+            event,
+            operations,
+        })
+    }
 }
 
 impl Debug for Transaction {
@@ -72,6 +186,10 @@ impl Display for Transaction {
             Some(tag) => format!("🔖:{tag}"),
             None => String::new(),
         };
+        let group_arg = match &self.group {
+            Some(group) => format!("👥:{group}"),
+            None => String::new(),
+        };
         let event_arg = if self.event { " ✅ " } else { " " };
         let mut lamport_arg = String::from("[]");
         if !self.operations.is_empty() {
@@ -84,8 +202,8 @@ impl Display for Transaction {
             }
         }
         f.write_fmt(format_args!(
-            "TX({}{}{}:{}:{}:{})",
-            tag_arg, event_arg, self.cuid, self.cseq, self.sseq, lamport_arg,
+            "TX({}{}{}{}:{}:{}:{})",
+            tag_arg, group_arg, event_arg, self.cuid, self.cseq, self.sseq, lamport_arg,
         ))
     }
 }
@@ -97,7 +215,11 @@ impl MemoryMeasurable for Transaction {
             Some(s) => s.len(),
             None => 0,
         };
-        TRANSACTION_CONSTANT_SIZE + tag_size + op_size
+        let group_size = match &self.group {
+            Some(_) => types::uid::UID_LEN,
+            None => 0,
+        };
+        TRANSACTION_CONSTANT_SIZE + tag_size + group_size + op_size
     }
 }
 
@@ -106,7 +228,12 @@ mod tests_transaction {
     use tracing::info;
 
     use super::{OperationId, TRANSACTION_CONSTANT_SIZE, Transaction};
-    use crate::operations::{MemoryMeasurable, Operation};
+    use crate::{
+        DatatypeError,
+        operations::{MemoryMeasurable, Operation},
+        types::uid::{Cuid, UID_LEN, Uid},
+        utils::codec,
+    };
 
     #[test]
     fn can_debug_and_display_transaction() {
@@ -114,6 +241,7 @@ mod tests_transaction {
         let mut tx = Transaction::new(&mut op_id);
         info!("{tx}");
         tx.set_tag(Some("tag1".to_string()));
+        tx.set_group(Some(Uid::new()));
         tx.set_event(true);
         info!("{tx}");
         let mut op1 = Operation::new_counter_increase(1);
@@ -143,5 +271,89 @@ mod tests_transaction {
         assert_eq!(tx.size(), TRANSACTION_CONSTANT_SIZE + 10 + op.size());
         tx.push_operation(op.clone());
         assert_eq!(tx.size(), TRANSACTION_CONSTANT_SIZE + 10 + op.size() * 2);
+        tx.set_group(Some(Uid::new()));
+        assert_eq!(
+            tx.size(),
+            TRANSACTION_CONSTANT_SIZE + 10 + UID_LEN + op.size() * 2
+        );
+    }
+
+    #[test]
+    fn can_encode_and_decode_transaction() {
+        let mut op_id = OperationId::new_with_cuid(&Cuid::new());
+        let mut tx = Transaction::new(&mut op_id);
+        assert_eq!(Transaction::decode(&tx.encode()).unwrap(), tx);
+
+        tx.set_tag(Some("tag🔖".to_string()));
+        tx.set_group(Some(Uid::new()));
+        tx.set_event(true);
+        tx.set_sseq(7);
+        for lamport in 1..=3 {
+            let mut op = Operation::new_variable_set(format!("v{lamport}").into());
+            op.set_lamport(lamport);
+            tx.push_operation(op);
+        }
+        let bytes = tx.encode();
+        let decoded = Transaction::decode(&bytes).unwrap();
+        info!("{decoded}");
+        assert_eq!(decoded, tx);
+
+        for len in [0, 4, 20, bytes.len() - 1] {
+            assert_eq!(
+                Transaction::decode(&bytes[..len]).unwrap_err(),
+                DatatypeError::FailedToDeserialize("".into())
+            );
+        }
+        assert!(Transaction::decode(&tx.get_op_id().encode()).is_err());
+    }
+
+    #[test]
+    fn can_decode_transaction_of_wire_version_1() {
+        // encoded before the group was added between the tag and the event flag.
+        let mut bytes = vec![b'S', b'Y', 1, codec::KIND_TRANSACTION];
+        bytes.extend_from_slice(&16u32.to_le_bytes());
+        bytes.extend_from_slice(b"0123456789abcdef"); // cuid
+        bytes.extend_from_slice(&3u64.to_le_bytes()); // cseq
+        bytes.extend_from_slice(&7u64.to_le_bytes()); // sseq
+        bytes.push(1);
+        bytes.extend_from_slice(&3u32.to_le_bytes());
+        bytes.extend_from_slice(b"tag");
+        bytes.push(1); // event
+        bytes.extend_from_slice(&0u32.to_le_bytes()); // operations
+
+        let tx = Transaction::decode(&bytes).unwrap();
+        assert_eq!(tx.cuid, Cuid::try_from("0123456789abcdef").unwrap());
+        assert_eq!((tx.cseq(), tx.sseq()), (3, 7));
+        assert_eq!(tx.tag(), Some("tag"));
+        assert_eq!(tx.group(), None);
+        assert!(tx.is_event());
+        assert!(tx.operations.is_empty());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn can_serialize_transaction_as_json() {
+        let mut op_id = OperationId::new_with_cuid(&Cuid::new());
+        let mut tx = Transaction::new(&mut op_id);
+        tx.set_tag(Some("tag1".to_string()));
+        let mut op = Operation::new_list_insert(0, vec![1.into(), "a".into()]);
+        op.set_lamport(1);
+        tx.push_operation(op);
+        tx.push_operation(Operation::new_counter_increase(2));
+
+        let json = serde_json::to_string(&tx).unwrap();
+        info!("{json}");
+        assert_eq!(serde_json::from_str::<Transaction>(&json).unwrap(), tx);
+
+        // optional fields can be omitted in dumps of older versions
+        let mut value = serde_json::to_value(&tx).unwrap();
+        let object = value.as_object_mut().unwrap();
+        for field in ["sseq", "tag", "group", "event"] {
+            object.remove(field);
+        }
+        let read = serde_json::from_value::<Transaction>(value).unwrap();
+        assert_eq!(read.cseq(), tx.cseq());
+        assert_eq!(read.tag, None);
+        assert_eq!(read.group(), None);
     }
 }
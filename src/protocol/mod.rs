@@ -0,0 +1,124 @@
+use std::{
+    fmt::{Display, Formatter},
+    sync::Arc,
+};
+
+use crate::{
+    DataType, DatatypeState,
+    operations::transaction::Transaction,
+    types::uid::{Cuid, Duid},
+};
+
+/// Checkpoint records how far a datatype has been synchronized with the SyncYam server.
+///
+/// - `sseq`: the last server sequence of the transactions received from the server.
+/// - `cseq`: the last client sequence of the local transactions acknowledged by the server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Checkpoint {
+    pub sseq: u64,
+    pub cseq: u64,
+}
+
+impl Checkpoint {
+    pub fn new(sseq: u64, cseq: u64) -> Self {
+        Self { sseq, cseq }
+    }
+
+    /// Merges `other` into this checkpoint, keeping the larger sequences.
+    pub fn sync(&mut self, other: &Self) {
+        self.sseq = self.sseq.max(other.sseq);
+        self.cseq = self.cseq.max(other.cseq);
+    }
+}
+
+impl Display for Checkpoint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "CP({}:{})", self.sseq, self.cseq)
+    }
+}
+
+/// PushPullPack carries the synchronization of a datatype between a client and the server.
+///
+/// From a client, `state` is the requested state (e.g., [`DatatypeState::DueToCreate`]),
+/// `checkpoint` is the client's checkpoint, and `transactions` are the pending local
+/// transactions. From the server, `state` is the resulting state, `checkpoint` is the
+/// updated checkpoint, and `transactions` are the transactions of other clients with their
+/// server sequences. `error` is set by the server if the request cannot be accepted.
+#[derive(Debug, Clone)]
+pub struct PushPullPack {
+    pub key: String,
+    pub duid: Duid,
+    pub r#type: DataType,
+    pub state: DatatypeState,
+    pub checkpoint: Checkpoint,
+    pub transactions: Vec<Arc<Transaction>>,
+    pub error: Option<String>,
+}
+
+impl Display for PushPullPack {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "PushPull({}:{:?}:{:?}:{}:{} txs",
+            self.key,
+            self.r#type,
+            self.state,
+            self.checkpoint,
+            self.transactions.len()
+        )?;
+        if let Some(error) = &self.error {
+            write!(f, ":{error}")?;
+        }
+        f.write_str(")")
+    }
+}
+
+/// A request from a client to synchronize its datatypes in a collection.
+#[derive(Debug, Clone)]
+pub struct PushPullRequest {
+    pub collection: String,
+    pub cuid: Cuid,
+    pub packs: Vec<PushPullPack>,
+}
+
+/// A response from the server for a [`PushPullRequest`].
+#[derive(Debug, Clone, Default)]
+pub struct PushPullResponse {
+    pub packs: Vec<PushPullPack>,
+}
+
+#[cfg(test)]
+mod tests_protocol {
+    use tracing::info;
+
+    use crate::{
+        DataType, DatatypeState,
+        protocol::{Checkpoint, PushPullPack},
+        types::uid::Duid,
+    };
+
+    #[test]
+    fn can_sync_checkpoints() {
+        let mut cp1 = Checkpoint::new(1, 5);
+        cp1.sync(&Checkpoint::new(3, 2));
+        assert_eq!(cp1, Checkpoint::new(3, 5));
+        assert_eq!(cp1.to_string(), "CP(3:5)");
+    }
+
+    #[test]
+    fn can_display_push_pull_pack() {
+        let mut pack = PushPullPack {
+            key: "k1".to_owned(),
+            duid: Duid::new(),
+            r#type: DataType::Counter,
+            state: DatatypeState::DueToCreate,
+            checkpoint: Default::default(),
+            transactions: vec![],
+            error: None,
+        };
+        info!("{pack}");
+        assert!(pack.to_string().ends_with("0 txs)"));
+        pack.error = Some("error".to_owned());
+        assert!(pack.to_string().ends_with(":error)"));
+    }
+}
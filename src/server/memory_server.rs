@@ -0,0 +1,652 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+use parking_lot::Mutex;
+use tracing::{instrument, warn};
+
+use crate::{
+    ClientError, DataType, DatatypeState,
+    clients::transport::{Notifier, Transport},
+    operations::transaction::Transaction,
+    protocol::{Checkpoint, PushPullPack, PushPullRequest, PushPullResponse},
+    types::uid::{Cuid, Duid},
+};
+
+struct ServerDatatype {
+    duid: Duid,
+    r#type: DataType,
+    transactions: Vec<Arc<Transaction>>,
+    cseqs: HashMap<Cuid, u64>,
+    subscribers: HashSet<Cuid>,
+    deleted: bool,
+}
+
+impl ServerDatatype {
+    fn new(pack: &PushPullPack) -> Self {
+        Self {
+            duid: pack.duid.clone(),
+            r#type: pack.r#type,
+            transactions: vec![],
+            cseqs: HashMap::new(),
+            subscribers: HashSet::new(),
+            deleted: false,
+        }
+    }
+
+    /// Returns the other subscribers than `cuid`.
+    fn others(&self, cuid: &Cuid) -> Vec<Cuid> {
+        self.subscribers
+            .iter()
+            .filter(|c| *c != cuid)
+            .cloned()
+            .collect()
+    }
+
+    /// Deletes this datatype, which is kept to tell the subscribers that it is deleted
+    /// until it is created again.
+    fn delete(&mut self) {
+        self.transactions.clear();
+        self.cseqs.clear();
+        self.subscribers.clear();
+        self.deleted = true;
+    }
+
+    fn last_sseq(&self) -> u64 {
+        self.transactions.len() as u64
+    }
+
+    /// Appends the transactions that have not been pushed yet, assigning their `sseq`.
+    fn push(&mut self, cuid: &Cuid, transactions: &[Arc<Transaction>]) -> bool {
+        let cseq = self.cseqs.entry(cuid.clone()).or_default();
+        let mut pushed = false;
+        for tx in transactions {
+            if tx.cseq() <= *cseq {
+                continue;
+            }
+            *cseq = tx.cseq();
+            let mut tx = (**tx).clone();
+            tx.set_sseq(self.transactions.len() as u64 + 1);
+            self.transactions.push(Arc::new(tx));
+            pushed = true;
+        }
+        pushed
+    }
+
+    /// Returns the transactions of other clients after `sseq`.
+    fn pull(&self, cuid: &Cuid, sseq: u64) -> Vec<Arc<Transaction>> {
+        self.transactions
+            .iter()
+            .skip(sseq as usize)
+            .filter(|tx| tx.cuid() != cuid)
+            .cloned()
+            .collect()
+    }
+}
+
+#[derive(Default)]
+struct ServerState {
+    collections: HashMap<String, HashMap<String, ServerDatatype>>,
+    notifiers: HashMap<Cuid, Notifier>,
+}
+
+/// An in-memory SyncYam server.
+///
+/// It keeps the datatypes of every collection in memory, orders the pushed transactions
+/// by assigning server sequences, and notifies the other subscribers of a datatype
+/// when it changes. Since it implements [`Transport`], multiple clients in a process
+/// can be synchronized by sharing it.
+///
+/// # Examples
+///
+/// ```
+/// # use std::sync::Arc;
+/// # use syncyam::{Client, server::MemoryServer};
+/// let server = Arc::new(MemoryServer::new());
+/// let client1 = Client::builder("col", "c1").with_transport(server.clone()).build().unwrap();
+/// let client2 = Client::builder("col", "c2").with_transport(server.clone()).build().unwrap();
+///
+/// let counter1 = client1.create_counter("counter").unwrap();
+/// counter1.increase_by(3);
+/// client1.sync().unwrap();
+///
+/// let counter2 = client2.subscribe_counter("counter").unwrap();
+/// client2.sync().unwrap();
+/// assert_eq!(counter2.get_value(), 3);
+/// ```
+#[derive(Default)]
+pub struct MemoryServer {
+    state: Mutex<ServerState>,
+}
+
+impl MemoryServer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn process_pack(
+        datatypes: &mut HashMap<String, ServerDatatype>,
+        cuid: &Cuid,
+        pack: &PushPullPack,
+    ) -> Result<(PushPullPack, Option<Vec<Cuid>>), String> {
+        if datatypes.get(&pack.key).is_some_and(|d| d.deleted) {
+            match pack.state {
+                DatatypeState::DueToCreate | DatatypeState::DueToSubscribeOrCreate => {
+                    datatypes.remove(&pack.key);
+                }
+                _ => return Ok((Self::ended_pack(pack, DatatypeState::Deleted), None)),
+            }
+        }
+        let datatype = match (pack.state, datatypes.get_mut(&pack.key)) {
+            (DatatypeState::DueToCreate, Some(existing)) if existing.duid != pack.duid => {
+                return Err(format!("'{}' already exists", pack.key));
+            }
+            (DatatypeState::DueToCreate | DatatypeState::DueToSubscribeOrCreate, None) => datatypes
+                .entry(pack.key.clone())
+                .or_insert_with(|| ServerDatatype::new(pack)),
+            (
+                DatatypeState::DueToCreate
+                | DatatypeState::DueToSubscribe
+                | DatatypeState::DueToSubscribeOrCreate,
+                Some(existing),
+            ) => existing,
+            (
+                DatatypeState::Subscribed
+                | DatatypeState::DueToUnsubscribe
+                | DatatypeState::DueToDelete,
+                Some(existing),
+            ) if existing.subscribers.contains(cuid) => existing,
+            (DatatypeState::DueToUnsubscribe, _) => {
+                return Ok((Self::ended_pack(pack, DatatypeState::Closed), None));
+            }
+            (DatatypeState::Subscribed | DatatypeState::DueToDelete, Some(_)) => {
+                return Err(format!("'{}' is not subscribed", pack.key));
+            }
+            (DatatypeState::DueToSubscribe | DatatypeState::Subscribed, None) => {
+                return Err(format!("'{}' does not exist", pack.key));
+            }
+            (state, _) => return Err(format!("{state:?} is not supported")),
+        };
+        if datatype.r#type != pack.r#type {
+            return Err(format!(
+                "'{}' is {:?}, not {:?}",
+                pack.key, datatype.r#type, pack.r#type
+            ));
+        }
+        match pack.state {
+            DatatypeState::DueToDelete => {
+                let to_notify = datatype.others(cuid);
+                datatype.delete();
+                return Ok((
+                    Self::ended_pack(pack, DatatypeState::Deleted),
+                    Some(to_notify),
+                ));
+            }
+            DatatypeState::DueToUnsubscribe => {
+                // the pending transactions are pushed before unsubscribing
+                let to_notify = datatype
+                    .push(cuid, &pack.transactions)
+                    .then(|| datatype.others(cuid));
+                datatype.subscribers.remove(cuid);
+                return Ok((Self::ended_pack(pack, DatatypeState::Closed), to_notify));
+            }
+            _ => {}
+        }
+        datatype.subscribers.insert(cuid.clone());
+
+        let to_notify = datatype
+            .push(cuid, &pack.transactions)
+            .then(|| datatype.others(cuid));
+        let response = PushPullPack {
+            key: pack.key.clone(),
+            duid: datatype.duid.clone(),
+            r#type: datatype.r#type,
+            state: DatatypeState::Subscribed,
+            checkpoint: Checkpoint::new(
+                datatype.last_sseq(),
+                datatype.cseqs.get(cuid).copied().unwrap_or_default(),
+            ),
+            transactions: datatype.pull(cuid, pack.checkpoint.sseq),
+            error: None,
+        };
+        Ok((response, to_notify))
+    }
+
+    /// Returns a response telling that the datatype of `pack` is closed or deleted.
+    fn ended_pack(pack: &PushPullPack, state: DatatypeState) -> PushPullPack {
+        PushPullPack {
+            state,
+            transactions: vec![],
+            error: None,
+            ..pack.clone()
+        }
+    }
+}
+
+impl Transport for MemoryServer {
+    #[instrument(skip_all, fields(col = %request.collection, cuid = %request.cuid))]
+    fn push_pull(&self, request: PushPullRequest) -> Result<PushPullResponse, ClientError> {
+        let mut response = PushPullResponse::default();
+        let mut to_notify = HashSet::new();
+        let notifiers = {
+            let mut state = self.state.lock();
+            let datatypes = state
+                .collections
+                .entry(request.collection.clone())
+                .or_default();
+            for pack in request.packs.iter() {
+                match Self::process_pack(datatypes, &request.cuid, pack) {
+                    Ok((pack, cuids)) => {
+                        to_notify.extend(cuids.into_iter().flatten());
+                        response.packs.push(pack);
+                    }
+                    Err(error) => {
+                        warn!("rejected {pack}: {error}");
+                        let mut pack = pack.clone();
+                        pack.transactions.clear();
+                        pack.error = Some(error);
+                        response.packs.push(pack);
+                    }
+                }
+            }
+            to_notify
+                .iter()
+                .filter_map(|cuid| state.notifiers.get(cuid).cloned())
+                .collect::<Vec<_>>()
+        };
+        for notifier in notifiers {
+            notifier.notify();
+        }
+        Ok(response)
+    }
+
+    fn connect(&self, cuid: &Cuid, notifier: Notifier) -> Result<(), ClientError> {
+        self.state.lock().notifiers.insert(cuid.clone(), notifier);
+        Ok(())
+    }
+
+    fn disconnect(&self, cuid: &Cuid) {
+        self.state.lock().notifiers.remove(cuid);
+    }
+}
+
+#[cfg(test)]
+mod tests_memory_server {
+    use std::{sync::Arc, time::Duration};
+
+    use tracing::instrument;
+
+    use crate::{
+        Client, ClientError, Datatype, DatatypeState, DocumentValue, server::MemoryServer,
+    };
+
+    fn new_client(server: &Arc<MemoryServer>, alias: &str) -> Client {
+        Client::builder(module_path!(), alias)
+            .with_transport(server.clone())
+            .with_sync_interval(Duration::from_secs(3600))
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    #[instrument]
+    fn can_converge_counters() {
+        let server = Arc::new(MemoryServer::new());
+        let client1 = new_client(&server, "c1");
+        let client2 = new_client(&server, "c2");
+
+        let counter1 = client1.create_counter("k1").unwrap();
+        counter1.increase_by(2);
+        client1.sync().unwrap();
+
+        let counter2 = client2.subscribe_counter("k1").unwrap();
+        counter2.increase_by(3);
+        client2.sync().unwrap();
+        assert_eq!(counter2.get_state(), DatatypeState::Subscribed);
+        assert_eq!(counter2.get_value(), 5);
+
+        counter1.increase();
+        client1.sync().unwrap();
+        client2.sync().unwrap();
+        assert_eq!(counter1.get_value(), 6);
+        assert_eq!(counter2.get_value(), 6);
+    }
+
+    #[test]
+    #[instrument]
+    fn can_converge_concurrent_list_inserts() {
+        let server = Arc::new(MemoryServer::new());
+        let client1 = new_client(&server, "c1");
+        let client2 = new_client(&server, "c2");
+        let list1 = client1.subscribe_or_create_list("k1").unwrap();
+        let list2 = client2.subscribe_or_create_list("k1").unwrap();
+
+        list1.insert(0, ["a1", "a2"]).unwrap();
+        list2.insert(0, ["b1"]).unwrap();
+        client1.sync().unwrap();
+        client2.sync().unwrap();
+        client1.sync().unwrap();
+        assert_eq!(list1.len(), 3);
+        assert_eq!(list1.to_vec(), list2.to_vec());
+
+        list1.delete(0, 1).unwrap();
+        list2.update(2, "updated").unwrap();
+        client1.sync().unwrap();
+        client2.sync().unwrap();
+        client1.sync().unwrap();
+        assert_eq!(list1.len(), 2);
+        assert_eq!(list1.to_vec(), list2.to_vec());
+    }
+
+    #[test]
+    #[instrument]
+    fn can_converge_concurrent_map_writes() {
+        let server = Arc::new(MemoryServer::new());
+        let client1 = new_client(&server, "c1");
+        let client2 = new_client(&server, "c2");
+        let map1 = client1.create_map("k1").unwrap();
+        map1.put_all([("a", 1), ("b", 2)]);
+        client1.sync().unwrap();
+        let map2 = client2.subscribe_map("k1").unwrap();
+        client2.sync().unwrap();
+        assert_eq!(map2.keys(), vec!["a", "b"]);
+
+        map1.put("a", "x");
+        map1.remove("b");
+        map2.put("a", "y");
+        map2.put("b", 3);
+        map2.put("c", 4);
+        client1.sync().unwrap();
+        client2.sync().unwrap();
+        client1.sync().unwrap();
+        assert_eq!(map1.keys(), map2.keys());
+        for key in map1.keys() {
+            assert_eq!(map1.get(&key), map2.get(&key));
+        }
+        assert!(map1.contains_key("c"));
+    }
+
+    #[test]
+    #[instrument]
+    fn can_converge_concurrent_document_edits() {
+        let server = Arc::new(MemoryServer::new());
+        let client1 = new_client(&server, "c1");
+        let client2 = new_client(&server, "c2");
+        let doc1 = client1.create_document("k1").unwrap();
+        doc1.put_to_object("", "user", DocumentValue::from_iter([("name", "yam")]))
+            .unwrap();
+        doc1.put_to_object("", "tags", DocumentValue::new_array())
+            .unwrap();
+        client1.sync().unwrap();
+        let doc2 = client2.subscribe_document("k1").unwrap();
+        client2.sync().unwrap();
+        assert_eq!(doc2.get_by_path("/user/name"), Some("yam".into()));
+
+        doc1.put_to_object("/user", "name", "x").unwrap();
+        doc1.insert_to_array("/tags", 0, ["a1"]).unwrap();
+        doc2.put_to_object("/user", "age", 3).unwrap();
+        doc2.insert_to_array("/tags", 0, ["b1", "b2"]).unwrap();
+        client1.sync().unwrap();
+        client2.sync().unwrap();
+        client1.sync().unwrap();
+        assert_eq!(doc1.to_value(), doc2.to_value());
+        assert_eq!(doc1.get_by_path("/user/age"), Some(3.into()));
+        assert_eq!(
+            doc1.get_by_path("/tags").unwrap().as_array().unwrap().len(),
+            3
+        );
+
+        // a concurrent replacement of the parent hides the edits in the old one
+        doc1.put_to_object("", "user", DocumentValue::new_object())
+            .unwrap();
+        doc2.put_to_object("/user", "name", "y").unwrap();
+        client1.sync().unwrap();
+        client2.sync().unwrap();
+        client1.sync().unwrap();
+        assert_eq!(doc1.to_value(), doc2.to_value());
+        assert_eq!(doc2.get_by_path("/user"), Some(DocumentValue::new_object()));
+    }
+
+    #[test]
+    #[instrument]
+    fn can_converge_concurrent_text_edits() {
+        let server = Arc::new(MemoryServer::new());
+        let client1 = new_client(&server, "c1");
+        let client2 = new_client(&server, "c2");
+        let text1 = client1.create_text("k1").unwrap();
+        text1.insert(0, "hello world").unwrap();
+        client1.sync().unwrap();
+        let text2 = client2.subscribe_text("k1").unwrap();
+        client2.sync().unwrap();
+        assert_eq!(text2.to_string(), "hello world");
+
+        let cursor = text2.cursor(6);
+        text1.replace(0, 5, "bye").unwrap();
+        text1.insert(0, "> ").unwrap();
+        text2.insert(11, "!").unwrap();
+        text2.delete(5, 1).unwrap();
+        client1.sync().unwrap();
+        client2.sync().unwrap();
+        client1.sync().unwrap();
+        assert_eq!(text1.to_string(), "> byeworld!");
+        assert_eq!(text1.to_string(), text2.to_string());
+        assert_eq!(text2.cursor_position(&cursor), Some(5));
+    }
+
+    #[test]
+    #[instrument]
+    fn can_converge_concurrent_set_adds_and_removes() {
+        let server = Arc::new(MemoryServer::new());
+        let client1 = new_client(&server, "c1");
+        let client2 = new_client(&server, "c2");
+        let set1 = client1.create_set("k1").unwrap();
+        set1.add("a");
+        set1.add("b");
+        client1.sync().unwrap();
+        let set2 = client2.subscribe_set("k1").unwrap();
+        client2.sync().unwrap();
+        assert_eq!(set2.len(), 2);
+
+        set1.remove("a");
+        set1.add("b");
+        set2.add("a");
+        set2.remove("b");
+        set2.add(vec![1u8]);
+        client1.sync().unwrap();
+        client2.sync().unwrap();
+        client1.sync().unwrap();
+        // the concurrent adds win over the removes
+        assert_eq!(set1.to_vec(), set2.to_vec());
+        assert!(set1.contains("a") && set1.contains("b"));
+        assert_eq!(set1.len(), 3);
+
+        set1.remove("a");
+        set2.remove("a");
+        client1.sync().unwrap();
+        client2.sync().unwrap();
+        client1.sync().unwrap();
+        assert!(!set1.contains("a") && !set2.contains("a"));
+        assert_eq!(set1.to_vec(), set2.to_vec());
+    }
+
+    #[test]
+    #[instrument]
+    fn can_reject_invalid_requests() {
+        let server = Arc::new(MemoryServer::new());
+        let client1 = new_client(&server, "c1");
+        let client2 = new_client(&server, "c2");
+
+        let variable1 = client1.subscribe_variable("k1").unwrap();
+        assert!(client1.sync().is_err());
+        assert_eq!(variable1.get_state(), DatatypeState::DueToSubscribe);
+
+        let variable2 = client2.create_variable("k1").unwrap();
+        client2.sync().unwrap();
+        assert_eq!(variable2.get_state(), DatatypeState::Subscribed);
+        // the retried subscription succeeds once the datatype exists
+        client1.sync().unwrap();
+        assert_eq!(variable1.get_state(), DatatypeState::Subscribed);
+
+        let client3 = new_client(&server, "c3");
+        let _ = client3.create_variable("k1").unwrap();
+        assert!(client3.sync().is_err());
+        let client4 = new_client(&server, "c4");
+        let _ = client4.create_list("k2").unwrap();
+        client4.sync().unwrap();
+        let client5 = new_client(&server, "c5");
+        let _ = client5.subscribe_counter("k2").unwrap();
+        assert!(client5.sync().is_err());
+    }
+
+    #[test]
+    #[instrument]
+    fn can_unsubscribe_and_delete_datatypes() {
+        let server = Arc::new(MemoryServer::new());
+        let client1 = new_client(&server, "c1");
+        let client2 = new_client(&server, "c2");
+        let counter1 = client1.create_counter("k1").unwrap();
+        client1.sync().unwrap();
+        let counter2 = client2.subscribe_counter("k1").unwrap();
+        client2.sync().unwrap();
+
+        // the pending changes are pushed before unsubscribing
+        counter2.increase_by(3);
+        client2.unsubscribe("k1").unwrap();
+        assert_eq!(counter2.get_state(), DatatypeState::DueToUnsubscribe);
+        assert_eq!(counter2.increase(), 3);
+        client2.sync().unwrap();
+        assert_eq!(counter2.get_state(), DatatypeState::Closed);
+        assert!(client2.get_datatype("k1").is_none());
+        client1.sync().unwrap();
+        assert_eq!(counter1.get_value(), 3);
+
+        // the closed datatype is reopened, and pulls the changes made in the meantime
+        counter1.increase_by(4);
+        client1.sync().unwrap();
+        let counter2 = client2.subscribe_counter("k1").unwrap();
+        counter2.increase_by(5);
+        client2.sync().unwrap();
+        client1.sync().unwrap();
+        assert_eq!(counter2.get_state(), DatatypeState::Subscribed);
+        assert_eq!(counter2.get_value(), 12);
+        assert_eq!(counter1.get_value(), 12);
+
+        client1.delete_datatype("k1").unwrap();
+        assert_eq!(counter1.get_state(), DatatypeState::DueToDelete);
+        // a datatype going to be deleted cannot be unsubscribed
+        assert!(matches!(
+            client1.unsubscribe("k1"),
+            Err(ClientError::FailedToChangeDatatypeState(_))
+        ));
+        client1.sync().unwrap();
+        assert_eq!(counter1.get_state(), DatatypeState::Deleted);
+        client2.sync().unwrap();
+        assert_eq!(counter2.get_state(), DatatypeState::Deleted);
+        assert!(client2.get_datatype("k1").is_none());
+
+        // a deleted datatype cannot be subscribed to, but can be created again
+        let counter2 = client2.subscribe_counter("k1").unwrap();
+        client2.sync().unwrap();
+        assert_eq!(counter2.get_state(), DatatypeState::Deleted);
+        let counter3 = client1.create_counter("k1").unwrap();
+        client1.sync().unwrap();
+        assert_eq!(counter3.get_state(), DatatypeState::Subscribed);
+        assert_eq!(counter3.get_value(), 0);
+    }
+
+    #[test]
+    #[instrument]
+    fn can_seed_datatypes_from_exported_snapshot() {
+        let production = Arc::new(MemoryServer::new());
+        let client = new_client(&production, "c0");
+        let list = client.create_list("k1").unwrap();
+        list.insert(0, ["a", "b", "c"]).unwrap();
+        client.sync().unwrap();
+        let snapshot = list.export_snapshot();
+
+        let staging = Arc::new(MemoryServer::new());
+        let client1 = new_client(&staging, "c1");
+        let client2 = new_client(&staging, "c2");
+        let list1 = client1
+            .import_datatype("k1", &snapshot)
+            .unwrap()
+            .ensure_list()
+            .unwrap();
+        let list2 = client2
+            .import_datatype("k1", &snapshot)
+            .unwrap()
+            .ensure_list()
+            .unwrap();
+        assert_eq!(list1.to_vec(), list.to_vec());
+
+        list1.delete(1, 1).unwrap();
+        list2.insert(2, ["x"]).unwrap();
+        client1.sync().unwrap();
+        client2.sync().unwrap();
+        client1.sync().unwrap();
+        assert_eq!(list1.get_state(), DatatypeState::Subscribed);
+        assert_eq!(list2.get_state(), DatatypeState::Subscribed);
+        assert_eq!(list1.to_vec(), vec!["a".into(), "x".into(), "c".into()]);
+        assert_eq!(list1.to_vec(), list2.to_vec());
+        assert_eq!(list.len(), 3);
+
+        let client3 = new_client(&staging, "c3");
+        assert!(client3.import_datatype("k2", &snapshot[1..]).is_err());
+    }
+
+    #[test]
+    #[instrument]
+    fn can_import_snapshot_on_exporting_server() {
+        let server = Arc::new(MemoryServer::new());
+        let client0 = new_client(&server, "c0");
+        let client1 = new_client(&server, "c1");
+        let counter0 = client0.create_counter("k1").unwrap();
+        counter0.increase_by(1);
+        client0.sync().unwrap();
+        let counter1 = client1.subscribe_counter("k1").unwrap();
+        client1.sync().unwrap();
+        counter1.increase_by(10);
+        client1.sync().unwrap();
+        client0.sync().unwrap();
+        // a pending transaction is also in the snapshot.
+        counter0.increase_by(100);
+        let snapshot = counter0.export_snapshot();
+
+        let client2 = new_client(&server, "c2");
+        let counter2 = client2
+            .import_datatype("k1", &snapshot)
+            .unwrap()
+            .ensure_counter()
+            .unwrap();
+        client2.sync().unwrap();
+        client0.sync().unwrap();
+        client2.sync().unwrap();
+        assert_eq!(counter2.get_state(), DatatypeState::Subscribed);
+        assert_eq!(counter2.get_value(), 111);
+
+        counter2.increase_by(1000);
+        client2.sync().unwrap();
+        client0.sync().unwrap();
+        client1.sync().unwrap();
+        assert_eq!(counter0.get_value(), 1111);
+        assert_eq!(counter1.get_value(), 1111);
+    }
+
+    #[test]
+    #[instrument]
+    fn can_converge_in_background_with_notifications() {
+        let server = Arc::new(MemoryServer::new());
+        let client1 = new_client(&server, "c1");
+        let client2 = new_client(&server, "c2");
+        let variable1 = client1.create_variable("k1").unwrap();
+        client1.sync().unwrap();
+        let variable2 = client2.subscribe_variable("k1").unwrap();
+        client2.sync().unwrap();
+
+        variable1.set("yam");
+        client1.sync().unwrap();
+        // client2 is notified, so it pulls without waiting for the sync interval
+        awaitility::at_most(Duration::from_secs(3)).until(|| variable2.get() == "yam".into());
+    }
+}
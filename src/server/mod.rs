@@ -0,0 +1,5 @@
+//! Server-side implementations of the SyncYam push/pull protocol.
+
+mod memory_server;
+
+pub use memory_server::MemoryServer;
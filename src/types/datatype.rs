@@ -1,14 +1,25 @@
+use crate::{
+    DatatypeError,
+    utils::bytes::{BytesReader, BytesWriter},
+};
+
 /// DataType represents the kinds of Datatypes in SyncYam
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(i32)]
 pub enum DataType {
     Counter = 0,
     Variable = 1,
     List = 2,
+    Map = 3,
+    Document = 4,
+    Text = 5,
+    Set = 6,
 }
 
 /// DatatypeState represents the state of a Datatype in SyncYam.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(i32)]
 pub enum DatatypeState {
     /// The Datatype is scheduled to be created on the SyncYam server.
@@ -29,3 +40,84 @@ pub enum DatatypeState {
     /// The Datatype has been deleted and synchronized with the SyncYam server.
     Deleted = 7,
 }
+
+impl DataType {
+    pub(crate) fn write_to(&self, writer: &mut BytesWriter) {
+        writer.write_u8(*self as u8);
+    }
+
+    pub(crate) fn read_from(reader: &mut BytesReader) -> Result<Self, DatatypeError> {
+        match reader.read_u8()? {
+            0 => Ok(DataType::Counter),
+            1 => Ok(DataType::Variable),
+            2 => Ok(DataType::List),
+            3 => Ok(DataType::Map),
+            4 => Ok(DataType::Document),
+            5 => Ok(DataType::Text),
+            6 => Ok(DataType::Set),
+            v => Err(DatatypeError::FailedToDeserialize(format!(
+                "unknown datatype: {v}"
+            ))),
+        }
+    }
+}
+
+impl DatatypeState {
+    /// Returns whether local operations can be executed in this state, i.e., the
+    /// datatype is neither closed nor deleted, nor going to be.
+    pub(crate) fn is_writable(&self) -> bool {
+        !matches!(
+            self,
+            DatatypeState::DueToUnsubscribe
+                | DatatypeState::Closed
+                | DatatypeState::DueToDelete
+                | DatatypeState::Deleted
+        )
+    }
+
+    /// Returns whether the state can change to `next`, following the lifecycle below.
+    ///
+    /// ```text
+    /// DueToCreate | DueToSubscribe | DueToSubscribeOrCreate -> Subscribed
+    /// Subscribed -> DueToUnsubscribe -> Closed -> DueToCreate | DueToSubscribe | DueToSubscribeOrCreate
+    /// Subscribed -> DueToDelete -> Deleted
+    /// ```
+    ///
+    /// Besides, a datatype can be closed or deleted from any state before it is closed or
+    /// deleted, and be unsubscribed or deleted before it is subscribed. Nothing follows
+    /// [`DatatypeState::Deleted`].
+    pub(crate) fn can_transit_to(&self, next: DatatypeState) -> bool {
+        use DatatypeState::*;
+        match self {
+            DueToCreate | DueToSubscribe | DueToSubscribeOrCreate => matches!(
+                next,
+                Subscribed | DueToUnsubscribe | Closed | DueToDelete | Deleted
+            ),
+            Subscribed => matches!(next, DueToUnsubscribe | Closed | DueToDelete | Deleted),
+            DueToUnsubscribe => matches!(next, Closed | Deleted),
+            Closed => matches!(next, DueToCreate | DueToSubscribe | DueToSubscribeOrCreate),
+            DueToDelete => matches!(next, Deleted),
+            Deleted => false,
+        }
+    }
+
+    pub(crate) fn write_to(&self, writer: &mut BytesWriter) {
+        writer.write_u8(*self as u8);
+    }
+
+    pub(crate) fn read_from(reader: &mut BytesReader) -> Result<Self, DatatypeError> {
+        match reader.read_u8()? {
+            0 => Ok(DatatypeState::DueToCreate),
+            1 => Ok(DatatypeState::DueToSubscribe),
+            2 => Ok(DatatypeState::DueToSubscribeOrCreate),
+            3 => Ok(DatatypeState::Subscribed),
+            4 => Ok(DatatypeState::DueToUnsubscribe),
+            5 => Ok(DatatypeState::Closed),
+            6 => Ok(DatatypeState::DueToDelete),
+            7 => Ok(DatatypeState::Deleted),
+            v => Err(DatatypeError::FailedToDeserialize(format!(
+                "unknown datatype state: {v}"
+            ))),
+        }
+    }
+}
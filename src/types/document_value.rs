@@ -0,0 +1,247 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    DatatypeError,
+    types::primitive::Primitive,
+    utils::bytes::{BytesReader, BytesWriter},
+};
+
+const TAG_PRIMITIVE: u8 = 0;
+const TAG_OBJECT: u8 = 1;
+const TAG_ARRAY: u8 = 2;
+
+/// DocumentValue represents a JSON-like value of a [`crate::Document`]: an object,
+/// an array, or a [`Primitive`].
+///
+/// # Examples
+/// ```
+/// use syncyam::{DocumentValue, Primitive};
+/// let object = DocumentValue::from_iter([("name", "yam"), ("kind", "root")]);
+/// assert_eq!(object.get("name"), Some(&DocumentValue::from("yam")));
+/// let array = DocumentValue::Array(vec![1.into(), object]);
+/// assert_eq!(array.as_array().unwrap().len(), 2);
+/// assert_eq!(DocumentValue::from(1), DocumentValue::Primitive(Primitive::Int(1)));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DocumentValue {
+    Primitive(Primitive),
+    Object(BTreeMap<String, DocumentValue>),
+    Array(Vec<DocumentValue>),
+}
+
+impl Default for DocumentValue {
+    fn default() -> Self {
+        DocumentValue::Primitive(Primitive::Null)
+    }
+}
+
+impl DocumentValue {
+    /// Returns an empty object.
+    pub fn new_object() -> Self {
+        DocumentValue::Object(BTreeMap::new())
+    }
+
+    /// Returns an empty array.
+    pub fn new_array() -> Self {
+        DocumentValue::Array(vec![])
+    }
+
+    /// Returns the value of `key` if this is an object.
+    pub fn get(&self, key: &str) -> Option<&DocumentValue> {
+        match self {
+            DocumentValue::Object(o) => o.get(key),
+            _ => None,
+        }
+    }
+
+    pub fn as_primitive(&self) -> Option<&Primitive> {
+        match self {
+            DocumentValue::Primitive(p) => Some(p),
+            _ => None,
+        }
+    }
+
+    pub fn as_object(&self) -> Option<&BTreeMap<String, DocumentValue>> {
+        match self {
+            DocumentValue::Object(o) => Some(o),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&Vec<DocumentValue>> {
+        match self {
+            DocumentValue::Array(a) => Some(a),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn size(&self) -> usize {
+        let payload = match self {
+            DocumentValue::Primitive(p) => p.size(),
+            DocumentValue::Object(o) => o.iter().map(|(k, v)| k.len() + v.size()).sum(),
+            DocumentValue::Array(a) => a.iter().map(|v| v.size()).sum(),
+        };
+        size_of::<Self>() + payload
+    }
+
+    pub(crate) fn write_to(&self, writer: &mut BytesWriter) {
+        match self {
+            DocumentValue::Primitive(p) => {
+                writer.write_u8(TAG_PRIMITIVE);
+                p.write_to(writer);
+            }
+            DocumentValue::Object(o) => {
+                writer.write_u8(TAG_OBJECT);
+                writer.write_u32(o.len() as u32);
+                for (key, value) in o.iter() {
+                    writer.write_str(key);
+                    value.write_to(writer);
+                }
+            }
+            DocumentValue::Array(a) => {
+                writer.write_u8(TAG_ARRAY);
+                writer.write_u32(a.len() as u32);
+                a.iter().for_each(|v| v.write_to(writer));
+            }
+        }
+    }
+
+    pub(crate) fn read_from(reader: &mut BytesReader) -> Result<Self, DatatypeError> {
+        match reader.read_u8()? {
+            TAG_PRIMITIVE => Ok(DocumentValue::Primitive(Primitive::read_from(reader)?)),
+            TAG_OBJECT => {
+                let len = reader.read_u32()?;
+                let object = (0..len)
+                    .map(|_| Ok((reader.read_string()?, DocumentValue::read_from(reader)?)))
+                    .collect::<Result<_, DatatypeError>>()?;
+                Ok(DocumentValue::Object(object))
+            }
+            TAG_ARRAY => {
+                let len = reader.read_u32()?;
+                let array = (0..len)
+                    .map(|_| DocumentValue::read_from(reader))
+                    .collect::<Result<_, _>>()?;
+                Ok(DocumentValue::Array(array))
+            }
+            tag => Err(DatatypeError::FailedToDeserialize(format!(
+                "unknown document value tag: {tag}"
+            ))),
+        }
+    }
+}
+
+impl<T: Into<Primitive>> From<T> for DocumentValue {
+    fn from(value: T) -> Self {
+        DocumentValue::Primitive(value.into())
+    }
+}
+
+impl From<Vec<DocumentValue>> for DocumentValue {
+    fn from(value: Vec<DocumentValue>) -> Self {
+        DocumentValue::Array(value)
+    }
+}
+
+impl<K: Into<String>, V: Into<DocumentValue>> FromIterator<(K, V)> for DocumentValue {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        DocumentValue::Object(
+            iter.into_iter()
+                .map(|(k, v)| (k.into(), v.into()))
+                .collect(),
+        )
+    }
+}
+
+#[cfg(feature = "json")]
+impl From<serde_json::Value> for DocumentValue {
+    /// Converts a JSON value; a number out of the range of `i64` becomes a float.
+    fn from(value: serde_json::Value) -> Self {
+        use serde_json::Value;
+        match value {
+            Value::Null => Primitive::Null.into(),
+            Value::Bool(b) => b.into(),
+            Value::Number(n) => match n.as_i64() {
+                Some(i) => i.into(),
+                None => n.as_f64().unwrap_or(f64::NAN).into(),
+            },
+            Value::String(s) => s.into(),
+            Value::Array(a) => DocumentValue::Array(a.into_iter().map(Into::into).collect()),
+            Value::Object(o) => o.into_iter().collect(),
+        }
+    }
+}
+
+#[cfg(feature = "json")]
+impl From<DocumentValue> for serde_json::Value {
+    /// Converts into a JSON value; bytes become an array of numbers, and a float that is
+    /// not finite becomes null.
+    fn from(value: DocumentValue) -> Self {
+        use serde_json::Value;
+        match value {
+            DocumentValue::Primitive(p) => match p {
+                Primitive::Null => Value::Null,
+                Primitive::Bool(b) => Value::Bool(b),
+                Primitive::Int(i) => Value::from(i),
+                Primitive::Float(f) => Value::from(f),
+                Primitive::String(s) => Value::String(s),
+                Primitive::Bytes(b) => Value::from(b),
+            },
+            DocumentValue::Object(o) => {
+                Value::Object(o.into_iter().map(|(k, v)| (k, v.into())).collect())
+            }
+            DocumentValue::Array(a) => Value::Array(a.into_iter().map(Into::into).collect()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_document_value {
+    use crate::{
+        types::{document_value::DocumentValue, primitive::Primitive},
+        utils::bytes::{BytesReader, BytesWriter},
+    };
+
+    #[test]
+    fn can_write_and_read_document_values() {
+        let value = DocumentValue::from_iter([
+            ("a", DocumentValue::from(1)),
+            (
+                "b",
+                DocumentValue::Array(vec!["x".into(), DocumentValue::new_object()]),
+            ),
+            ("c", Primitive::Null.into()),
+        ]);
+        let mut writer = BytesWriter::new();
+        value.write_to(&mut writer);
+        let bytes = writer.into_bytes();
+        let mut reader = BytesReader::new(&bytes);
+        assert_eq!(value, DocumentValue::read_from(&mut reader).unwrap());
+        assert!(reader.is_empty());
+
+        let mut reader = BytesReader::new(&bytes[..bytes.len() - 1]);
+        assert!(DocumentValue::read_from(&mut reader).is_err());
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn can_convert_json_values() {
+        let json = serde_json::json!({
+            "name": "yam",
+            "tags": [1, 2.5, true, null],
+            "nested": {"big": u64::MAX},
+        });
+        let value = DocumentValue::from(json.clone());
+        assert_eq!(value.get("name"), Some(&"yam".into()));
+        assert_eq!(
+            value.get("nested").unwrap().get("big"),
+            Some(&(u64::MAX as f64).into())
+        );
+        let converted = serde_json::Value::from(value);
+        assert_eq!(converted["tags"], json["tags"]);
+        assert_eq!(
+            serde_json::Value::from(DocumentValue::from(vec![1u8, 2])),
+            serde_json::json!([1, 2])
+        );
+    }
+}
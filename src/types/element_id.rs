@@ -0,0 +1,118 @@
+use std::{
+    cmp::Ordering,
+    fmt::{Debug, Display, Formatter},
+};
+
+use crate::{
+    DatatypeError,
+    types::{operation_id::OperationId, uid::Cuid},
+    utils::bytes::{BytesReader, BytesWriter},
+};
+
+/// ElementId identifies an element of a sequence datatype.
+///
+/// It is derived from the [`OperationId`] timestamp (lamport and cuid) of the operation
+/// that inserted the element, and an `offset` of the element among the values
+/// inserted together by that operation.
+#[derive(Clone, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ElementId {
+    pub lamport: u64,
+    pub cuid: Cuid,
+    pub offset: u32,
+}
+
+impl ElementId {
+    pub fn new(op_id: &OperationId, offset: u32) -> Self {
+        Self {
+            lamport: op_id.lamport,
+            cuid: op_id.cuid.clone(),
+            offset,
+        }
+    }
+
+    pub(crate) fn write_to(&self, writer: &mut BytesWriter) {
+        writer.write_u64(self.lamport);
+        self.cuid.write_to(writer);
+        writer.write_u32(self.offset);
+    }
+
+    pub(crate) fn read_from(reader: &mut BytesReader) -> Result<Self, DatatypeError> {
+        let lamport = reader.read_u64()?;
+        let cuid = Cuid::read_from(reader)?;
+        Ok(Self {
+            lamport,
+            cuid,
+            offset: reader.read_u32()?,
+        })
+    }
+}
+
+impl Ord for ElementId {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.lamport
+            .cmp(&other.lamport)
+            .then_with(|| self.cuid.cmp(&other.cuid))
+            .then_with(|| self.offset.cmp(&other.offset))
+    }
+}
+
+impl PartialOrd for ElementId {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Debug for ElementId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.to_string().as_str())
+    }
+}
+
+impl Display for ElementId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!(
+            "{}:{}:{}",
+            self.lamport, self.cuid, self.offset
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests_element_id {
+    use super::*;
+
+    #[test]
+    fn can_order_element_ids() {
+        let mut op_id = OperationId::new_with_cuid(&Cuid::try_from("0000000000000001").unwrap());
+        op_id.next_lamport();
+        let e1 = ElementId::new(&op_id, 0);
+        let e2 = ElementId::new(&op_id, 1);
+        assert!(e1 < e2);
+        assert_eq!(e1.to_string(), "1:0000000000000001:0");
+
+        let mut other = OperationId::new_with_cuid(&Cuid::try_from("0000000000000002").unwrap());
+        other.next_lamport();
+        let e3 = ElementId::new(&other, 0);
+        assert!(e2 < e3);
+
+        op_id.next_lamport();
+        let e4 = ElementId::new(&op_id, 0);
+        assert!(e3 < e4);
+    }
+
+    #[test]
+    fn can_write_and_read_element_id() {
+        let mut op_id = OperationId::new_with_cuid(&Cuid::new());
+        op_id.next_lamport();
+        let e = ElementId::new(&op_id, 7);
+        let mut writer = BytesWriter::new();
+        e.write_to(&mut writer);
+        let bytes = writer.into_bytes();
+        assert_eq!(
+            e,
+            ElementId::read_from(&mut BytesReader::new(&bytes)).unwrap()
+        );
+        assert!(ElementId::read_from(&mut BytesReader::new(&bytes[..3])).is_err());
+    }
+}
@@ -1,3 +1,7 @@
 pub mod datatype;
+pub mod document_value;
+pub mod element_id;
 pub mod operation_id;
+pub mod primitive;
+pub mod set_element;
 pub mod uid;
@@ -3,9 +3,17 @@ use std::{
     fmt::{Debug, Display, Formatter},
 };
 
-use crate::types::uid::Cuid;
+use crate::{
+    DatatypeError,
+    types::uid::Cuid,
+    utils::{
+        bytes::{BytesReader, BytesWriter},
+        codec,
+    },
+};
 
 #[derive(PartialEq, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct OperationId {
     pub lamport: u64,
     pub cuid: Cuid,
@@ -58,6 +66,39 @@ impl OperationId {
             self.cseq = self.cseq.max(other.cseq);
         }
     }
+
+    /// Encodes the operation id into the binary wire format.
+    pub fn encode(&self) -> Vec<u8> {
+        codec::encode(codec::KIND_OPERATION_ID, |w| self.write_to(w))
+    }
+
+    /// Decodes an operation id from the binary wire format.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DatatypeError::FailedToDeserialize`] if `bytes` is not a valid encoded
+    /// operation id.
+    pub fn decode(bytes: &[u8]) -> Result<Self, DatatypeError> {
+        codec::decode(bytes, codec::KIND_OPERATION_ID, Self::read_from)
+    }
+
+    pub(crate) fn write_to(&self, writer: &mut BytesWriter) {
+        writer.write_u64(self.lamport);
+        self.cuid.write_to(writer);
+        writer.write_u64(self.cseq);
+        writer.write_u64(self.sseq);
+    }
+
+    pub(crate) fn read_from(reader: &mut BytesReader) -> Result<Self, DatatypeError> {
+        let lamport = reader.read_u64()?;
+        let cuid = Cuid::read_from(reader)?;
+        Ok(Self {
+            lamport,
+            cuid,
+            cseq: reader.read_u64()?,
+            sseq: reader.read_u64()?,
+        })
+    }
 }
 
 impl Debug for OperationId {
@@ -126,4 +167,23 @@ mod tests_operation_id {
 
         assert_eq!(op_id1.partial_cmp(&op_id2).unwrap(), Ordering::Equal);
     }
+
+    #[test]
+    fn can_write_and_read_operation_id() {
+        let mut op_id = OperationId::new_with_cuid(&Cuid::new());
+        op_id.next_lamport();
+        op_id.next_cseq();
+        op_id.sseq = 3;
+        let mut writer = BytesWriter::new();
+        op_id.write_to(&mut writer);
+        let bytes = writer.into_bytes();
+        let read = OperationId::read_from(&mut BytesReader::new(&bytes)).unwrap();
+        assert_eq!(op_id, read);
+        assert!(OperationId::read_from(&mut BytesReader::new(&bytes[..10])).is_err());
+
+        let encoded = op_id.encode();
+        assert_eq!(OperationId::decode(&encoded).unwrap(), op_id);
+        assert!(OperationId::decode(&encoded[..encoded.len() - 1]).is_err());
+        assert!(OperationId::decode(&Cuid::new().encode()).is_err());
+    }
 }
@@ -0,0 +1,192 @@
+use std::fmt::{Display, Formatter};
+
+use crate::{
+    DatatypeError,
+    utils::bytes::{BytesReader, BytesWriter},
+};
+
+const TAG_NULL: u8 = 0;
+const TAG_BOOL: u8 = 1;
+const TAG_INT: u8 = 2;
+const TAG_FLOAT: u8 = 3;
+const TAG_STRING: u8 = 4;
+const TAG_BYTES: u8 = 5;
+
+/// Primitive represents a single value that can be stored in SyncYam datatypes.
+///
+/// # Examples
+/// ```
+/// use syncyam::Primitive;
+/// assert_eq!(Primitive::from(1), Primitive::Int(1));
+/// assert_eq!(Primitive::from("yam"), Primitive::String("yam".to_string()));
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Primitive {
+    #[default]
+    Null,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+    Bytes(Vec<u8>),
+}
+
+impl Primitive {
+    /// Returns true if this primitive is `Primitive::Null`.
+    pub fn is_null(&self) -> bool {
+        matches!(self, Primitive::Null)
+    }
+
+    pub(crate) fn size(&self) -> usize {
+        let payload = match self {
+            Primitive::Null => 0,
+            Primitive::Bool(_) => size_of::<bool>(),
+            Primitive::Int(_) => size_of::<i64>(),
+            Primitive::Float(_) => size_of::<f64>(),
+            Primitive::String(s) => s.len(),
+            Primitive::Bytes(b) => b.len(),
+        };
+        size_of::<Self>() + payload
+    }
+
+    pub(crate) fn write_to(&self, writer: &mut BytesWriter) {
+        match self {
+            Primitive::Null => writer.write_u8(TAG_NULL),
+            Primitive::Bool(b) => {
+                writer.write_u8(TAG_BOOL);
+                writer.write_u8(*b as u8);
+            }
+            Primitive::Int(i) => {
+                writer.write_u8(TAG_INT);
+                writer.write_i64(*i);
+            }
+            Primitive::Float(f) => {
+                writer.write_u8(TAG_FLOAT);
+                writer.write_f64(*f);
+            }
+            Primitive::String(s) => {
+                writer.write_u8(TAG_STRING);
+                writer.write_str(s);
+            }
+            Primitive::Bytes(b) => {
+                writer.write_u8(TAG_BYTES);
+                writer.write_bytes(b);
+            }
+        }
+    }
+
+    pub(crate) fn read_from(reader: &mut BytesReader) -> Result<Self, DatatypeError> {
+        match reader.read_u8()? {
+            TAG_NULL => Ok(Primitive::Null),
+            TAG_BOOL => Ok(Primitive::Bool(reader.read_u8()? != 0)),
+            TAG_INT => Ok(Primitive::Int(reader.read_i64()?)),
+            TAG_FLOAT => Ok(Primitive::Float(reader.read_f64()?)),
+            TAG_STRING => Ok(Primitive::String(reader.read_string()?)),
+            TAG_BYTES => Ok(Primitive::Bytes(reader.read_bytes()?.to_vec())),
+            tag => Err(DatatypeError::FailedToDeserialize(format!(
+                "unknown primitive tag: {tag}"
+            ))),
+        }
+    }
+}
+
+impl Display for Primitive {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Primitive::Null => f.write_str("null"),
+            Primitive::Bool(b) => write!(f, "{b}"),
+            Primitive::Int(i) => write!(f, "{i}"),
+            Primitive::Float(v) => write!(f, "{v}"),
+            Primitive::String(s) => write!(f, "{s:?}"),
+            Primitive::Bytes(b) => write!(f, "{b:?}"),
+        }
+    }
+}
+
+impl From<bool> for Primitive {
+    fn from(value: bool) -> Self {
+        Primitive::Bool(value)
+    }
+}
+
+impl From<i32> for Primitive {
+    fn from(value: i32) -> Self {
+        Primitive::Int(value as i64)
+    }
+}
+
+impl From<i64> for Primitive {
+    fn from(value: i64) -> Self {
+        Primitive::Int(value)
+    }
+}
+
+impl From<f64> for Primitive {
+    fn from(value: f64) -> Self {
+        Primitive::Float(value)
+    }
+}
+
+impl From<&str> for Primitive {
+    fn from(value: &str) -> Self {
+        Primitive::String(value.to_owned())
+    }
+}
+
+impl From<String> for Primitive {
+    fn from(value: String) -> Self {
+        Primitive::String(value)
+    }
+}
+
+impl From<Vec<u8>> for Primitive {
+    fn from(value: Vec<u8>) -> Self {
+        Primitive::Bytes(value)
+    }
+}
+
+impl From<&[u8]> for Primitive {
+    fn from(value: &[u8]) -> Self {
+        Primitive::Bytes(value.to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests_primitive {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case::null(Primitive::Null)]
+    #[case::bool(Primitive::from(true))]
+    #[case::int(Primitive::from(-123))]
+    #[case::float(Primitive::from(2.5))]
+    #[case::string(Primitive::from("syncyam"))]
+    #[case::bytes(Primitive::from(vec![1u8, 2, 3]))]
+    fn can_write_and_read_primitives(#[case] p: Primitive) {
+        let mut writer = BytesWriter::new();
+        p.write_to(&mut writer);
+        let bytes = writer.into_bytes();
+        let mut reader = BytesReader::new(&bytes);
+        assert_eq!(p, Primitive::read_from(&mut reader).unwrap());
+        assert!(reader.is_empty());
+    }
+
+    #[test]
+    fn can_fail_to_read_invalid_primitives() {
+        let mut reader = BytesReader::new(&[99]);
+        assert!(Primitive::read_from(&mut reader).is_err());
+        let mut reader = BytesReader::new(&[TAG_INT, 1, 2]);
+        assert!(Primitive::read_from(&mut reader).is_err());
+    }
+
+    #[test]
+    fn can_display_primitives() {
+        assert_eq!(Primitive::Null.to_string(), "null");
+        assert_eq!(Primitive::from("a").to_string(), "\"a\"");
+        assert_eq!(Primitive::from(1).to_string(), "1");
+        assert!(Primitive::default().is_null());
+    }
+}
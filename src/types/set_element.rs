@@ -0,0 +1,100 @@
+use std::fmt::{Display, Formatter};
+
+use crate::{
+    DatatypeError,
+    types::primitive::Primitive,
+    utils::bytes::{BytesReader, BytesWriter},
+};
+
+const TAG_STRING: u8 = 0;
+const TAG_BYTES: u8 = 1;
+
+/// SetElement represents an element of a [`crate::Set`]: a string or bytes.
+///
+/// # Examples
+/// ```
+/// use syncyam::{Primitive, SetElement};
+/// assert_eq!(SetElement::from("yam"), SetElement::String("yam".to_string()));
+/// assert_eq!(Primitive::from(SetElement::from(vec![1u8])), Primitive::Bytes(vec![1]));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SetElement {
+    String(String),
+    Bytes(Vec<u8>),
+}
+
+impl SetElement {
+    pub(crate) fn size(&self) -> usize {
+        let payload = match self {
+            SetElement::String(s) => s.len(),
+            SetElement::Bytes(b) => b.len(),
+        };
+        size_of::<Self>() + payload
+    }
+
+    pub(crate) fn write_to(&self, writer: &mut BytesWriter) {
+        match self {
+            SetElement::String(s) => {
+                writer.write_u8(TAG_STRING);
+                writer.write_str(s);
+            }
+            SetElement::Bytes(b) => {
+                writer.write_u8(TAG_BYTES);
+                writer.write_bytes(b);
+            }
+        }
+    }
+
+    pub(crate) fn read_from(reader: &mut BytesReader) -> Result<Self, DatatypeError> {
+        match reader.read_u8()? {
+            TAG_STRING => Ok(SetElement::String(reader.read_string()?)),
+            TAG_BYTES => Ok(SetElement::Bytes(reader.read_bytes()?.to_vec())),
+            tag => Err(DatatypeError::FailedToDeserialize(format!(
+                "unknown set element tag: {tag}"
+            ))),
+        }
+    }
+}
+
+impl Display for SetElement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SetElement::String(s) => write!(f, "{s:?}"),
+            SetElement::Bytes(b) => write!(f, "{b:?}"),
+        }
+    }
+}
+
+impl From<&str> for SetElement {
+    fn from(value: &str) -> Self {
+        SetElement::String(value.to_owned())
+    }
+}
+
+impl From<String> for SetElement {
+    fn from(value: String) -> Self {
+        SetElement::String(value)
+    }
+}
+
+impl From<Vec<u8>> for SetElement {
+    fn from(value: Vec<u8>) -> Self {
+        SetElement::Bytes(value)
+    }
+}
+
+impl From<&[u8]> for SetElement {
+    fn from(value: &[u8]) -> Self {
+        SetElement::Bytes(value.to_vec())
+    }
+}
+
+impl From<SetElement> for Primitive {
+    fn from(value: SetElement) -> Self {
+        match value {
+            SetElement::String(s) => Primitive::String(s),
+            SetElement::Bytes(b) => Primitive::Bytes(b),
+        }
+    }
+}
@@ -2,12 +2,25 @@ use std::fmt::{Display, Formatter};
 
 use nanoid::nanoid;
 
+use crate::{
+    DatatypeError,
+    utils::{
+        bytes::{BytesReader, BytesWriter},
+        codec,
+    },
+};
+
 pub type Cuid = Uid;
 pub type Duid = Uid;
 
 pub const UID_LEN: usize = 16;
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Ord, PartialOrd)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "String", into = "String")
+)]
 pub struct Uid(String);
 
 impl Uid {
@@ -19,6 +32,29 @@ impl Uid {
         Self("0000000000000000".to_string())
     }
 
+    /// Encodes the uid into the binary wire format.
+    pub fn encode(&self) -> Vec<u8> {
+        codec::encode(codec::KIND_UID, |w| self.write_to(w))
+    }
+
+    /// Decodes a uid from the binary wire format.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DatatypeError::FailedToDeserialize`] if `bytes` is not a valid encoded uid.
+    pub fn decode(bytes: &[u8]) -> Result<Self, DatatypeError> {
+        codec::decode(bytes, codec::KIND_UID, Self::read_from)
+    }
+
+    pub(crate) fn write_to(&self, writer: &mut BytesWriter) {
+        writer.write_str(&self.0);
+    }
+
+    pub(crate) fn read_from(reader: &mut BytesReader) -> Result<Self, DatatypeError> {
+        Self::try_from(reader.read_string()?)
+            .map_err(|e| DatatypeError::FailedToDeserialize(e.to_string()))
+    }
+
     fn validate(s: &str) -> bool {
         s.len() == UID_LEN
             && s.chars()
@@ -61,6 +97,12 @@ impl TryFrom<&str> for Uid {
     }
 }
 
+impl From<Uid> for String {
+    fn from(value: Uid) -> Self {
+        value.0
+    }
+}
+
 impl AsRef<str> for Uid {
     fn as_ref(&self) -> &str {
         &self.0
@@ -108,6 +150,31 @@ mod tests_uid {
         assert_eq!(uid_set.len(), LIMIT + 1)
     }
 
+    #[test]
+    fn can_encode_and_decode_uids() {
+        let uid = Uid::new();
+        assert_eq!(Uid::decode(&uid.encode()).unwrap(), uid);
+
+        let mut invalid = Uid::new_nil().encode();
+        let last = invalid.len() - 1;
+        invalid[last] = b'(';
+        assert_eq!(
+            Uid::decode(&invalid).unwrap_err(),
+            DatatypeError::FailedToDeserialize("".into())
+        );
+        assert!(Uid::decode(&[]).is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn can_serialize_uids_as_strings() {
+        let uid = Uid::new();
+        let json = serde_json::to_string(&uid).unwrap();
+        assert_eq!(json, format!("\"{uid}\""));
+        assert_eq!(serde_json::from_str::<Uid>(&json).unwrap(), uid);
+        assert!(serde_json::from_str::<Uid>("\"invalid\"").is_err());
+    }
+
     #[rstest]
     #[case::valid1("0000000000000000", true)]
     #[case::valid2("-_00000000000000", true)]
@@ -0,0 +1,172 @@
+use crate::{DatatypeError, utils::codec::WIRE_VERSION};
+
+/// A little-endian byte writer used to build compact binary snapshots.
+#[derive(Default)]
+pub struct BytesWriter {
+    buf: Vec<u8>,
+}
+
+impl BytesWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn write_u8(&mut self, v: u8) {
+        self.buf.push(v);
+    }
+
+    pub fn write_bool(&mut self, v: bool) {
+        self.buf.push(v as u8);
+    }
+
+    pub fn write_u32(&mut self, v: u32) {
+        self.buf.extend_from_slice(&v.to_le_bytes());
+    }
+
+    pub fn write_u64(&mut self, v: u64) {
+        self.buf.extend_from_slice(&v.to_le_bytes());
+    }
+
+    pub fn write_i64(&mut self, v: i64) {
+        self.buf.extend_from_slice(&v.to_le_bytes());
+    }
+
+    pub fn write_f64(&mut self, v: f64) {
+        self.buf.extend_from_slice(&v.to_le_bytes());
+    }
+
+    /// Writes length-prefixed bytes.
+    pub fn write_bytes(&mut self, v: &[u8]) {
+        self.write_u32(v.len() as u32);
+        self.buf.extend_from_slice(v);
+    }
+
+    /// Writes a length-prefixed UTF-8 string.
+    pub fn write_str(&mut self, v: &str) {
+        self.write_bytes(v.as_bytes());
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.buf
+    }
+}
+
+/// A reader for bytes produced by [`BytesWriter`].
+///
+/// Every read fails with [`DatatypeError::FailedToDeserialize`] if the input is too short.
+pub struct BytesReader<'a> {
+    buf: &'a [u8],
+    version: u8,
+}
+
+impl<'a> BytesReader<'a> {
+    /// Creates a reader for bytes of the current [`WIRE_VERSION`].
+    pub fn new(buf: &'a [u8]) -> Self {
+        Self::with_version(buf, WIRE_VERSION)
+    }
+
+    /// Creates a reader for bytes written in the wire format of `version`.
+    pub fn with_version(buf: &'a [u8], version: u8) -> Self {
+        Self { buf, version }
+    }
+
+    /// Returns the wire format version of the bytes being read.
+    pub fn version(&self) -> u8 {
+        self.version
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.buf.is_empty()
+    }
+
+    fn take(&mut self, n: usize) -> Result<&'a [u8], DatatypeError> {
+        if self.buf.len() < n {
+            return Err(DatatypeError::FailedToDeserialize(format!(
+                "need {n} bytes, but only {} bytes remain",
+                self.buf.len()
+            )));
+        }
+        let (head, tail) = self.buf.split_at(n);
+        self.buf = tail;
+        Ok(head)
+    }
+
+    fn take_array<const N: usize>(&mut self) -> Result<[u8; N], DatatypeError> {
+        let mut array = [0u8; N];
+        array.copy_from_slice(self.take(N)?);
+        Ok(array)
+    }
+
+    pub fn read_u8(&mut self) -> Result<u8, DatatypeError> {
+        Ok(self.take(1)?[0])
+    }
+
+    pub fn read_bool(&mut self) -> Result<bool, DatatypeError> {
+        match self.read_u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            v => Err(DatatypeError::FailedToDeserialize(format!(
+                "invalid bool: {v}"
+            ))),
+        }
+    }
+
+    pub fn read_u32(&mut self) -> Result<u32, DatatypeError> {
+        Ok(u32::from_le_bytes(self.take_array()?))
+    }
+
+    pub fn read_u64(&mut self) -> Result<u64, DatatypeError> {
+        Ok(u64::from_le_bytes(self.take_array()?))
+    }
+
+    pub fn read_i64(&mut self) -> Result<i64, DatatypeError> {
+        Ok(i64::from_le_bytes(self.take_array()?))
+    }
+
+    pub fn read_f64(&mut self) -> Result<f64, DatatypeError> {
+        Ok(f64::from_le_bytes(self.take_array()?))
+    }
+
+    pub fn read_bytes(&mut self) -> Result<&'a [u8], DatatypeError> {
+        let len = self.read_u32()? as usize;
+        self.take(len)
+    }
+
+    pub fn read_string(&mut self) -> Result<String, DatatypeError> {
+        let bytes = self.read_bytes()?;
+        String::from_utf8(bytes.to_vec())
+            .map_err(|e| DatatypeError::FailedToDeserialize(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests_bytes {
+    use crate::utils::bytes::{BytesReader, BytesWriter};
+
+    #[test]
+    fn can_write_and_read_bytes() {
+        let mut writer = BytesWriter::new();
+        writer.write_u8(1);
+        writer.write_bool(true);
+        writer.write_u32(2);
+        writer.write_u64(3);
+        writer.write_i64(-4);
+        writer.write_f64(5.5);
+        writer.write_str("six");
+        writer.write_bytes(&[7, 7]);
+        let bytes = writer.into_bytes();
+
+        let mut reader = BytesReader::new(&bytes);
+        assert_eq!(1, reader.read_u8().unwrap());
+        assert!(reader.read_bool().unwrap());
+        assert_eq!(2, reader.read_u32().unwrap());
+        assert_eq!(3, reader.read_u64().unwrap());
+        assert_eq!(-4, reader.read_i64().unwrap());
+        assert_eq!(5.5, reader.read_f64().unwrap());
+        assert_eq!("six", reader.read_string().unwrap());
+        assert_eq!(&[7, 7], reader.read_bytes().unwrap());
+        assert!(reader.is_empty());
+        assert!(reader.read_u8().is_err());
+        assert!(BytesReader::new(&[2]).read_bool().is_err());
+    }
+}
@@ -0,0 +1,115 @@
+//! Helpers for the versioned binary wire format.
+//!
+//! Every encoded value starts with a header of the magic bytes `SY`, the format version,
+//! and a kind byte that identifies the encoded type, followed by the body written by the
+//! type itself with a [`BytesWriter`].
+
+use crate::{
+    DatatypeError,
+    utils::bytes::{BytesReader, BytesWriter},
+};
+
+const MAGIC: [u8; 2] = *b"SY";
+/// The version written by [`encode`]; [`decode`] also reads bytes of the older versions.
+///
+/// - 1: the initial format.
+/// - 2: adds the group of a transaction, and the push queue of a datatype snapshot.
+/// - 3: adds the remote cseqs of an exported snapshot.
+pub const WIRE_VERSION: u8 = 3;
+/// The first version encoding [`Transaction::group`](crate::Transaction::group).
+pub const WIRE_VERSION_TX_GROUP: u8 = 2;
+/// The first version encoding the pending transactions checkpointed in a datatype
+/// snapshot.
+pub const WIRE_VERSION_PUSH_QUEUE: u8 = 2;
+/// The first version encoding the cseqs of the clients whose transactions an exported
+/// snapshot includes.
+pub const WIRE_VERSION_EXPORTED_CSEQS: u8 = 3;
+const MIN_WIRE_VERSION: u8 = 1;
+
+pub const KIND_UID: u8 = 1;
+pub const KIND_OPERATION_ID: u8 = 2;
+pub const KIND_OPERATION: u8 = 3;
+pub const KIND_TRANSACTION: u8 = 4;
+pub const KIND_DATATYPE_RECORD: u8 = 5;
+pub const KIND_EXPORTED_SNAPSHOT: u8 = 6;
+
+/// Encodes a value of `kind` with the header.
+pub fn encode(kind: u8, write: impl FnOnce(&mut BytesWriter)) -> Vec<u8> {
+    let mut writer = BytesWriter::new();
+    writer.write_u8(MAGIC[0]);
+    writer.write_u8(MAGIC[1]);
+    writer.write_u8(WIRE_VERSION);
+    writer.write_u8(kind);
+    write(&mut writer);
+    writer.into_bytes()
+}
+
+/// Decodes a value of `kind`, validating the header and rejecting trailing bytes.
+///
+/// `read` is given a reader of the version in the header, so that it can read the
+/// layout of an older version.
+pub fn decode<T>(
+    bytes: &[u8],
+    kind: u8,
+    read: impl FnOnce(&mut BytesReader) -> Result<T, DatatypeError>,
+) -> Result<T, DatatypeError> {
+    let mut reader = BytesReader::new(bytes);
+    let magic = [reader.read_u8()?, reader.read_u8()?];
+    if magic != MAGIC {
+        return Err(DatatypeError::FailedToDeserialize(format!(
+            "invalid magic bytes: {magic:?}"
+        )));
+    }
+    let version = reader.read_u8()?;
+    if !(MIN_WIRE_VERSION..=WIRE_VERSION).contains(&version) {
+        return Err(DatatypeError::FailedToDeserialize(format!(
+            "unsupported wire version: {version}"
+        )));
+    }
+    // the rest is read in the layout of the version.
+    let mut reader = BytesReader::with_version(&bytes[3..], version);
+    let actual = reader.read_u8()?;
+    if actual != kind {
+        return Err(DatatypeError::FailedToDeserialize(format!(
+            "expected kind {kind}, but got {actual}"
+        )));
+    }
+    let value = read(&mut reader)?;
+    if !reader.is_empty() {
+        return Err(DatatypeError::FailedToDeserialize(
+            "trailing bytes".to_owned(),
+        ));
+    }
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests_codec {
+    use rstest::rstest;
+
+    use crate::{
+        DatatypeError,
+        utils::codec::{KIND_OPERATION, KIND_UID, WIRE_VERSION, decode, encode},
+    };
+
+    #[test]
+    fn can_encode_and_decode_with_header() {
+        let bytes = encode(KIND_UID, |w| w.write_u64(7));
+        assert_eq!(&bytes[..4], &[b'S', b'Y', WIRE_VERSION, KIND_UID]);
+        assert_eq!(decode(&bytes, KIND_UID, |r| r.read_u64()).unwrap(), 7);
+    }
+
+    #[rstest]
+    #[case::empty(vec![])]
+    #[case::invalid_magic(vec![b'X', b'Y', 1, 1, 7, 0, 0, 0, 0, 0, 0, 0])]
+    #[case::unsupported_version(vec![b'S', b'Y', 99, 1, 7, 0, 0, 0, 0, 0, 0, 0])]
+    #[case::different_kind(vec![b'S', b'Y', 1, KIND_OPERATION, 7, 0, 0, 0, 0, 0, 0, 0])]
+    #[case::truncated(vec![b'S', b'Y', 1, 1, 7, 0])]
+    #[case::trailing(vec![b'S', b'Y', 1, 1, 7, 0, 0, 0, 0, 0, 0, 0, 0])]
+    fn can_reject_malformed_input(#[case] bytes: Vec<u8>) {
+        assert_eq!(
+            decode(&bytes, KIND_UID, |r| r.read_u64()).unwrap_err(),
+            DatatypeError::FailedToDeserialize("".into())
+        );
+    }
+}
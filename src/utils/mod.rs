@@ -1,3 +1,6 @@
+pub mod bytes;
+pub mod codec;
 pub mod defer_guard;
 pub mod no_guard_mutex;
 pub mod runtime;
+pub mod unwind;
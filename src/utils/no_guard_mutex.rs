@@ -1,6 +1,8 @@
+use std::time::Instant;
+
 use parking_lot::{
     RawMutex,
-    lock_api::{RawMutex as _, RawMutexFair},
+    lock_api::{RawMutex as _, RawMutexFair, RawMutexTimed},
 };
 
 pub struct NoGuardMutex {
@@ -18,6 +20,18 @@ impl NoGuardMutex {
         self.lock.lock();
     }
 
+    /// Locks the mutex unless `deadline` passes first, and returns whether it is locked.
+    /// Without a `deadline`, it waits as long as it takes like [`Self::lock`].
+    pub fn lock_until(&self, deadline: Option<Instant>) -> bool {
+        match deadline {
+            Some(deadline) => self.lock.try_lock_until(deadline),
+            None => {
+                self.lock.lock();
+                true
+            }
+        }
+    }
+
     pub fn unlock(&self) {
         if self.lock.is_locked() {
             unsafe { self.lock.unlock_fair() }
@@ -49,7 +63,7 @@ mod tests_no_guard_mutex {
             Arc,
             atomic::{AtomicI32, Ordering},
         },
-        time::Duration,
+        time::{Duration, Instant},
     };
 
     use crate::utils::no_guard_mutex::NoGuardMutex;
@@ -70,6 +84,17 @@ mod tests_no_guard_mutex {
         assert!(ng_mutex.is_locked());
     }
 
+    #[test]
+    fn can_lock_until_deadline() {
+        let ng_mutex = NoGuardMutex::new();
+        assert!(ng_mutex.lock_until(None));
+        let started = Instant::now();
+        assert!(!ng_mutex.lock_until(Some(started + Duration::from_millis(50))));
+        assert!(started.elapsed() >= Duration::from_millis(50));
+        ng_mutex.unlock();
+        assert!(ng_mutex.lock_until(Some(Instant::now())));
+    }
+
     #[tokio::test(flavor = "multi_thread", worker_threads = 10)]
     async fn can_lock_and_unlock_in_threads() {
         let ng_mutex = Arc::new(NoGuardMutex::new());
@@ -31,6 +31,22 @@ pub fn get_or_init_runtime(group: &str) -> Arc<Runtime> {
     }
 }
 
+/// Runs the blocking `func` on a thread of the runtime of `group`, and awaits its result
+/// without blocking the executor of the caller.
+///
+/// A panic in `func` is resumed in the caller.
+pub async fn run_blocking<F, R>(group: &str, func: F) -> R
+where
+    F: FnOnce() -> R + Send + 'static,
+    R: Send + 'static,
+{
+    match get_or_init_runtime(group).spawn_blocking(func).await {
+        Ok(ret) => ret,
+        Err(e) if e.is_panic() => std::panic::resume_unwind(e.into_panic()),
+        Err(e) => panic!("failed to run a blocking task on '{group}': {e}"),
+    }
+}
+
 #[allow(dead_code)]
 pub fn close_runtime(group: &str) {
     if let Some(map) = RUNTIME_MAP.get() {
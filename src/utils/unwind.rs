@@ -0,0 +1,39 @@
+use std::panic::{self, AssertUnwindSafe};
+
+/// Calls `f`, catching its panic as the error of the panic message.
+///
+/// It is used to keep a datatype usable when a function given by the user panics in a
+/// transaction; `f` is asserted to be unwind-safe, since the transaction is rolled back
+/// after the panic.
+pub fn catch_unwind<R>(f: impl FnOnce() -> R) -> Result<R, String> {
+    panic::catch_unwind(AssertUnwindSafe(f)).map_err(|payload| match payload.downcast::<String>() {
+        Ok(msg) => *msg,
+        Err(payload) => match payload.downcast::<&str>() {
+            Ok(msg) => msg.to_string(),
+            Err(_) => "unknown panic".to_owned(),
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests_unwind {
+    use crate::utils::unwind::catch_unwind;
+
+    #[test]
+    fn can_catch_panic_messages() {
+        assert_eq!(catch_unwind(|| 1), Ok(1));
+        assert_eq!(
+            catch_unwind(|| panic!("static")),
+            Err::<(), _>("static".into())
+        );
+        let n = 2;
+        assert_eq!(
+            catch_unwind(|| panic!("formatted {n}")),
+            Err::<(), _>("formatted 2".into())
+        );
+        assert_eq!(
+            catch_unwind(|| std::panic::panic_any(3)),
+            Err::<(), _>("unknown panic".into())
+        );
+    }
+}